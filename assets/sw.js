@@ -4,6 +4,7 @@ var filesToCache = [
   './index.html',
   './ak_riic_tools.js',
   './ak_riic_tools_bg.wasm',
+  './worker.js',
 ];
 
 /* Start the service worker and cache all of the app's content */
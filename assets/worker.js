@@ -0,0 +1,10 @@
+// Runs simulations off the page's thread. The page posts a JSON request, and the worker posts
+// back progress and the output as JSON.
+import init, { run_simulation_worker } from "./ak_riic_tools.js";
+
+const ready = init();
+
+self.onmessage = async (event) => {
+  await ready;
+  run_simulation_worker(event.data);
+};
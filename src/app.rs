@@ -1,25 +1,177 @@
 use egui::Ui;
 use std::cmp::{max, min};
 
+use self::background::{Calculation, CalculationOutput, SimulationJob};
 use self::toggle_switch::toggle;
+mod background;
+pub mod base_sim;
+pub mod factory_sim;
+mod names;
+pub mod roster;
 pub mod toggle_switch;
 pub mod trading_sim;
+use base_sim::*;
+use factory_sim::*;
+use roster::*;
 use trading_sim::*;
 
+#[cfg(target_arch = "wasm32")]
+pub use background::run_simulation_worker;
+
+/// Part of the base planner shown.
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Eq, Clone, Copy)]
+enum View {
+    TradingPost,
+    BaseLayouts,
+    Roster,
+}
+
 #[derive(serde::Deserialize, serde::Serialize)]
 #[serde(default)]
 pub struct TradingPostProductionApp {
+    view: View,
     input: TradingPostProductionInput,
     output: TradingPostProductionOutput,
     is_editing: bool,
+    is_day_plan: bool,
+    /// Shifts of the day plan, in the order they run.
+    day_plan: Vec<TradingPostProductionInput>,
+    selected_shift: usize,
+    day_output: TradingPostDayOutput,
+    /// Whether several trading posts run side by side, each with its own input.
+    is_posts: bool,
+    posts: Vec<TradingPostProductionInput>,
+    selected_post: usize,
+    posts_output: TradingPostsOutput,
+    /// Game data JSON to use instead of the bundled data, if any.
+    game_data_override: Option<String>,
+    #[serde(skip)]
+    game_data: TradingPostGameData,
+    #[serde(skip)]
+    game_data_text: String,
+    #[serde(skip)]
+    game_data_error: Option<String>,
+    /// Calculation in progress, if any.
+    #[serde(skip)]
+    job: Option<SimulationJob>,
+    /// `inputs_key` when the calculation in progress started.
+    #[serde(skip)]
+    job_inputs: String,
+    /// Heatmap of the stored order counts over the output's timeline, drawn once per output.
+    #[serde(skip)]
+    stored_chart: Option<egui::TextureHandle>,
+    /// Sweep of the shown input over speeds and shift lengths, for the shift length heatmap.
+    #[serde(skip)]
+    shift_lengths: Option<SweepResult>,
+    /// `shift_lengths_key` when the shift lengths were calculated.
+    #[serde(skip)]
+    shift_lengths_inputs: String,
+    /// Shift length sweep in progress, if any.
+    #[serde(skip)]
+    shift_lengths_job: Option<SimulationJob>,
+    /// `shift_lengths_key` when the sweep in progress started.
+    #[serde(skip)]
+    shift_lengths_job_inputs: String,
+    /// Whether the shift length heatmap shows daily LMD rather than stall chance.
+    heatmap_lmd: bool,
+    /// Field to solve for, and the stall limit to keep within.
+    solver_field: SweepField,
+    solver_limit: StallLimit,
+    /// Outcome of the last solve.
+    #[serde(skip)]
+    solution: Option<Result<Option<Solution>, SimError>>,
+    /// Solve in progress, if any.
+    #[serde(skip)]
+    solver_job: Option<SimulationJob>,
+    /// `solver_key` when the solve in progress started.
+    #[serde(skip)]
+    solver_job_inputs: String,
+    /// Drones to plan the use of.
+    drone_budget: i32,
+    /// Outcome of the last drone plan.
+    #[serde(skip)]
+    drone_plan: Option<Result<DronePlan, SimError>>,
+    /// Drone plan in progress, if any.
+    #[serde(skip)]
+    drone_job: Option<SimulationJob>,
+    /// `drone_plan_key` when the plan in progress started.
+    #[serde(skip)]
+    drone_job_inputs: String,
+    /// Gold factories that supply the trading post, all alike.
+    factory: FactoryInput,
+    factory_count: i32,
+    /// Base layouts compared, building by building.
+    base_layouts: Vec<BaseLayout>,
+    /// Code of the next layout to add, such as `243`.
+    new_layout_code: String,
+    /// Outcome of the last comparison, by layout name.
+    #[serde(skip)]
+    layouts: Vec<(String, Result<BaseOutput, BaseError>)>,
+    /// Comparison in progress, if any.
+    #[serde(skip)]
+    layouts_job: Option<SimulationJob>,
+    /// `layouts_key` when the comparison in progress started.
+    #[serde(skip)]
+    layouts_job_inputs: String,
+    /// Operators owned, which the operator pickers draw from.
+    roster: Roster,
 }
 
 impl Default for TradingPostProductionApp {
     fn default() -> Self {
         Self {
+            view: View::TradingPost,
             input: TradingPostProductionInput::default(),
             output: TradingPostProductionOutput::default(),
             is_editing: true,
+            is_day_plan: false,
+            day_plan: vec![],
+            selected_shift: 0,
+            day_output: TradingPostDayOutput::default(),
+            is_posts: false,
+            posts: vec![],
+            selected_post: 0,
+            posts_output: TradingPostsOutput::default(),
+            game_data_override: None,
+            game_data: TradingPostGameData::bundled(),
+            game_data_text: String::new(),
+            game_data_error: None,
+            job: None,
+            job_inputs: String::new(),
+            stored_chart: None,
+            shift_lengths: None,
+            shift_lengths_inputs: String::new(),
+            shift_lengths_job: None,
+            shift_lengths_job_inputs: String::new(),
+            heatmap_lmd: false,
+            solver_field: SweepField::DurationMinutes,
+            solver_limit: StallLimit::Chance(5.0),
+            solution: None,
+            solver_job: None,
+            solver_job_inputs: String::new(),
+            drone_budget: 20,
+            drone_plan: None,
+            drone_job: None,
+            drone_job_inputs: String::new(),
+            factory: FactoryInput::default(),
+            factory_count: 1,
+            base_layouts: ["243", "252", "153"]
+                .iter()
+                .map(|code| {
+                    BaseLayout::standard(
+                        code,
+                        &TradingPostProductionInput::default(),
+                        &FactoryInput::default(),
+                        &PowerPlant::default(),
+                    )
+                    .expect("standard layouts fit the base")
+                })
+                .collect(),
+            new_layout_code: "243".to_string(),
+            layouts: vec![],
+            layouts_job: None,
+            layouts_job_inputs: String::new(),
+            roster: Roster::default(),
         }
     }
 }
@@ -32,29 +184,600 @@ impl TradingPostProductionApp {
 
         // Load previous app state (if any).
         // Note that you must enable the `persistence` feature for this to work.
-        if let Some(storage) = cc.storage {
-            return eframe::get_value(storage, eframe::APP_KEY).unwrap_or_default();
+        let mut app: Self = match cc.storage {
+            Some(storage) => eframe::get_value(storage, eframe::APP_KEY).unwrap_or_default(),
+            None => Default::default(),
+        };
+        app.apply_game_data(app.game_data_override.clone());
+        app
+    }
+
+    /// Switches to the game data in `json`, or back to the bundled data for `None`. Invalid JSON
+    /// is reported and leaves the current data in place.
+    fn apply_game_data(&mut self, json: Option<String>) {
+        let game_data = match &json {
+            Some(json) => TradingPostGameData::from_json(json),
+            None => Ok(TradingPostGameData::bundled()),
+        };
+        match game_data {
+            Ok(game_data) => {
+                self.game_data_text = game_data.to_json();
+                self.game_data = game_data;
+                self.game_data_override = json;
+                self.game_data_error = None;
+            }
+            Err(error) => self.game_data_error = Some(error.to_string()),
+        }
+    }
+
+    /// Everything a calculation depends on, to tell when one in progress has gone stale.
+    fn inputs_key(&self) -> String {
+        serde_json::to_string(&(
+            self.is_day_plan,
+            &self.input,
+            &self.day_plan,
+            self.is_posts,
+            &self.posts,
+            &self.game_data,
+        ))
+        .unwrap_or_default()
+    }
+
+    /// The input shown, which is that of the selected shift in a day plan, or of the selected
+    /// post when several run side by side.
+    fn shown_input(&self) -> &TradingPostProductionInput {
+        if let Some(post) = self.posts.get(self.selected_post) {
+            if self.is_posts {
+                return post;
+            }
+        }
+        match self.day_plan.get(self.selected_shift) {
+            Some(shift) if self.is_day_plan => shift,
+            _ => &self.input,
+        }
+    }
+
+    /// Everything the shift length heatmap of the shown input depends on, but for the speed and
+    /// duration it is drawn over.
+    fn shift_lengths_key(&self) -> String {
+        let input = TradingPostProductionInput {
+            speed100: 0,
+            speed_breakdown: SpeedBreakdown::default(),
+            duration_minutes: 0,
+            gold_supply: None,
+            ..self.shown_input().clone()
+        };
+        serde_json::to_string(&(&input, &self.game_data)).unwrap_or_default()
+    }
+
+    /// Everything a solve depends on.
+    fn solver_key(&self) -> String {
+        serde_json::to_string(&(
+            self.shown_input(),
+            &self.game_data,
+            self.solver_field,
+            self.solver_limit,
+        ))
+        .unwrap_or_default()
+    }
+
+    /// Everything a drone plan depends on, but for the drone usages it replaces.
+    fn drone_plan_key(&self) -> String {
+        let input = TradingPostProductionInput {
+            drone_usages: vec![],
+            ..self.shown_input().clone()
+        };
+        serde_json::to_string(&(&input, &self.game_data, self.drone_budget)).unwrap_or_default()
+    }
+
+    /// The layouts compared, with the game data in use.
+    fn layouts_to_compare(&self) -> Vec<BaseLayout> {
+        let mut layouts = self.base_layouts.clone();
+        for trading_post in layouts.iter_mut().flat_map(|l| l.trading_posts.iter_mut()) {
+            trading_post.game_data = self.game_data.clone();
+        }
+        layouts
+    }
+
+    /// Everything a comparison of base layouts depends on.
+    fn layouts_key(&self) -> String {
+        serde_json::to_string(&(&self.base_layouts, &self.game_data)).unwrap_or_default()
+    }
+
+    fn timelines_mut(&mut self) -> impl Iterator<Item = &mut ShiftTimeline> {
+        std::iter::once(&mut self.output.timeline)
+            .chain(std::iter::once(&mut self.day_output.day.timeline))
+            .chain(
+                self.day_output
+                    .shifts
+                    .iter_mut()
+                    .map(|shift| &mut shift.timeline),
+            )
+            .chain(
+                self.posts_output
+                    .trading_posts
+                    .iter_mut()
+                    .map(|post| &mut post.timeline),
+            )
+    }
+
+    /// Draws the chance of each stored order count at each minute of the output's timeline, as
+    /// an image with a column per minute and the highest count on top.
+    fn draw_stored_chart(&self, ctx: &egui::Context) -> Option<egui::TextureHandle> {
+        let stored = &self.output.timeline.stored;
+        let counts = stored.iter().map(Vec::len).max()?;
+        // long day plans are sampled down to fit in a texture
+        let width = stored.len().min(1024);
+        let mut image = egui::ColorImage::new([width, counts], egui::Color32::TRANSPARENT);
+        for x in 0..width {
+            let chances = &stored[x * stored.len() / width];
+            for (count, chance) in chances.iter().enumerate() {
+                let alpha = (chance / 100.0 * 255.0).round().clamp(0.0, 255.0) as u8;
+                image[(x, counts - 1 - count)] =
+                    egui::Color32::from_rgba_unmultiplied(66, 135, 245, alpha);
+            }
+        }
+        Some(ctx.load_texture("stored orders", image, egui::TextureFilter::Nearest))
+    }
+
+    /// Why the current inputs can't be calculated, if they can't.
+    fn calculation_error(&self) -> Option<String> {
+        if self.is_day_plan {
+            validate_day(&self.day_plan)
+                .err()
+                .map(|(index, error)| format!("Shift {}: {}", index + 1, error))
+        } else if self.is_posts {
+            self.posts.iter().enumerate().find_map(|(index, post)| {
+                let error = post.validate().err()?;
+                Some(format!("Trading post {}: {}", index + 1, error))
+            })
+        } else {
+            self.input.validate().err().map(|error| error.to_string())
+        }
+    }
+
+    /// Starts calculating the current inputs in the background, dropping any calculation already
+    /// in progress.
+    fn start_calculation(&mut self, ctx: &egui::Context) {
+        if self.calculation_error().is_some() {
+            return;
+        }
+        self.input.game_data = self.game_data.clone();
+        for shift in self.day_plan.iter_mut().chain(self.posts.iter_mut()) {
+            shift.game_data = self.game_data.clone();
+        }
+        let calculation = if self.is_day_plan {
+            Calculation::Day(self.day_plan.clone())
+        } else if self.is_posts {
+            Calculation::Posts(self.posts.clone())
+        } else {
+            Calculation::Day(vec![self.input.clone()])
+        };
+        self.job_inputs = self.inputs_key();
+        self.job = Some(SimulationJob::start(calculation, ctx));
+    }
+
+    /// Starts sweeping the shown input over speeds and shift lengths in the background.
+    fn start_shift_lengths(&mut self, ctx: &egui::Context) {
+        // the heatmap doesn't show gold shortages, which take long to estimate
+        let input = TradingPostProductionInput {
+            game_data: self.game_data.clone(),
+            gold_supply: None,
+            ..self.shown_input().clone()
+        };
+        if input.validate().is_err() {
+            return;
+        }
+        let sweep = ParameterSweep::shift_lengths(input);
+        self.shift_lengths_job_inputs = self.shift_lengths_key();
+        self.shift_lengths_job = Some(SimulationJob::start(
+            Calculation::Sweep(Box::new(sweep)),
+            ctx,
+        ));
+    }
+
+    /// Starts solving for the chosen field of the shown input in the background.
+    fn start_solver(&mut self, ctx: &egui::Context) {
+        let solver = Solver {
+            base: TradingPostProductionInput {
+                game_data: self.game_data.clone(),
+                ..self.shown_input().clone()
+            },
+            field: self.solver_field,
+            limit: self.solver_limit,
+        };
+        self.solver_job_inputs = self.solver_key();
+        self.solver_job = Some(SimulationJob::start(
+            Calculation::Solve(Box::new(solver)),
+            ctx,
+        ));
+    }
+
+    /// Starts planning the drone budget over the shown input in the background.
+    fn start_drone_plan(&mut self, ctx: &egui::Context) {
+        let planner = DronePlanner {
+            base: TradingPostProductionInput {
+                game_data: self.game_data.clone(),
+                ..self.shown_input().clone()
+            },
+            budget: self.drone_budget,
+        };
+        self.drone_job_inputs = self.drone_plan_key();
+        let calculation = Calculation::PlanDrones(Box::new(planner));
+        self.drone_job = Some(SimulationJob::start(calculation, ctx));
+    }
+
+    /// Starts comparing the base layouts in the background.
+    fn start_layouts(&mut self, ctx: &egui::Context) {
+        let layouts = self.layouts_to_compare();
+        self.layouts_job_inputs = self.layouts_key();
+        self.layouts_job = Some(SimulationJob::start(Calculation::Layouts(layouts), ctx));
+    }
+
+    /// Picks up the output of the calculations in progress once they are done, and drops those
+    /// whose inputs have changed since they started.
+    fn poll_calculation(&mut self) {
+        if let Some(job) = &self.job {
+            if self.inputs_key() != self.job_inputs {
+                self.job = None;
+            } else {
+                match job.take_output() {
+                    Some(CalculationOutput::Day(day_output)) => {
+                        self.stored_chart = None;
+                        self.output = day_output.day.clone();
+                        if self.is_day_plan {
+                            self.day_output = *day_output;
+                        }
+                        self.job = None;
+                    }
+                    Some(CalculationOutput::Posts(posts_output)) => {
+                        self.stored_chart = None;
+                        let post = posts_output.trading_posts.get(self.selected_post);
+                        self.output = post.cloned().unwrap_or_default();
+                        self.posts_output = *posts_output;
+                        self.job = None;
+                    }
+                    _ => {}
+                }
+            }
+        }
+        if let Some(job) = &self.shift_lengths_job {
+            if self.shift_lengths_key() != self.shift_lengths_job_inputs {
+                self.shift_lengths_job = None;
+            } else if let Some(CalculationOutput::Sweep(sweep)) = job.take_output() {
+                self.shift_lengths = Some(sweep);
+                self.shift_lengths_inputs = std::mem::take(&mut self.shift_lengths_job_inputs);
+                self.shift_lengths_job = None;
+            }
         }
+        if let Some(job) = &self.solver_job {
+            if self.solver_key() != self.solver_job_inputs {
+                self.solver_job = None;
+            } else if let Some(CalculationOutput::Solve(solution)) = job.take_output() {
+                self.solution = Some(*solution);
+                self.solver_job = None;
+            }
+        }
+        if let Some(job) = &self.drone_job {
+            if self.drone_plan_key() != self.drone_job_inputs {
+                self.drone_job = None;
+            } else if let Some(CalculationOutput::PlanDrones(plan)) = job.take_output() {
+                self.drone_plan = Some(*plan);
+                self.drone_job = None;
+            }
+        }
+        if let Some(job) = &self.layouts_job {
+            if self.layouts_key() != self.layouts_job_inputs {
+                self.layouts_job = None;
+            } else if let Some(CalculationOutput::Layouts(layouts)) = job.take_output() {
+                let names = self.base_layouts.iter().map(|layout| layout.name.clone());
+                self.layouts = names.zip(layouts).collect();
+                self.layouts_job = None;
+            }
+        }
+    }
+    /// Compares base layouts side by side, each simulated in full.
+    fn show_base_layouts(&mut self, ctx: &egui::Context) {
+        // trading posts a layout can copy
+        let mut sources = vec![("Shown".to_string(), self.shown_input().clone())];
+        sources.extend(
+            self.posts
+                .iter()
+                .enumerate()
+                .map(|(index, post)| (format!("Side by side {}", index + 1), post.clone())),
+        );
+        let standard = BaseLayout::standard(
+            &self.new_layout_code,
+            &sources[0].1,
+            &self.factory,
+            &PowerPlant::default(),
+        );
+        let factory = &self.factory;
+        let mut compare = false;
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.heading("Base layouts");
+            ui.label(
+                "Every building has an input of its own. Trading posts are copied from the \
+                 trading post shown or those side by side. New layouts start from a code such as \
+                 243, with trading posts like the one shown and factories like its gold \
+                 factories, one making gold for each trading post and the rest battle records.",
+            );
+            ui.horizontal(|ui| {
+                ui.label("New layout:");
+                ui.add(egui::TextEdit::singleline(&mut self.new_layout_code).desired_width(30.0));
+                if ui
+                    .add_enabled(standard.is_ok(), egui::Button::new("➕"))
+                    .clicked()
+                {
+                    if let Ok(layout) = &standard {
+                        self.base_layouts.push(layout.clone());
+                    }
+                }
+                if let Err(error) = &standard {
+                    ui.colored_label(egui::Color32::RED, error.to_string());
+                }
+            });
+            let mut index = 0;
+            self.base_layouts.retain_mut(|layout| {
+                index += 1;
+                let mut retained = true;
+                ui.horizontal(|ui| {
+                    ui.add(egui::TextEdit::singleline(&mut layout.name).desired_width(60.0));
+                    ui.label(format!("{}/{} rooms", layout.rooms(), LEFT_SIDE_ROOMS));
+                    if ui.button("❌").clicked() {
+                        retained = false;
+                    }
+                });
+                egui::CollapsingHeader::new("Buildings")
+                    .id_source(("layout buildings", index))
+                    .show(ui, |ui| edit_layout(ui, index, layout, &sources, factory));
+                retained
+            });
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(!self.base_layouts.is_empty(), egui::Button::new("Compare"))
+                    .clicked()
+                {
+                    compare = true;
+                }
+                if let Some(running) = &self.layouts_job {
+                    ui.add(
+                        egui::ProgressBar::new(running.progress())
+                            .desired_width(200.0)
+                            .show_percentage(),
+                    );
+                    if ui.button("Cancel").clicked() {
+                        self.layouts_job = None;
+                    }
+                }
+            });
 
-        Default::default()
+            // label of each row, and how to show it for a layout
+            type Row = (&'static str, fn(&BaseOutput) -> String);
+            let rows: [Row; 8] = [
+                ("LMD a day", |base| format!("{:.0}", base.daily_lmd)),
+                ("A trading post stalls", |base| {
+                    format!("{:.1}%", base.trading_posts.stall_chance)
+                }),
+                ("EXP a day", |base| format!("{:.0}", base.daily_exp)),
+                ("Shards a day", |base| format!("{:.1}", base.daily_shards)),
+                ("Gold a day, net", |base| format!("{:+.1}", base.gold.net())),
+                ("Drones a day", |base| format!("{:.0}", base.daily_drones)),
+                ("Drones spent a day", |base| {
+                    format!("{:.1}", base.daily_drones_used)
+                }),
+                ("Power used", |base| {
+                    format!("{}/{}", base.power_used, base.power_supplied)
+                }),
+            ];
+            egui::Grid::new("base layouts")
+                .num_columns(self.layouts.len() + 1)
+                .spacing([16.0, 4.0])
+                .show(ui, |ui| {
+                    ui.label("Layout");
+                    for (name, _) in self.layouts.iter() {
+                        ui.strong(name);
+                    }
+                    ui.end_row();
+                    for (name, cell) in rows {
+                        ui.label(name);
+                        for (_, layout) in self.layouts.iter() {
+                            ui.label(layout.as_ref().map_or_else(|_| "-".to_string(), cell));
+                        }
+                        ui.end_row();
+                    }
+                });
+            for (name, layout) in self.layouts.iter() {
+                match layout {
+                    Ok(base) if !base.has_power() => {
+                        let text = format!("{}: the power plants don't supply enough power.", name);
+                        ui.colored_label(egui::Color32::YELLOW, text);
+                    }
+                    Ok(base) if !base.gold.keeps_up() => {
+                        let text = format!("{}: the gold factories don't keep up.", name);
+                        ui.colored_label(egui::Color32::YELLOW, text);
+                    }
+                    Ok(_) => {}
+                    Err(error) => {
+                        let text = format!("{}: {}", name, error);
+                        ui.colored_label(egui::Color32::RED, text);
+                    }
+                }
+            }
+        });
+        if compare {
+            self.start_layouts(ctx);
+        }
+    }
+
+    /// Edits the operators owned.
+    fn show_roster(&mut self, ctx: &egui::Context) {
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.heading("Roster");
+            ui.label(
+                "Operator pickers only offer the operators owned, up to the elite phase they are \
+                 at. While the roster is empty, they offer every operator.",
+            );
+            egui::Grid::new("roster")
+                .num_columns(5)
+                .spacing([8.0, 4.0])
+                .show(ui, |ui| {
+                    ui.label("Operator");
+                    ui.label("Elite");
+                    ui.label("Level");
+                    ui.label("Tailoring");
+                    ui.end_row();
+                    let mut i = 0;
+                    self.roster.operators.retain_mut(|operator| {
+                        let mut retained = true;
+                        ui.add(egui::TextEdit::singleline(&mut operator.name).desired_width(110.0));
+                        egui::ComboBox::from_id_source(format!("roster elite{}", i))
+                            .selected_text(operator.elite.to_string())
+                            .show_ui(ui, |ui| {
+                                for phase in [
+                                    HighRarityOperatorPhase::E0,
+                                    HighRarityOperatorPhase::E1,
+                                    HighRarityOperatorPhase::E2,
+                                ] {
+                                    let text = phase.to_string();
+                                    ui.selectable_value(&mut operator.elite, phase, text);
+                                }
+                            });
+                        ui.add(egui::DragValue::new(&mut operator.level).clamp_range(1..=90));
+                        let tailoring = &mut operator.tailoring;
+                        egui::ComboBox::from_id_source(format!("roster tailoring{}", i))
+                            .selected_text(
+                                tailoring
+                                    .as_ref()
+                                    .map_or("None".to_string(), |skill| skill.to_string()),
+                            )
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(tailoring, None, "None");
+                                for skill in [
+                                    TradingPostTailoringSkill::Alpha,
+                                    TradingPostTailoringSkill::Beta,
+                                ] {
+                                    let text = skill.to_string();
+                                    ui.selectable_value(tailoring, Some(skill), text);
+                                }
+                            });
+                        if ui.button("❌").clicked() {
+                            retained = false;
+                        }
+                        ui.end_row();
+                        i += 1;
+                        retained
+                    });
+                });
+            ui.horizontal(|ui| {
+                if ui.button("➕").clicked() {
+                    self.roster.operators.push(OwnedOperator::default());
+                }
+                for name in NAMED_OPERATORS {
+                    if self.roster.get(name).is_none()
+                        && ui.button(format!("➕ {}", name)).clicked()
+                    {
+                        self.roster.operators.push(OwnedOperator {
+                            name: name.to_string(),
+                            ..OwnedOperator::default()
+                        });
+                    }
+                }
+            });
+        });
     }
 }
 
 impl eframe::App for TradingPostProductionApp {
     /// Called by the frame work to save state before shutdown.
     fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        // timelines are large, and only drawn for outputs calculated since the app started
+        let timelines: Vec<ShiftTimeline> = self.timelines_mut().map(std::mem::take).collect();
         eframe::set_value(storage, eframe::APP_KEY, self);
+        for (timeline, saved) in self.timelines_mut().zip(timelines) {
+            *timeline = saved;
+        }
     }
 
     /// Called each time the UI needs repainting, which may be many times per second.
     /// Put your widgets into a `SidePanel`, `TopPanel`, `CentralPanel`, `Window` or `Area`.
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.poll_calculation();
+        egui::TopBottomPanel::top("view").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.selectable_value(&mut self.view, View::TradingPost, "Trading post");
+                ui.selectable_value(&mut self.view, View::BaseLayouts, "Base layouts");
+                ui.selectable_value(&mut self.view, View::Roster, "Roster");
+            });
+        });
+        match self.view {
+            View::TradingPost => {}
+            View::BaseLayouts => {
+                self.show_base_layouts(ctx);
+                return;
+            }
+            View::Roster => {
+                self.show_roster(ctx);
+                return;
+            }
+        }
+        let calculation_error = self.calculation_error();
+        let shift_lengths_stale =
+            self.shift_lengths.is_some() && self.shift_lengths_key() != self.shift_lengths_inputs;
+        if self.stored_chart.is_none() {
+            self.stored_chart = self.draw_stored_chart(ctx);
+        }
+
         let Self {
+            view: _,
             input,
             output,
             is_editing: _,
+            is_day_plan,
+            day_plan,
+            selected_shift,
+            day_output,
+            is_posts,
+            posts,
+            selected_post,
+            posts_output,
+            game_data_override: _,
+            game_data,
+            game_data_text,
+            game_data_error,
+            job,
+            job_inputs: _,
+            stored_chart,
+            shift_lengths,
+            shift_lengths_inputs: _,
+            shift_lengths_job,
+            shift_lengths_job_inputs: _,
+            heatmap_lmd,
+            solver_field,
+            solver_limit,
+            solution,
+            solver_job,
+            solver_job_inputs: _,
+            drone_budget,
+            drone_plan,
+            drone_job,
+            drone_job_inputs: _,
+            factory,
+            factory_count,
+            base_layouts: _,
+            new_layout_code: _,
+            layouts: _,
+            layouts_job: _,
+            layouts_job_inputs: _,
+            roster,
         } = self;
 
+        // game data to switch to once the panel is drawn
+        let mut new_game_data = None;
+        let mut calculate = false;
+        let mut calculate_shift_lengths = false;
+        let mut solve = false;
+        let mut plan_drones = false;
         egui::CentralPanel::default().show(ctx, |ui| {
             ui.heading("TP calc");
 
@@ -62,8 +785,80 @@ impl eframe::App for TradingPostProductionApp {
                 ui.label("Edit Mode");
                 ui.add(toggle(&mut self.is_editing));
                 // ui.toggle_value(is_editing, "Edit Mode");
+                ui.label("Day plan");
+                if ui.add(toggle(is_day_plan)).changed() && *is_day_plan {
+                    *is_posts = false;
+                }
+                ui.label("Trading posts");
+                if ui.add(toggle(is_posts)).changed() && *is_posts {
+                    *is_day_plan = false;
+                }
             });
 
+            // day plan shifts
+            if *is_day_plan {
+                if day_plan.is_empty() {
+                    day_plan.push(input.clone());
+                }
+                ui.horizontal(|ui| {
+                    for i in 0..day_plan.len() {
+                        ui.selectable_value(selected_shift, i, format!("Shift {}", i + 1));
+                    }
+                    if self.is_editing {
+                        if ui.button("➕").clicked() {
+                            day_plan.insert(*selected_shift + 1, day_plan[*selected_shift].clone());
+                            *selected_shift += 1;
+                        }
+                        if day_plan.len() > 1 && ui.button("❌").clicked() {
+                            day_plan.remove(*selected_shift);
+                        }
+                    }
+                });
+                *selected_shift = (*selected_shift).min(day_plan.len() - 1);
+            }
+            // trading posts run side by side
+            if *is_posts {
+                if posts.is_empty() {
+                    posts.push(input.clone());
+                }
+                ui.horizontal(|ui| {
+                    for i in 0..posts.len() {
+                        let tab = ui.selectable_value(selected_post, i, format!("TP {}", i + 1));
+                        if let Some(post_output) = posts_output.trading_posts.get(i) {
+                            if tab.clicked() {
+                                *output = post_output.clone();
+                                *stored_chart = None;
+                            }
+                        }
+                    }
+                    if self.is_editing {
+                        if ui.button("➕").clicked() {
+                            posts.insert(*selected_post + 1, posts[*selected_post].clone());
+                            *selected_post += 1;
+                        }
+                        if posts.len() > 1 && ui.button("❌").clicked() {
+                            posts.remove(*selected_post);
+                        }
+                    }
+                });
+                *selected_post = (*selected_post).min(posts.len() - 1);
+            }
+            let input = if *is_day_plan {
+                &mut day_plan[*selected_shift]
+            } else if *is_posts {
+                &mut posts[*selected_post]
+            } else {
+                input
+            };
+            // shown next to the field it concerns
+            let input_error = input.validate().err();
+            let field_error = |concerns: fn(&SimError) -> bool| {
+                input_error
+                    .as_ref()
+                    .filter(|error| concerns(error))
+                    .map(|error| error.to_string())
+            };
+
             egui::Grid::new("tp calc")
             .num_columns(2)
             .spacing([4.0, 4.0])
@@ -127,13 +922,25 @@ impl eframe::App for TradingPostProductionApp {
                             } else {
                                 ui.add(egui::Slider::new(&mut input.capacity, 0..=38));
                             }
+                            if let Some(error) = field_error(|e| matches!(e, SimError::NonPositiveCapacity(_))) {
+                                ui.colored_label(egui::Color32::RED, error);
+                            }
                             ui.end_row();
 
                             // TP Speed
                             ui.label("TP speed: ");
                             if !&self.is_editing {
-                                ui.label(input.speed100.to_string() + "%");
-                            } else {
+                                if input.speed_breakdown.is_empty() {
+                                    ui.label(input.speed100.to_string() + "%");
+                                } else {
+                                    ui.vertical(|ui| {
+                                        for part in input.speed_breakdown.0.iter() {
+                                            ui.label(part.to_string());
+                                        }
+                                        ui.label(format!("= {:.3}%", input.speed_breakdown.speed()));
+                                    });
+                                }
+                            } else if input.speed_breakdown.is_empty() {
                                 ui.add(egui::Slider::new(&mut input.speed100, 40..=300).suffix("%"));
                                 ui.vertical_centered_justified(|ui| {
                                     if ui.button("+1").clicked() {
@@ -142,7 +949,94 @@ impl eframe::App for TradingPostProductionApp {
                                     if ui.button("-1").clicked() {
                                         input.speed100 = max(0, input.speed100 - 1);
                                     }
+                                    if ui.button("Break down").clicked() {
+                                        input.speed_breakdown = SpeedBreakdown(vec![
+                                            SpeedContribution::add("Base", 100.0),
+                                            SpeedContribution::add(
+                                                "Skills and buffs",
+                                                (input.speed100 - 100) as f64,
+                                            ),
+                                        ]);
+                                    }
                                 });
+                            } else {
+                                ui.vertical(|ui| {
+                                    egui::Grid::new("speed breakdown")
+                                        .num_columns(4)
+                                        .spacing([4.0, 4.0])
+                                        .show(ui, |ui| {
+                                            let mut i = 0;
+                                            input.speed_breakdown.0.retain_mut(|part| {
+                                                let mut retained = true;
+                                                ui.add(
+                                                    egui::TextEdit::singleline(&mut part.name)
+                                                        .desired_width(110.0),
+                                                );
+                                                egui::ComboBox::from_id_source(format!("speed effect{}", i))
+                                                    .width(30.0)
+                                                    .selected_text(match part.effect {
+                                                        SpeedEffect::Add => "+",
+                                                        SpeedEffect::Multiply => "×",
+                                                    })
+                                                    .show_ui(ui, |ui| {
+                                                        // start from no change when switching
+                                                        if ui
+                                                            .selectable_value(
+                                                                &mut part.effect,
+                                                                SpeedEffect::Add,
+                                                                "+",
+                                                            )
+                                                            .clicked()
+                                                        {
+                                                            part.value = 0.0;
+                                                        }
+                                                        if ui
+                                                            .selectable_value(
+                                                                &mut part.effect,
+                                                                SpeedEffect::Multiply,
+                                                                "×",
+                                                            )
+                                                            .clicked()
+                                                        {
+                                                            part.value = 1.0;
+                                                        }
+                                                    });
+                                                match part.effect {
+                                                    SpeedEffect::Add => ui.add(
+                                                        egui::DragValue::new(&mut part.value)
+                                                            .speed(0.1)
+                                                            .suffix("%"),
+                                                    ),
+                                                    SpeedEffect::Multiply => ui.add(
+                                                        egui::DragValue::new(&mut part.value)
+                                                            .speed(0.01)
+                                                            .clamp_range(0.0..=10.0),
+                                                    ),
+                                                };
+                                                if ui.button("❌").clicked() {
+                                                    retained = false;
+                                                }
+                                                ui.end_row();
+                                                i += 1;
+                                                retained
+                                            });
+                                        });
+                                    ui.horizontal(|ui| {
+                                        if ui.button("➕").clicked() {
+                                            input.speed_breakdown.0.push(SpeedContribution::add("", 0.0));
+                                        }
+                                        ui.label(format!("= {:.3}%", input.speed_breakdown.speed()));
+                                    });
+                                });
+                                ui.vertical_centered_justified(|ui| {
+                                    if ui.button("Single value").clicked() {
+                                        input.speed100 = input.speed_breakdown.speed().round() as i32;
+                                        input.speed_breakdown = SpeedBreakdown::default();
+                                    }
+                                });
+                            }
+                            if let Some(error) = field_error(|e| matches!(e, SimError::NonPositiveSpeed(_))) {
+                                ui.colored_label(egui::Color32::RED, error);
                             }
                             ui.end_row();
 
@@ -166,13 +1060,12 @@ impl eframe::App for TradingPostProductionApp {
                                     .num_columns(4)
                                     .spacing([4.0, 4.0])
                                     .show(ui, |ui| {
-                                        let mut new_button = |ui: &mut egui::Ui, value, text: &str| {
+                                        let mut new_button = |ui: &mut egui::Ui, value: i32, text: &str| {
                                             ui.centered_and_justified(|ui| {
                                                 if ui.button(text).clicked() {
-                                                    input.duration_minutes = max(
-                                                        1,
-                                                        min(5400, input.duration_minutes + value),
-                                                    );
+                                                    input.duration_minutes =
+                                                        (input.duration_minutes + value)
+                                                            .clamp(1, 5400);
                                                 }
                                             });
                                         };
@@ -197,6 +1090,16 @@ impl eframe::App for TradingPostProductionApp {
                                         ui.end_row();
                                     });
                             }
+                            if let Some(error) = field_error(|e| matches!(e, SimError::NonPositiveDuration(_))) {
+                                ui.colored_label(egui::Color32::RED, error);
+                            } else if let Some(shortfall) = input.morale_shortfall() {
+                                ui.vertical(|ui| {
+                                    ui.colored_label(egui::Color32::YELLOW, shortfall.to_string());
+                                    if self.is_editing && ui.button("End shift then").clicked() {
+                                        input.duration_minutes = shortfall.exhausted_minutes.max(1);
+                                    }
+                                });
+                            }
                             ui.end_row();
                         });
                 });
@@ -254,11 +1157,10 @@ impl eframe::App for TradingPostProductionApp {
                                                 .spacing([4.0, 4.0])
                                                 .show(ui, |ui| {
                                                     let mut new_button =
-                                                        |ui: &mut egui::Ui, value, text: &str| {
+                                                        |ui: &mut egui::Ui, value: i32, text: &str| {
                                                             ui.centered_and_justified(|ui| {
                                                                 if ui.button(text).clicked() {
-                                                                    *ramp =
-                                                                        max(0, min(180, *ramp + value));
+                                                                    *ramp = (*ramp + value).clamp(0, 180);
                                                                 }
                                                             });
                                                         };
@@ -277,16 +1179,16 @@ impl eframe::App for TradingPostProductionApp {
                                                         .width(50.0)
                                                         .selected_text(skill.to_string())
                                                         .show_ui(ui, |ui| {
-                                                            ui.selectable_value(
-                                                                skill,
+                                                            for option in [
                                                                 TradingPostTailoringSkill::Alpha,
-                                                                "Alpha",
-                                                            );
-                                                            ui.selectable_value(
-                                                                skill,
                                                                 TradingPostTailoringSkill::Beta,
-                                                                "Beta",
-                                                            );
+                                                            ] {
+                                                                let allowed = roster.allows_tailoring(&option);
+                                                                let text = option.to_string();
+                                                                ui.add_enabled_ui(allowed, |ui| {
+                                                                    ui.selectable_value(skill, option, text)
+                                                                });
+                                                            }
                                                         });
                                                     ui.centered_and_justified(|ui| ui.add(label_hour));
                                                     ui.centered_and_justified(|ui| {
@@ -312,12 +1214,32 @@ impl eframe::App for TradingPostProductionApp {
                                 });
                                 if input.tailoring_ramped.len() < 3 {
                                     ui.horizontal_centered(|ui| {
-                                        if ui.button("➕").clicked() {
-                                            input.tailoring_ramped.push((
-                                                TradingPostTailoringSkill::Alpha,
-                                                0,
-                                            ));
+                                        if roster.operators.is_empty() {
+                                            if ui.button("➕").clicked() {
+                                                input.tailoring_ramped.push((
+                                                    TradingPostTailoringSkill::Alpha,
+                                                    0,
+                                                ));
+                                            }
+                                            return;
                                         }
+                                        // skills of the operators owned
+                                        ui.menu_button("➕", |ui| {
+                                            for tailor in roster.tailors() {
+                                                let skill = match &tailor.tailoring {
+                                                    Some(skill) => skill,
+                                                    None => continue,
+                                                };
+                                                let text = format!("{} ({})", tailor.name, skill);
+                                                if ui.button(text).clicked() {
+                                                    input.tailoring_ramped.push((skill.clone(), 0));
+                                                    ui.close_menu();
+                                                }
+                                            }
+                                            if roster.tailors().next().is_none() {
+                                                ui.label("No tailoring operators owned.");
+                                            }
+                                        });
                                     });
                                 }
                             });
@@ -336,6 +1258,16 @@ impl eframe::App for TradingPostProductionApp {
                                 ui.label("Jaye:");
                                 ui.label(input.jaye_phase.to_string());
                                 ui.end_row();
+                                if input.jaye_phase != HighRarityOperatorPhase::None {
+                                    ui.label("Jaye counts orders:");
+                                    ui.label(input.jaye_order_count.to_string());
+                                    ui.end_row();
+                                }
+                                if let Some(error) = field_error(|e| matches!(e, SimError::StalledBySkills { .. })) {
+                                    ui.label("");
+                                    ui.colored_label(egui::Color32::RED, error);
+                                    ui.end_row();
+                                }
                                 ui.label("Tequila:");
                                 ui.label(input.tequila_phase.to_string());
                                 ui.end_row();
@@ -344,28 +1276,26 @@ impl eframe::App for TradingPostProductionApp {
                                 ui.end_row();
                             });
                     } else {
+                        // greyed out where the roster can't field the operator
                         let op_combobox =
-                            |ui: &mut egui::Ui, name, var: &mut HighRarityOperatorPhase| {
-                                ui.label(name);
+                            |ui: &mut egui::Ui, name: &str, var: &mut HighRarityOperatorPhase| {
+                                let owned = roster.allows(name, &HighRarityOperatorPhase::E0);
+                                ui.add_enabled(owned, egui::Label::new(format!("{}:", name)));
                                 egui::ComboBox::from_id_source(name)
                                     .selected_text(var.to_string())
                                     .show_ui(ui, |ui| {
-                                        ui.selectable_value(var, HighRarityOperatorPhase::None, "None");
-                                        ui.selectable_value(
-                                            var,
+                                        for phase in [
+                                            HighRarityOperatorPhase::None,
                                             HighRarityOperatorPhase::E0,
-                                            "Elite 0",
-                                        );
-                                        ui.selectable_value(
-                                            var,
                                             HighRarityOperatorPhase::E1,
-                                            "Elite 1",
-                                        );
-                                        ui.selectable_value(
-                                            var,
                                             HighRarityOperatorPhase::E2,
-                                            "Elite 2",
-                                        );
+                                        ] {
+                                            let allowed = roster.allows(name, &phase);
+                                            let text = phase.to_string();
+                                            ui.add_enabled_ui(allowed, |ui| {
+                                                ui.selectable_value(var, phase, text)
+                                            });
+                                        }
                                     });
                                 ui.end_row();
                             };
@@ -374,62 +1304,947 @@ impl eframe::App for TradingPostProductionApp {
                             .spacing([20.0, 4.0])
                             // .striped(true)
                             .show(ui, |ui| {
-                                op_combobox(ui, "Jaye:", &mut input.jaye_phase);
-                                op_combobox(ui, "Tequila:", &mut input.tequila_phase);
-                                op_combobox(ui, "Proviso:", &mut input.proviso_phase);
+                                op_combobox(ui, "Jaye", &mut input.jaye_phase);
+                                if input.jaye_phase != HighRarityOperatorPhase::None {
+                                    ui.label("Jaye counts orders:");
+                                    egui::ComboBox::from_id_source("jaye order count")
+                                        .selected_text(input.jaye_order_count.to_string())
+                                        .show_ui(ui, |ui| {
+                                            ui.selectable_value(
+                                                &mut input.jaye_order_count,
+                                                JayeOrderCount::BeforeFinish,
+                                                "Before finish",
+                                            );
+                                            ui.selectable_value(
+                                                &mut input.jaye_order_count,
+                                                JayeOrderCount::AfterFinish,
+                                                "After finish",
+                                            );
+                                        });
+                                    ui.end_row();
+                                }
+                                if let Some(error) = field_error(|e| matches!(e, SimError::StalledBySkills { .. })) {
+                                    ui.label("");
+                                    ui.colored_label(egui::Color32::RED, error);
+                                    ui.end_row();
+                                }
+                                op_combobox(ui, "Tequila", &mut input.tequila_phase);
+                                op_combobox(ui, "Proviso", &mut input.proviso_phase);
+                                let unowned = roster.unowned_in(input);
+                                if !unowned.is_empty() {
+                                    ui.label("");
+                                    let text =
+                                        format!("Not owned at this phase: {}", unowned.join(", "));
+                                    ui.colored_label(egui::Color32::YELLOW, text);
+                                    ui.end_row();
+                                }
                             });
                     }
                 });
                 ui.end_row();
+                // starting state, handed over from the previous shift after the first
+                if !*is_day_plan || *selected_shift == 0 {
+                    ui.group(|ui| {
+                        let start_state = &mut input.start_state;
+                        egui::Grid::new("start state")
+                            .num_columns(2)
+                            .spacing([20.0, 4.0])
+                            .show(ui, |ui| {
+                                for (stored, name) in start_state.stored_orders.iter_mut().zip(ORDER_NAMES) {
+                                    ui.label(format!("Stored {} orders:", name));
+                                    if !&self.is_editing {
+                                        ui.label(stored.to_string());
+                                    } else {
+                                        ui.add(egui::DragValue::new(stored).clamp_range(0..=38));
+                                    }
+                                    ui.end_row();
+                                }
+                                ui.label("Current order:");
+                                if !&self.is_editing {
+                                    ui.label(match start_state.minutes_left {
+                                        Some(minutes) => {
+                                            format!("{:0>2}", minutes / 60)
+                                                + "h"
+                                                + &*(format!("{:0>2}", minutes % 60))
+                                                + "m left"
+                                        }
+                                        None => "(unknown progress)".to_string(),
+                                    });
+                                } else {
+                                    ui.horizontal(|ui| {
+                                        let mut known = start_state.minutes_left.is_some();
+                                        ui.checkbox(&mut known, "Time left:");
+                                        match (known, &mut start_state.minutes_left) {
+                                            (true, Some(minutes)) => {
+                                                ui.add(
+                                                    egui::DragValue::new(minutes)
+                                                        .clamp_range(0..=1440)
+                                                        .suffix("m"),
+                                                );
+                                            }
+                                            (true, minutes @ None) => *minutes = Some(0),
+                                            (false, minutes) => *minutes = None,
+                                        }
+                                    });
+                                }
+                                ui.end_row();
+                                if let Some(error) = field_error(|e| matches!(e, SimError::NegativeStartState)) {
+                                    ui.label("");
+                                    ui.colored_label(egui::Color32::RED, error);
+                                    ui.end_row();
+                                }
+                            });
+                    });
+                }
+                // collection schedule
+                ui.group(|ui| {
+                    ui.vertical(|ui| {
+                        ui.label("Collections:");
+                        if !&self.is_editing {
+                            if input.collection_minutes.is_empty() {
+                                ui.label("(end of shift only)");
+                            }
+                            for minutes in input.collection_minutes.iter() {
+                                ui.label(
+                                    format!("{:0>2}", minutes / 60)
+                                        + "h"
+                                        + &*(format!("{:0>2}", minutes % 60))
+                                        + "m",
+                                );
+                            }
+                        } else {
+                            input.collection_minutes.retain_mut(|minutes| {
+                                let mut retained = true;
+                                ui.horizontal(|ui| {
+                                    let (mut hours, mut mins) = (*minutes / 60, *minutes % 60);
+                                    ui.add(egui::DragValue::new(&mut hours).clamp_range(0..=90).suffix("h"));
+                                    ui.add(egui::DragValue::new(&mut mins).clamp_range(0..=59).suffix("m"));
+                                    *minutes = hours * 60 + mins;
+                                    if ui.button("❌").clicked() {
+                                        retained = false;
+                                    }
+                                });
+                                retained
+                            });
+                            if ui.button("➕").clicked() {
+                                input.collection_minutes.push(input.duration_minutes / 2);
+                            }
+                        }
+                    });
+                });
+                // speed schedule
+                ui.group(|ui| {
+                    ui.vertical(|ui| {
+                        ui.label("Speed changes:");
+                        if !&self.is_editing {
+                            if input.speed_schedule.is_empty() {
+                                ui.label("(same speed all shift)");
+                            }
+                            for change in input.speed_schedule.iter() {
+                                let minutes = change.from_minutes;
+                                ui.label(
+                                    format!("from {:0>2}", minutes / 60)
+                                        + "h"
+                                        + &*(format!("{:0>2}", minutes % 60))
+                                        + "m: "
+                                        + &*(format!("{}%", change.speed100)),
+                                );
+                            }
+                        } else {
+                            let base_speed = input.base_speed();
+                            input.speed_schedule.retain_mut(|change| {
+                                let mut retained = true;
+                                ui.horizontal(|ui| {
+                                    let minutes = change.from_minutes;
+                                    let (mut hours, mut mins) = (minutes / 60, minutes % 60);
+                                    ui.add(egui::DragValue::new(&mut hours).clamp_range(0..=90).suffix("h"));
+                                    ui.add(egui::DragValue::new(&mut mins).clamp_range(0..=59).suffix("m"));
+                                    change.from_minutes = hours * 60 + mins;
+                                    ui.add(
+                                        egui::DragValue::new(&mut change.speed100)
+                                            .clamp_range(1.0..=1000.0)
+                                            .suffix("%"),
+                                    );
+                                    if ui.button("❌").clicked() {
+                                        retained = false;
+                                    }
+                                });
+                                retained
+                            });
+                            if ui.button("➕").clicked() {
+                                input.speed_schedule.push(SpeedChange {
+                                    from_minutes: input.duration_minutes / 2,
+                                    speed100: base_speed,
+                                });
+                            }
+                        }
+                    });
+                });
+                // drone usages
+                ui.group(|ui| {
+                    ui.vertical(|ui| {
+                        ui.label("Drones:");
+                        if !&self.is_editing {
+                            if input.drone_usages.is_empty() {
+                                ui.label("(none)");
+                            }
+                            for usage in input.drone_usages.iter() {
+                                ui.label(drone_usage_text(usage));
+                            }
+                        } else {
+                            input.drone_usages.retain_mut(|usage| {
+                                let mut retained = true;
+                                ui.horizontal(|ui| {
+                                    let minute = usage.minute;
+                                    let (mut hours, mut mins) = (minute / 60, minute % 60);
+                                    ui.add(egui::DragValue::new(&mut hours).clamp_range(0..=90).suffix("h"));
+                                    ui.add(egui::DragValue::new(&mut mins).clamp_range(0..=59).suffix("m"));
+                                    usage.minute = hours * 60 + mins;
+                                    ui.add(
+                                        egui::DragValue::new(&mut usage.drones)
+                                            .clamp_range(0..=9999)
+                                            .suffix(" drones"),
+                                    );
+                                    if ui.button("❌").clicked() {
+                                        retained = false;
+                                    }
+                                });
+                                retained
+                            });
+                            if ui.button("➕").clicked() {
+                                input.drone_usages.push(DroneUsage {
+                                    minute: 0,
+                                    drones: 10,
+                                });
+                            }
+                        }
+                        if let Some(error) = field_error(|e| matches!(e, SimError::NegativeDroneUsage)) {
+                            ui.colored_label(egui::Color32::RED, error);
+                        }
+                    });
+                });
+                // operator morale
+                ui.group(|ui| {
+                    ui.vertical(|ui| {
+                        ui.label("Morale:");
+                        if !&self.is_editing {
+                            if input.morale.is_empty() {
+                                ui.label("(not tracked)");
+                            }
+                            for operator in input.morale.iter() {
+                                ui.label(morale_text(operator));
+                            }
+                        } else {
+                            input.morale.retain_mut(|operator| {
+                                let mut retained = true;
+                                ui.horizontal(|ui| {
+                                    ui.add(
+                                        egui::TextEdit::singleline(&mut operator.name)
+                                            .desired_width(80.0),
+                                    );
+                                    ui.add(
+                                        egui::DragValue::new(&mut operator.morale)
+                                            .speed(0.1)
+                                            .clamp_range(0.0..=MAX_MORALE),
+                                    );
+                                    ui.add(
+                                        egui::DragValue::new(&mut operator.drain_per_hour)
+                                            .speed(0.01)
+                                            .clamp_range(0.0..=MAX_MORALE)
+                                            .prefix("-")
+                                            .suffix("/h"),
+                                    );
+                                    if ui.button("❌").clicked() {
+                                        retained = false;
+                                    }
+                                });
+                                retained
+                            });
+                            if ui.button("➕").clicked() {
+                                input.morale.push(OperatorMorale {
+                                    name: "Jaye".to_string(),
+                                    morale: MAX_MORALE,
+                                    drain_per_hour: 1.0,
+                                });
+                            }
+                        }
+                        if let Some(error) = field_error(|e| matches!(e, SimError::InvalidMorale)) {
+                            ui.colored_label(egui::Color32::RED, error);
+                        }
+                    });
+                });
+                // gold on hand to deliver orders with
+                ui.group(|ui| {
+                    ui.vertical(|ui| {
+                        ui.label("Gold:");
+                        if !&self.is_editing {
+                            ui.label(match &input.gold_supply {
+                                Some(supply) => {
+                                    format!("{} on hand, +{}/h", supply.stock, supply.per_hour)
+                                }
+                                None => "(always enough)".to_string(),
+                            });
+                        } else {
+                            let mut limited = input.gold_supply.is_some();
+                            ui.checkbox(&mut limited, "Limited");
+                            match (limited, &mut input.gold_supply) {
+                                (true, Some(supply)) => {
+                                    ui.add(
+                                        egui::DragValue::new(&mut supply.stock)
+                                            .clamp_range(0..=9999)
+                                            .suffix(" on hand"),
+                                    );
+                                    ui.add(
+                                        egui::DragValue::new(&mut supply.per_hour)
+                                            .speed(0.01)
+                                            .clamp_range(0.0..=100.0)
+                                            .prefix("+")
+                                            .suffix("/h"),
+                                    );
+                                }
+                                (true, None) => {
+                                    input.gold_supply = Some(GoldSupply {
+                                        stock: 100,
+                                        per_hour: 0.0,
+                                    });
+                                }
+                                (false, _) => input.gold_supply = None,
+                            }
+                        }
+                        if let Some(error) = field_error(|e| matches!(e, SimError::NegativeGoldSupply)) {
+                            ui.colored_label(egui::Color32::RED, error);
+                        }
+                    });
+                });
+                ui.end_row();
 
             });
 
+            // game data
+            egui::CollapsingHeader::new(format!("Game data ({})", game_data.version))
+                .show(ui, |ui| {
+                    if self.is_editing {
+                        ui.add(
+                            egui::TextEdit::multiline(game_data_text)
+                                .code_editor()
+                                .desired_rows(8),
+                        );
+                        ui.horizontal(|ui| {
+                            if ui.button("Apply").clicked() {
+                                new_game_data = Some(Some(game_data_text.clone()));
+                            }
+                            if ui.button("Reset to bundled").clicked() {
+                                new_game_data = Some(None);
+                            }
+                        });
+                        if let Some(error) = game_data_error {
+                            ui.colored_label(egui::Color32::RED, error.as_str());
+                        }
+                    } else {
+                        ui.label(game_data.to_json());
+                    }
+                });
+
             //// Output
             ui.separator();
-            if ui.button("Calculate").clicked() {
-                *output = simulate_tp_production(input);
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(calculation_error.is_none(), egui::Button::new("Calculate"))
+                    .clicked()
+                {
+                    calculate = true;
+                }
+                if let Some(error) = &calculation_error {
+                    ui.colored_label(egui::Color32::RED, error);
+                }
+                if let Some(running) = job {
+                    ui.add(
+                        egui::ProgressBar::new(running.progress())
+                            .desired_width(200.0)
+                            .show_percentage(),
+                    );
+                    if ui.button("Cancel").clicked() {
+                        *job = None;
+                    }
+                }
+            });
+            let right_align_label = |ui: &mut Ui, text: String| {
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    ui.label(text);
+                });
+            };
+            ui.horizontal_top(|ui| {
+                egui::Grid::new("output grid")
+                    .num_columns(4)
+                    .spacing([4.0, 4.0])
+                    .show(ui, |ui| {
+                        ui.label("Stall chance");
+                        right_align_label(ui, format!("{:.4}", output.stall_chance));
+                        ui.label("%");
+                        ui.end_row();
+                        if output.interval_end_minutes.len() > 1 {
+                            for (end, chance) in output
+                                .interval_end_minutes
+                                .iter()
+                                .zip(output.interval_stall_chance.iter())
+                            {
+                                ui.label(
+                                    format!("  until {:0>2}", end / 60)
+                                        + "h"
+                                        + &*(format!("{:0>2}", end % 60))
+                                        + "m",
+                                );
+                                right_align_label(ui, format!("{:.4}", chance));
+                                ui.label("%");
+                                ui.end_row();
+                            }
+                        }
+                        ui.label("Average stall time");
+                        right_align_label(ui, format!("{:.4}", output.average_stall_time));
+                        ui.label("minutes");
+                        ui.end_row();
+                        ui.label("Total LMD");
+                        right_align_label(ui, format!("{:.4}", output.total_lmd));
+                        ui.end_row();
+                        ui.label("Total Gold");
+                        right_align_label(ui, format!("{:.4}", output.total_gold));
+                        ui.end_row();
+                        ui.label("Daily LMD");
+                        right_align_label(ui, format!("{:.4}", output.daily_lmd));
+                        ui.end_row();
+                        ui.label("Daily Gold");
+                        right_align_label(ui, format!("{:.4}", output.daily_gold));
+                        ui.end_row();
+                        ui.label("Net LMD Speed");
+                        right_align_label(ui, format!("{:.4}", output.net_lmd_speed));
+                        ui.label("%");
+                        ui.end_row();
+                        ui.label("Net Gold Speed");
+                        right_align_label(ui, format!("{:.4}", output.net_gold_speed));
+                        ui.label("%");
+                        ui.end_row();
+                        ui.label("Average Speed");
+                        right_align_label(ui, format!("{:.4}", output.average_speed));
+                        ui.label("%");
+                        ui.end_row();
+                        ui.label("Drones Used");
+                        right_align_label(ui, format!("{:.4}", output.drones_used));
+                        ui.end_row();
+                        if let Some(shortage) = &output.gold_shortage {
+                            ui.label("Gold wait chance");
+                            right_align_label(ui, format!("{:.4}", shortage.wait_chance));
+                            ui.label("%");
+                            ui.end_row();
+                            if output.interval_end_minutes.len() > 1 {
+                                for (end, chance) in output
+                                    .interval_end_minutes
+                                    .iter()
+                                    .zip(shortage.interval_wait_chance.iter())
+                                {
+                                    ui.label(format!("  at {:0>2}h{:0>2}m", end / 60, end % 60));
+                                    right_align_label(ui, format!("{:.4}", chance));
+                                    ui.label("%");
+                                    ui.end_row();
+                                }
+                            }
+                            ui.label("Delayed LMD");
+                            right_align_label(ui, format!("{:.4}", shortage.delayed_lmd));
+                            ui.end_row();
+                            ui.label("Lost LMD");
+                            right_align_label(ui, format!("{:.4}", shortage.lost_lmd));
+                            ui.end_row();
+                            ui.label("Gold at end");
+                            right_align_label(ui, format!("{:.4}", shortage.stock_at_end));
+                            ui.end_row();
+                        }
+                    });
+                ui.separator();
+                egui::Grid::new("distribution grid")
+                    .num_columns(3)
+                    .spacing([12.0, 4.0])
+                    .show(ui, |ui| {
+                        let distributions = [&output.lmd_distribution, &output.gold_distribution];
+                        let stat_row = |ui: &mut Ui, name, stat: fn(&ShiftDistribution) -> f64| {
+                            ui.label(name);
+                            for distribution in distributions {
+                                right_align_label(ui, format!("{:.4}", stat(distribution)));
+                            }
+                            ui.end_row();
+                        };
+                        ui.label("");
+                        ui.label("LMD");
+                        ui.label("Gold");
+                        ui.end_row();
+                        stat_row(ui, "Variance", |d| d.variance);
+                        stat_row(ui, "Std. deviation", |d| d.std_dev());
+                        stat_row(ui, "5th percentile", |d| d.p5);
+                        stat_row(ui, "Median", |d| d.p50);
+                        stat_row(ui, "95th percentile", |d| d.p95);
+                    });
+            });
+            if *is_day_plan && !day_output.shifts.is_empty() {
+                ui.separator();
+                egui::Grid::new("shift grid")
+                    .num_columns(6)
+                    .spacing([12.0, 4.0])
+                    .show(ui, |ui| {
+                        ui.label("");
+                        ui.label("Duration");
+                        ui.label("Stall chance (%)");
+                        ui.label("Average stall time");
+                        ui.label("LMD");
+                        ui.label("Gold");
+                        ui.end_row();
+                        for (i, shift) in day_output.shifts.iter().enumerate() {
+                            let minutes = shift.interval_end_minutes.last().copied().unwrap_or(0);
+                            ui.label(format!("Shift {}", i + 1));
+                            right_align_label(
+                                ui,
+                                format!("{:0>2}", minutes / 60)
+                                    + "h"
+                                    + &*(format!("{:0>2}", minutes % 60))
+                                    + "m",
+                            );
+                            right_align_label(ui, format!("{:.4}", shift.stall_chance));
+                            right_align_label(ui, format!("{:.4}", shift.average_stall_time));
+                            right_align_label(ui, format!("{:.4}", shift.total_lmd));
+                            right_align_label(ui, format!("{:.4}", shift.total_gold));
+                            ui.end_row();
+                        }
+                    });
             }
-            egui::Grid::new("output grid")
-                .num_columns(4)
-                .spacing([4.0, 4.0])
-                .show(ui, |ui| {
-                    let right_align_label = |ui: &mut Ui, text: String| {
-                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                            ui.label(text);
+            if *is_posts && !posts_output.trading_posts.is_empty() {
+                ui.separator();
+                egui::Grid::new("post grid")
+                    .num_columns(5)
+                    .spacing([12.0, 4.0])
+                    .show(ui, |ui| {
+                        ui.label("");
+                        ui.label("Stall chance (%)");
+                        ui.label("Daily LMD");
+                        ui.label("Daily Gold");
+                        ui.label("Drones used");
+                        ui.end_row();
+                        for (i, post) in posts_output.trading_posts.iter().enumerate() {
+                            ui.label(format!("TP {}", i + 1));
+                            right_align_label(ui, format!("{:.4}", post.stall_chance));
+                            right_align_label(ui, format!("{:.4}", post.daily_lmd));
+                            right_align_label(ui, format!("{:.4}", post.daily_gold));
+                            right_align_label(ui, format!("{:.4}", post.drones_used));
+                            ui.end_row();
+                        }
+                        ui.label("Together");
+                        right_align_label(ui, format!("{:.4}", posts_output.stall_chance));
+                        right_align_label(ui, format!("{:.4}", posts_output.daily_lmd));
+                        right_align_label(ui, format!("{:.4}", posts_output.daily_gold));
+                        right_align_label(ui, format!("{:.4}", posts_output.drones_used));
+                        ui.end_row();
+                    });
+                ui.label(format!(
+                    "The stall chance together is that of at least one post stalling. The posts \
+                     use up {:.4} gold a day.",
+                    posts_output.gold_demand,
+                ));
+            }
+            ui.horizontal(|ui| {
+                for (name, distribution) in [
+                    ("LMD distribution", &output.lmd_distribution),
+                    ("Gold distribution", &output.gold_distribution),
+                ] {
+                    let bars = distribution
+                        .pmf
+                        .iter()
+                        .map(|(value, p)| egui::plot::Bar::new(*value, p * 100.0))
+                        .collect();
+                    egui::plot::Plot::new(name)
+                        .width(280.0)
+                        .height(120.0)
+                        .allow_drag(false)
+                        .allow_zoom(false)
+                        .allow_scroll(false)
+                        .show(ui, |plot_ui| {
+                            plot_ui.bar_chart(
+                                egui::plot::BarChart::new(bars)
+                                    .width(distribution.bin_width)
+                                    .name(name),
+                            )
+                        });
+                }
+            });
+            // course of the shift, or of the day
+            let timeline = &output.timeline;
+            if !timeline.lmd.is_empty() {
+                let chart = |name: &str| {
+                    egui::plot::Plot::new(name)
+                        .width(280.0)
+                        .height(120.0)
+                        .allow_drag(false)
+                        .allow_zoom(false)
+                        .allow_scroll(false)
+                };
+                // x in hours into the shift
+                let curve = |values: &[f64]| {
+                    values
+                        .iter()
+                        .enumerate()
+                        .map(|(minute, value)| [minute as f64 / 60.0, *value])
+                        .collect::<egui::plot::PlotPoints>()
+                };
+                let hours = (timeline.lmd.len() - 1) as f64 / 60.0;
+                ui.horizontal(|ui| {
+                    for (name, values) in [
+                        ("Stall chance (%) by shift length (h)", &timeline.stall_chance),
+                        ("Expected LMD over time (h)", &timeline.lmd),
+                        ("Expected gold over time (h)", &timeline.gold),
+                    ] {
+                        ui.vertical(|ui| {
+                            ui.label(name);
+                            chart(name).show(ui, |plot_ui| {
+                                plot_ui.line(egui::plot::Line::new(curve(values)).name(name))
+                            });
                         });
+                    }
+                });
+                ui.horizontal(|ui| {
+                    if let Some(texture) = stored_chart {
+                        let counts = texture.size()[1] as f64;
+                        ui.vertical(|ui| {
+                            ui.label("Stored order count over time (h), darker when more likely");
+                            chart("Stored orders over time")
+                                .width(584.0)
+                                .include_y(-0.5)
+                                .include_y(counts - 0.5)
+                                .show(ui, |plot_ui| {
+                                    plot_ui.image(egui::plot::PlotImage::new(
+                                        texture.id(),
+                                        egui::plot::PlotPoint::new(hours / 2.0, counts / 2.0 - 0.5),
+                                        [hours as f32, counts as f32],
+                                    ))
+                                });
+                        });
+                    }
+                    // a full post counts as standing still
+                    let name = "Expected speed (%) over time (h)";
+                    ui.vertical(|ui| {
+                        ui.label(name);
+                        chart(name).include_y(0.0).show(ui, |plot_ui| {
+                            plot_ui.line(egui::plot::Line::new(curve(&timeline.speed)).name(name))
+                        });
+                    });
+                });
+            }
+            // stall chance over speeds and shift lengths, to choose how long to leave the post
+            egui::CollapsingHeader::new("Shift length").show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(input_error.is_none(), egui::Button::new("Calculate heatmap"))
+                        .clicked()
+                    {
+                        calculate_shift_lengths = true;
+                    }
+                    if let Some(running) = shift_lengths_job {
+                        ui.add(
+                            egui::ProgressBar::new(running.progress())
+                                .desired_width(200.0)
+                                .show_percentage(),
+                        );
+                        if ui.button("Cancel").clicked() {
+                            *shift_lengths_job = None;
+                        }
+                    }
+                    ui.checkbox(heatmap_lmd, "Show daily LMD");
+                });
+                if *is_day_plan {
+                    ui.label("The selected shift is swept on its own, from its own start state.");
+                }
+                if let Some(sweep) = shift_lengths {
+                    if shift_lengths_stale {
+                        ui.label("Inputs have changed since the heatmap was calculated.");
+                    }
+                    shift_length_heatmap(ui, sweep, *heatmap_lmd, input);
+                }
+            });
+            // how far speed, duration or order limit go before stalling too much
+            egui::CollapsingHeader::new("Solve for…").show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_id_source("solve for")
+                        .selected_text(solver_field_name(*solver_field))
+                        .show_ui(ui, |ui| {
+                            for field in [
+                                SweepField::Speed,
+                                SweepField::DurationMinutes,
+                                SweepField::Capacity,
+                            ] {
+                                ui.selectable_value(solver_field, field, solver_field_name(field));
+                            }
+                        });
+                    ui.label("keeping");
+                    let (mut is_chance, mut amount) = match *solver_limit {
+                        StallLimit::Chance(percent) => (true, percent),
+                        StallLimit::AverageTime(minutes) => (false, minutes),
                     };
-                    ui.label("Stall chance");
-                    right_align_label(ui, format!("{:.4}", output.stall_chance));
-                    ui.label("%");
-                    ui.end_row();
-                    ui.label("Average stall time");
-                    right_align_label(ui, format!("{:.4}", output.average_stall_time));
-                    ui.label("minutes");
-                    ui.end_row();
-                    ui.label("Total LMD");
-                    right_align_label(ui, format!("{:.4}", output.total_lmd));
-                    ui.end_row();
-                    ui.label("Total Gold");
-                    right_align_label(ui, format!("{:.4}", output.total_gold));
-                    ui.end_row();
-                    ui.label("Daily LMD");
-                    right_align_label(ui, format!("{:.4}", output.daily_lmd));
-                    ui.end_row();
-                    ui.label("Daily Gold");
-                    right_align_label(ui, format!("{:.4}", output.daily_gold));
-                    ui.end_row();
-                    ui.label("Net LMD Speed");
-                    right_align_label(ui, format!("{:.4}", output.net_lmd_speed));
-                    ui.label("%");
-                    ui.end_row();
-                    ui.label("Net Gold Speed");
-                    right_align_label(ui, format!("{:.4}", output.net_gold_speed));
-                    ui.label("%");
-                    ui.end_row();
+                    egui::ComboBox::from_id_source("stall limit")
+                        .selected_text(if is_chance {
+                            "stall chance"
+                        } else {
+                            "average stall time"
+                        })
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut is_chance, true, "stall chance");
+                            ui.selectable_value(&mut is_chance, false, "average stall time");
+                        });
+                    ui.label("at most");
+                    ui.add(
+                        egui::DragValue::new(&mut amount)
+                            .speed(0.1)
+                            .clamp_range(0.0..=if is_chance { 100.0 } else { 5400.0 })
+                            .suffix(if is_chance { "%" } else { " minutes" }),
+                    );
+                    *solver_limit = if is_chance {
+                        StallLimit::Chance(amount)
+                    } else {
+                        StallLimit::AverageTime(amount)
+                    };
+                    if ui
+                        .add_enabled(input_error.is_none(), egui::Button::new("Solve"))
+                        .clicked()
+                    {
+                        solve = true;
+                    }
+                    if let Some(running) = solver_job {
+                        ui.add(
+                            egui::ProgressBar::new(running.progress())
+                                .desired_width(200.0)
+                                .show_percentage(),
+                        );
+                        if ui.button("Cancel").clicked() {
+                            *solver_job = None;
+                        }
+                    }
                 });
+                match solution {
+                    Some(Ok(Some(found))) => {
+                        ui.horizontal(|ui| {
+                            let value = match solver_field {
+                                SweepField::Speed => format!("{}%", found.value),
+                                SweepField::DurationMinutes => {
+                                    format!("{:0>2}h{:0>2}m", found.value / 60, found.value % 60)
+                                }
+                                SweepField::Capacity => found.value.to_string(),
+                            };
+                            ui.label(format!("{}: {}", solver_field_name(*solver_field), value));
+                            match &found.output {
+                                Ok(output) => ui.label(format!(
+                                    "(stall chance {:.4}%, average stall time {:.4} minutes)",
+                                    output.stall_chance, output.average_stall_time
+                                )),
+                                Err(error) => ui.label(format!("({})", error)),
+                            };
+                            if found.at_range_end {
+                                ui.label("The search stops here; the limit may allow more.");
+                            }
+                            if self.is_editing && ui.button("Use").clicked() {
+                                match solver_field {
+                                    SweepField::Speed => {
+                                        input.speed100 = found.value;
+                                        input.speed_breakdown = SpeedBreakdown::default();
+                                    }
+                                    SweepField::DurationMinutes => {
+                                        input.duration_minutes = found.value
+                                    }
+                                    SweepField::Capacity => input.capacity = found.value,
+                                }
+                            }
+                        });
+                    }
+                    Some(Ok(None)) => {
+                        ui.label(format!("No value keeps the {}.", solver_limit));
+                    }
+                    Some(Err(error)) => {
+                        ui.colored_label(egui::Color32::RED, error.to_string());
+                    }
+                    None => {}
+                }
+            });
+            // when to spend a drone budget
+            egui::CollapsingHeader::new("Drone plan").show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Spend");
+                    ui.add(
+                        egui::DragValue::new(drone_budget)
+                            .clamp_range(0..=9999)
+                            .suffix(" drones"),
+                    );
+                    if ui
+                        .add_enabled(input_error.is_none(), egui::Button::new("Plan"))
+                        .clicked()
+                    {
+                        plan_drones = true;
+                    }
+                    if let Some(running) = drone_job {
+                        ui.add(
+                            egui::ProgressBar::new(running.progress())
+                                .desired_width(200.0)
+                                .show_percentage(),
+                        );
+                        if ui.button("Cancel").clicked() {
+                            *drone_job = None;
+                        }
+                    }
+                });
+                ui.label("Tries the start of the shift, every hour and every collection.");
+                match drone_plan {
+                    Some(Ok(plan)) => {
+                        ui.horizontal(|ui| {
+                            if plan.usages.is_empty() {
+                                ui.label("No drones to spend.");
+                            }
+                            for usage in plan.usages.iter() {
+                                ui.label(drone_usage_text(usage));
+                            }
+                            if self.is_editing && ui.button("Use").clicked() {
+                                input.drone_usages = plan.usages.clone();
+                            }
+                        });
+                        ui.label(format!(
+                            "Total LMD {:.4}, total gold {:.4}, stall chance {:.4}%, \
+                             {:.4} drones used",
+                            plan.output.total_lmd,
+                            plan.output.total_gold,
+                            plan.output.stall_chance,
+                            plan.output.drones_used,
+                        ));
+                    }
+                    Some(Err(error)) => {
+                        ui.colored_label(egui::Color32::RED, error.to_string());
+                    }
+                    None => {}
+                }
+            });
+            // gold made by factories against the gold the trading post uses up
+            egui::CollapsingHeader::new("Gold factories").show(ui, |ui| {
+                egui::Grid::new("factory")
+                    .num_columns(2)
+                    .spacing([4.0, 4.0])
+                    .show(ui, |ui| {
+                        ui.label("Factories:");
+                        ui.add(egui::DragValue::new(factory_count).clamp_range(0..=9));
+                        ui.end_row();
+                        ui.label("Level:");
+                        egui::ComboBox::from_id_source("factory level")
+                            .selected_text(factory.level.to_string())
+                            .show_ui(ui, |ui| {
+                                let levels = [FactoryLevel::L1, FactoryLevel::L2, FactoryLevel::L3];
+                                for level in levels {
+                                    let text = level.to_string();
+                                    ui.selectable_value(&mut factory.level, level, text);
+                                }
+                            });
+                        ui.end_row();
+                        ui.label("Gold limit:");
+                        ui.label(factory.item_limit().to_string());
+                        ui.end_row();
+                        ui.label("Speed:");
+                        ui.add(
+                            egui::DragValue::new(&mut factory.speed100)
+                                .clamp_range(1.0..=1000.0)
+                                .suffix("%"),
+                        );
+                        ui.end_row();
+                        ui.label("Skills:");
+                        ui.vertical(|ui| {
+                            factory.skills.retain_mut(|skill| {
+                                let mut retained = true;
+                                ui.horizontal(|ui| {
+                                    ui.add(
+                                        egui::TextEdit::singleline(&mut skill.name)
+                                            .desired_width(110.0),
+                                    );
+                                    ui.add(
+                                        egui::DragValue::new(&mut skill.speed100)
+                                            .speed(0.1)
+                                            .prefix("+")
+                                            .suffix("%"),
+                                    );
+                                    ui.add(
+                                        egui::DragValue::new(&mut skill.capacity)
+                                            .prefix("+")
+                                            .suffix(" gold"),
+                                    );
+                                    if ui.button("❌").clicked() {
+                                        retained = false;
+                                    }
+                                });
+                                retained
+                            });
+                            if ui.button("➕").clicked() {
+                                factory.skills.push(FactorySkill {
+                                    name: String::new(),
+                                    speed100: 0.0,
+                                    capacity: 0,
+                                });
+                            }
+                        });
+                        ui.end_row();
+                    });
+                ui.label("Collected along with the trading post, over a shift as long as its.");
+                let factory_input = FactoryInput {
+                    duration_minutes: input.duration_minutes,
+                    collection_minutes: input.collection_minutes.clone(),
+                    ..factory.clone()
+                };
+                // set against every post when several run side by side
+                let (trading_posts, used_by) = if *is_posts {
+                    (&posts_output.trading_posts[..], "the trading posts")
+                } else {
+                    (std::slice::from_ref(output), "the trading post")
+                };
+                match simulate_factory(&factory_input) {
+                    Ok(factory_output) => {
+                        let count = max(*factory_count, 0) as usize;
+                        let factories = vec![factory_output.clone(); count];
+                        let balance = GoldBalance::new(&factories, trading_posts);
+                        ui.label(format!(
+                            "Each factory makes {:.4} gold a day, and stands full for {:.4} \
+                             minutes of the shift.",
+                            factory_output.daily_made, factory_output.full_minutes,
+                        ));
+                        ui.label(format!(
+                            "Gold a day: {:.4} made, {:.4} used by {}, {:+.4} net.",
+                            balance.produced,
+                            balance.consumed,
+                            used_by,
+                            balance.net(),
+                        ));
+                        if !balance.keeps_up() {
+                            let text = format!("The factories don't keep up with {}.", used_by);
+                            ui.colored_label(egui::Color32::YELLOW, text);
+                        }
+                    }
+                    Err(error) => {
+                        ui.colored_label(egui::Color32::RED, error.to_string());
+                    }
+                }
+            });
             // ui.horizontal(|ui| ui.label(""));
         });
 
+        if let Some(json) = new_game_data {
+            self.apply_game_data(json);
+        }
+        if calculate {
+            self.start_calculation(ctx);
+        }
+        if calculate_shift_lengths {
+            self.start_shift_lengths(ctx);
+        }
+        if solve {
+            self.start_solver(ctx);
+        }
+        if plan_drones {
+            self.start_drone_plan(ctx);
+        }
+
         if false {
             egui::CentralPanel::default().show(ctx, |ui| {
                 // The central panel the region left after adding TopPanel's and SidePanel's
@@ -454,3 +2269,311 @@ impl eframe::App for TradingPostProductionApp {
         }
     }
 }
+
+/// When drones are spent, and how many.
+/// Edits the buildings of a base layout one by one, under ids made unique by `id`. Trading posts
+/// are copied from `sources`, by name, and new factories are like `factory`.
+fn edit_layout(
+    ui: &mut Ui,
+    id: usize,
+    layout: &mut BaseLayout,
+    sources: &[(String, TradingPostProductionInput)],
+    factory: &FactoryInput,
+) {
+    egui::Grid::new(("layout", id))
+        .num_columns(2)
+        .spacing([8.0, 4.0])
+        .show(ui, |ui| {
+            let mut building = 0;
+            layout.trading_posts.retain_mut(|trading_post| {
+                building += 1;
+                let mut retained = true;
+                ui.label(format!("Trading post {}", building));
+                ui.horizontal(|ui| {
+                    ui.label(format!(
+                        "{}, {:.0}%, {} orders",
+                        trading_post.phase,
+                        trading_post.base_speed(),
+                        trading_post.capacity
+                    ));
+                    egui::ComboBox::from_id_source(("trading post source", id, building))
+                        .selected_text("Copy from")
+                        .show_ui(ui, |ui| {
+                            for (name, source) in sources {
+                                if ui.selectable_label(false, name).clicked() {
+                                    *trading_post = source.clone();
+                                }
+                            }
+                        });
+                    if ui.button("❌").clicked() {
+                        retained = false;
+                    }
+                });
+                ui.end_row();
+                retained
+            });
+            let levels = [FactoryLevel::L1, FactoryLevel::L2, FactoryLevel::L3];
+            let mut building = 0;
+            layout.factories.retain_mut(|factory| {
+                building += 1;
+                let mut retained = true;
+                ui.label(format!("Factory {}", building));
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_id_source(("factory product", id, building))
+                        .selected_text(factory.product.to_string())
+                        .show_ui(ui, |ui| {
+                            let products = [
+                                FactoryProduct::Gold,
+                                FactoryProduct::BattleRecord,
+                                FactoryProduct::OriginiumShard,
+                            ];
+                            for product in products {
+                                let text = product.to_string();
+                                ui.selectable_value(&mut factory.product, product, text);
+                            }
+                        });
+                    egui::ComboBox::from_id_source(("factory level", id, building))
+                        .selected_text(factory.level.to_string())
+                        .show_ui(ui, |ui| {
+                            for level in levels {
+                                let text = level.to_string();
+                                ui.selectable_value(&mut factory.level, level, text);
+                            }
+                        });
+                    ui.add(
+                        egui::DragValue::new(&mut factory.speed100)
+                            .clamp_range(1.0..=1000.0)
+                            .suffix("%"),
+                    );
+                    if ui.button("❌").clicked() {
+                        retained = false;
+                    }
+                });
+                ui.end_row();
+                retained
+            });
+            let levels = [
+                PowerPlantLevel::L1,
+                PowerPlantLevel::L2,
+                PowerPlantLevel::L3,
+            ];
+            let mut building = 0;
+            layout.power_plants.retain_mut(|power_plant| {
+                building += 1;
+                let mut retained = true;
+                ui.label(format!("Power plant {}", building));
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_id_source(("power plant level", id, building))
+                        .selected_text(power_plant.level.to_string())
+                        .show_ui(ui, |ui| {
+                            for level in levels {
+                                let text = level.to_string();
+                                ui.selectable_value(&mut power_plant.level, level, text);
+                            }
+                        });
+                    ui.label("Drone recharge:");
+                    ui.add(
+                        egui::DragValue::new(&mut power_plant.drone_speed100)
+                            .clamp_range(0.0..=100.0)
+                            .prefix("+")
+                            .suffix("%"),
+                    );
+                    if ui.button("❌").clicked() {
+                        retained = false;
+                    }
+                });
+                ui.end_row();
+                retained
+            });
+        });
+    ui.horizontal(|ui| {
+        let shown = &sources[0].1;
+        if ui.button("➕ Trading post").clicked() {
+            layout.trading_posts.push(shown.clone());
+        }
+        if ui.button("➕ Factory").clicked() {
+            layout.factories.push(FactoryInput {
+                duration_minutes: shown.duration_minutes,
+                collection_minutes: shown.collection_minutes.clone(),
+                ..factory.clone()
+            });
+        }
+        if ui.button("➕ Power plant").clicked() {
+            layout.power_plants.push(PowerPlant::default());
+        }
+    });
+}
+
+fn drone_usage_text(usage: &DroneUsage) -> String {
+    format!(
+        "{:0>2}h{:0>2}m: {} drones",
+        usage.minute / 60,
+        usage.minute % 60,
+        usage.drones
+    )
+}
+
+/// An operator's morale, how fast it drains, and when it runs out.
+fn morale_text(operator: &OperatorMorale) -> String {
+    let text = format!(
+        "{}: {} morale, -{}/h",
+        operator.name, operator.morale, operator.drain_per_hour
+    );
+    match operator.exhausted_seconds() {
+        Some(seconds) => {
+            let minutes = seconds / 60;
+            text + &format!(", out at {:0>2}h{:0>2}m", minutes / 60, minutes % 60)
+        }
+        None => text,
+    }
+}
+
+/// What solving for `field` finds.
+fn solver_field_name(field: SweepField) -> &'static str {
+    match field {
+        SweepField::Speed => "Highest speed",
+        SweepField::DurationMinutes => "Longest duration",
+        SweepField::Capacity => "Smallest order limit",
+    }
+}
+
+/// Color from green for `fraction` 0, through yellow, to red for 1.
+fn heat_color(fraction: f64) -> egui::Color32 {
+    let fraction = fraction.clamp(0.0, 1.0);
+    let (red, green) = if fraction < 0.5 {
+        (fraction * 2.0, 1.0)
+    } else {
+        (1.0, (1.0 - fraction) * 2.0)
+    };
+    egui::Color32::from_rgb((red * 230.0) as u8, (green * 200.0) as u8, 60)
+}
+
+/// Draws `sweep`, over speeds and shift lengths, as a heatmap of stall chance or of daily LMD
+/// with the values written in, and marks the speed and duration of `current`.
+fn shift_length_heatmap(
+    ui: &mut Ui,
+    sweep: &SweepResult,
+    lmd: bool,
+    current: &TradingPostProductionInput,
+) {
+    let axis = |field| {
+        sweep
+            .fields
+            .iter()
+            .position(|f| *f == field)
+            .expect("shift lengths are swept over speed and duration")
+    };
+    let (speed_axis, minutes_axis) = (axis(SweepField::Speed), axis(SweepField::DurationMinutes));
+    // cells reach halfway to their neighbours
+    let half_step = |axis: usize| {
+        let mut values: Vec<i32> = sweep.rows.iter().map(|row| row.values[axis]).collect();
+        values.sort_unstable();
+        values.dedup();
+        values.windows(2).map(|w| w[1] - w[0]).min().unwrap_or(2) as f64 / 2.0
+    };
+    let (speed_half, minutes_half) = (half_step(speed_axis), half_step(minutes_axis));
+    let value = |output: &TradingPostProductionOutput| {
+        if lmd {
+            output.daily_lmd
+        } else {
+            output.stall_chance
+        }
+    };
+    let values = sweep
+        .rows
+        .iter()
+        .filter_map(|row| row.output.as_ref().ok())
+        .map(value);
+    // stall chance on its own scale; daily LMD from the worst to the best
+    let (low, high) = if lmd {
+        values.fold((f64::INFINITY, f64::NEG_INFINITY), |(low, high), value| {
+            (low.min(value), high.max(value))
+        })
+    } else {
+        (0.0, 100.0)
+    };
+
+    ui.label(if lmd {
+        "Daily LMD by shift length (h) and speed (%)"
+    } else {
+        "Stall chance (%) by shift length (h) and speed (%)"
+    });
+    let hovered = egui::plot::Plot::new("shift lengths")
+        .width(872.0)
+        .height(300.0)
+        .allow_drag(false)
+        .allow_zoom(false)
+        .allow_scroll(false)
+        .show(ui, |plot_ui| {
+            for row in sweep.rows.iter() {
+                let speed = row.values[speed_axis] as f64;
+                let hours = row.values[minutes_axis] as f64 / 60.0;
+                let (left, right) = (hours - minutes_half / 60.0, hours + minutes_half / 60.0);
+                let (bottom, top) = (speed - speed_half, speed + speed_half);
+                let color = match &row.output {
+                    Ok(output) => {
+                        let fraction = (value(output) - low) / (high - low).max(f64::EPSILON);
+                        heat_color(if lmd { 1.0 - fraction } else { fraction })
+                    }
+                    Err(_) => egui::Color32::GRAY,
+                };
+                plot_ui.polygon(
+                    egui::plot::Polygon::new(egui::plot::PlotPoints::new(vec![
+                        [left, bottom],
+                        [right, bottom],
+                        [right, top],
+                        [left, top],
+                    ]))
+                    .color(color)
+                    .fill_alpha(1.0),
+                );
+                if let Ok(output) = &row.output {
+                    let text = if lmd {
+                        format!("{:.0}", output.daily_lmd)
+                    } else {
+                        format!("{:.1}", output.stall_chance)
+                    };
+                    plot_ui.text(
+                        egui::plot::Text::new(egui::plot::PlotPoint::new(hours, speed), text)
+                            .color(egui::Color32::BLACK),
+                    );
+                }
+            }
+            plot_ui.points(
+                egui::plot::Points::new(vec![[
+                    current.duration_minutes as f64 / 60.0,
+                    current.base_speed(),
+                ]])
+                .shape(egui::plot::MarkerShape::Circle)
+                .radius(6.0)
+                .color(egui::Color32::WHITE)
+                .name("Current"),
+            );
+            plot_ui.pointer_coordinate()
+        })
+        .inner;
+
+    // figures of the cell under the pointer
+    let row = hovered.and_then(|pointer| {
+        sweep.rows.iter().find(|row| {
+            (pointer.x * 60.0 - row.values[minutes_axis] as f64).abs() <= minutes_half
+                && (pointer.y - row.values[speed_axis] as f64).abs() <= speed_half
+        })
+    });
+    if let Some(row) = row {
+        let minutes = row.values[minutes_axis];
+        let cell = format!(
+            "{}% speed for {:0>2}h{:0>2}m",
+            row.values[speed_axis],
+            minutes / 60,
+            minutes % 60
+        );
+        ui.label(match &row.output {
+            Ok(output) => format!(
+                "{}: stall chance {:.4}%, daily LMD {:.0}",
+                cell, output.stall_chance, output.daily_lmd
+            ),
+            Err(error) => format!("{}: {}", cell, error),
+        });
+    }
+}
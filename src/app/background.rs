@@ -0,0 +1,286 @@
+//! Runs simulations off the UI thread: on a worker thread natively, and in a web worker on the
+//! web.
+
+use super::base_sim::{compare_layouts_with_progress, BaseError, BaseLayout, BaseOutput};
+use super::trading_sim::{
+    gold_shortage, simulate_tp_day_with_progress, simulate_trading_posts_with_progress, DronePlan,
+    DronePlanner, ParameterSweep, SimError, Solution, Solver, SweepResult, TradingPostDayOutput,
+    TradingPostProductionInput, TradingPostsOutput,
+};
+
+/// Calculation to run in the background.
+#[derive(serde::Deserialize, serde::Serialize)]
+pub enum Calculation {
+    /// Consecutive shifts, as in `simulate_tp_day`.
+    Day(Vec<TradingPostProductionInput>),
+    /// Trading posts side by side, as in `simulate_trading_posts`.
+    Posts(Vec<TradingPostProductionInput>),
+    Sweep(Box<ParameterSweep>),
+    Solve(Box<Solver>),
+    PlanDrones(Box<DronePlanner>),
+    /// Base layouts, compared side by side.
+    Layouts(Vec<BaseLayout>),
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+pub enum CalculationOutput {
+    Day(Box<TradingPostDayOutput>),
+    Posts(Box<TradingPostsOutput>),
+    Sweep(SweepResult),
+    Solve(Box<Result<Option<Solution>, SimError>>),
+    PlanDrones(Box<Result<DronePlan, SimError>>),
+    Layouts(Vec<Result<BaseOutput, BaseError>>),
+}
+
+impl Calculation {
+    /// Runs the calculation, calling `report` with the fraction done and giving up with `None`
+    /// as soon as it returns false.
+    fn run(&self, report: &mut dyn FnMut(f64) -> bool) -> Option<CalculationOutput> {
+        match self {
+            Calculation::Day(shifts) => {
+                let mut output = simulate_tp_day_with_progress(shifts, report)?;
+                // only a single shift can have a gold supply
+                if let [shift] = shifts.as_slice() {
+                    output.day.gold_shortage = gold_shortage(shift);
+                }
+                Some(CalculationOutput::Day(Box::new(output)))
+            }
+            Calculation::Posts(posts) => {
+                let mut output = simulate_trading_posts_with_progress(posts, report)?;
+                for (post, input) in output.trading_posts.iter_mut().zip(posts) {
+                    post.gold_shortage = gold_shortage(input);
+                }
+                Some(CalculationOutput::Posts(Box::new(output)))
+            }
+            Calculation::Sweep(sweep) => sweep
+                .run_with_progress(report)
+                .map(CalculationOutput::Sweep),
+            Calculation::Solve(solver) => solver
+                .solve_with_progress(report)
+                .map(|solution| CalculationOutput::Solve(Box::new(solution))),
+            Calculation::PlanDrones(planner) => planner
+                .plan_with_progress(report)
+                .map(|plan| CalculationOutput::PlanDrones(Box::new(plan))),
+            Calculation::Layouts(layouts) => {
+                compare_layouts_with_progress(layouts, report).map(CalculationOutput::Layouts)
+            }
+        }
+    }
+
+    /// Every input of the calculation.
+    #[cfg(target_arch = "wasm32")]
+    fn inputs_mut(&mut self) -> Vec<&mut TradingPostProductionInput> {
+        match self {
+            Calculation::Day(shifts) | Calculation::Posts(shifts) => shifts.iter_mut().collect(),
+            Calculation::Sweep(sweep) => vec![&mut sweep.base],
+            Calculation::Solve(solver) => vec![&mut solver.base],
+            Calculation::PlanDrones(planner) => vec![&mut planner.base],
+            Calculation::Layouts(layouts) => layouts
+                .iter_mut()
+                .flat_map(|layout| layout.trading_posts.iter_mut())
+                .collect(),
+        }
+    }
+}
+
+/// Progress and outcome of a simulation job, shared with whatever runs it.
+#[derive(Default)]
+struct JobState {
+    progress: f32,
+    output: Option<CalculationOutput>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use native::SimulationJob;
+#[cfg(target_arch = "wasm32")]
+pub use web::{run_simulation_worker, SimulationJob};
+
+#[cfg(not(target_arch = "wasm32"))]
+mod native {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    use super::{Calculation, CalculationOutput, JobState};
+
+    /// Calculation running on its own thread. Dropping the job cancels it.
+    pub struct SimulationJob {
+        state: Arc<Mutex<JobState>>,
+        cancelled: Arc<AtomicBool>,
+    }
+
+    impl SimulationJob {
+        pub fn start(calculation: Calculation, ctx: &egui::Context) -> Self {
+            let state = Arc::new(Mutex::new(JobState::default()));
+            let cancelled = Arc::new(AtomicBool::new(false));
+            let job = Self {
+                state: state.clone(),
+                cancelled: cancelled.clone(),
+            };
+            let ctx = ctx.clone();
+            std::thread::spawn(move || {
+                let output = calculation.run(&mut |fraction| {
+                    state.lock().unwrap().progress = fraction as f32;
+                    ctx.request_repaint();
+                    !cancelled.load(Ordering::Relaxed)
+                });
+                state.lock().unwrap().output = output;
+                ctx.request_repaint();
+            });
+            job
+        }
+
+        /// Fraction of the calculation done.
+        pub fn progress(&self) -> f32 {
+            self.state.lock().unwrap().progress
+        }
+
+        /// The output, once the calculation has finished.
+        pub fn take_output(&self) -> Option<CalculationOutput> {
+            self.state.lock().unwrap().output.take()
+        }
+    }
+
+    impl Drop for SimulationJob {
+        fn drop(&mut self) {
+            self.cancelled.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod web {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use wasm_bindgen::prelude::*;
+    use wasm_bindgen::JsCast;
+
+    use super::super::trading_sim::TradingPostGameData;
+    use super::{Calculation, CalculationOutput, JobState};
+
+    /// Request posted to the web worker. Game data is sent alongside the calculation, since inputs
+    /// don't serialize theirs.
+    #[derive(serde::Deserialize, serde::Serialize)]
+    struct WorkerRequest {
+        calculation: Calculation,
+        game_data: Vec<TradingPostGameData>,
+    }
+
+    /// Message posted back by the web worker.
+    #[derive(serde::Deserialize, serde::Serialize)]
+    enum WorkerMessage {
+        Progress(f32),
+        Done(CalculationOutput),
+    }
+
+    /// Calculation running in a web worker, or run on the spot where workers aren't available.
+    /// Dropping the job terminates the worker.
+    pub struct SimulationJob {
+        state: Rc<RefCell<JobState>>,
+        worker: Option<web_sys::Worker>,
+        _on_message: Option<Closure<dyn FnMut(web_sys::MessageEvent)>>,
+    }
+
+    impl SimulationJob {
+        pub fn start(mut calculation: Calculation, ctx: &egui::Context) -> Self {
+            let state = Rc::new(RefCell::new(JobState::default()));
+            let mut options = web_sys::WorkerOptions::new();
+            options.type_(web_sys::WorkerType::Module);
+            let worker = match web_sys::Worker::new_with_options("./worker.js", &options) {
+                Ok(worker) => worker,
+                Err(_) => {
+                    state.borrow_mut().output = calculation.run(&mut |_| true);
+                    return Self {
+                        state,
+                        worker: None,
+                        _on_message: None,
+                    };
+                }
+            };
+
+            let on_message = {
+                let state = state.clone();
+                let ctx = ctx.clone();
+                Closure::wrap(Box::new(move |event: web_sys::MessageEvent| {
+                    let message = event
+                        .data()
+                        .as_string()
+                        .and_then(|json| serde_json::from_str(&json).ok());
+                    match message {
+                        Some(WorkerMessage::Progress(fraction)) => {
+                            state.borrow_mut().progress = fraction
+                        }
+                        Some(WorkerMessage::Done(output)) => {
+                            state.borrow_mut().output = Some(output)
+                        }
+                        None => {}
+                    }
+                    ctx.request_repaint();
+                }) as Box<dyn FnMut(web_sys::MessageEvent)>)
+            };
+            worker.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+            let request = WorkerRequest {
+                game_data: calculation
+                    .inputs_mut()
+                    .into_iter()
+                    .map(|input| input.game_data.clone())
+                    .collect(),
+                calculation,
+            };
+            let request = serde_json::to_string(&request).expect("request serializes to JSON");
+            // the worker queues messages until its script has loaded
+            let _ = worker.post_message(&JsValue::from_str(&request));
+            Self {
+                state,
+                worker: Some(worker),
+                _on_message: Some(on_message),
+            }
+        }
+
+        /// Fraction of the calculation done.
+        pub fn progress(&self) -> f32 {
+            self.state.borrow().progress
+        }
+
+        /// The output, once the calculation has finished.
+        pub fn take_output(&self) -> Option<CalculationOutput> {
+            self.state.borrow_mut().output.take()
+        }
+    }
+
+    impl Drop for SimulationJob {
+        fn drop(&mut self) {
+            if let Some(worker) = &self.worker {
+                worker.terminate();
+            }
+        }
+    }
+
+    /// Entry point of `worker.js`: runs the calculation in `request` and posts progress and the
+    /// output back to the page.
+    #[wasm_bindgen]
+    pub fn run_simulation_worker(request: String) {
+        let scope: web_sys::DedicatedWorkerGlobalScope = js_sys::global().unchecked_into();
+        let post = |message: &WorkerMessage| {
+            let json = serde_json::to_string(message).expect("message serializes to JSON");
+            let _ = scope.post_message(&JsValue::from_str(&json));
+        };
+        let WorkerRequest {
+            mut calculation,
+            game_data,
+        } = match serde_json::from_str(&request) {
+            Ok(request) => request,
+            Err(_) => return,
+        };
+        for (input, game_data) in calculation.inputs_mut().into_iter().zip(game_data) {
+            input.game_data = game_data;
+        }
+        let output = calculation.run(&mut |fraction| {
+            post(&WorkerMessage::Progress(fraction as f32));
+            true
+        });
+        if let Some(output) = output {
+            post(&WorkerMessage::Done(output));
+        }
+    }
+}
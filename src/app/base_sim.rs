@@ -0,0 +1,304 @@
+//! Whole bases: the trading posts, factories and power plants of a layout simulated together, to
+//! compare layouts such as 243, 252 and 153.
+
+use crate::app::factory_sim::{
+    simulate_factory, FactoryError, FactoryInput, FactoryLevel, FactoryOutput, FactoryProduct,
+    GoldBalance,
+};
+use crate::app::trading_sim::{
+    simulate_trading_posts_with_progress, SimError, TradingPostPhase, TradingPostProductionInput,
+    TradingPostsOutput,
+};
+
+/// Seconds a drone takes to recharge before power plant skills.
+pub const DRONE_RECHARGE_SECONDS: f64 = 360.0;
+
+/// Rooms on the left side of the base, shared by trading posts, factories and power plants.
+pub const LEFT_SIDE_ROOMS: usize = 9;
+
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Eq, Clone, Copy, Debug)]
+pub enum PowerPlantLevel {
+    L1,
+    L2,
+    L3,
+}
+
+impl PowerPlantLevel {
+    /// Power a power plant of the level supplies.
+    pub fn power(self) -> i32 {
+        match self {
+            PowerPlantLevel::L1 => 60,
+            PowerPlantLevel::L2 => 130,
+            PowerPlantLevel::L3 => 270,
+        }
+    }
+}
+
+impl std::fmt::Display for PowerPlantLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            PowerPlantLevel::L1 => "Lv1",
+            PowerPlantLevel::L2 => "Lv2",
+            PowerPlantLevel::L3 => "Lv3",
+        })
+    }
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct PowerPlant {
+    pub level: PowerPlantLevel,
+    /// Percentage points of drone recharge speed added by the operator stationed.
+    pub drone_speed100: f64,
+}
+
+impl Default for PowerPlant {
+    fn default() -> Self {
+        Self {
+            level: PowerPlantLevel::L3,
+            drone_speed100: 0.0,
+        }
+    }
+}
+
+/// Power a trading post of the level draws.
+fn trading_post_power(phase: &TradingPostPhase) -> i32 {
+    match phase {
+        TradingPostPhase::L1 => 10,
+        TradingPostPhase::L2 => 30,
+        TradingPostPhase::L3 => 60,
+    }
+}
+
+/// Power a factory of the level draws.
+fn factory_power(level: FactoryLevel) -> i32 {
+    match level {
+        FactoryLevel::L1 => 10,
+        FactoryLevel::L2 => 30,
+        FactoryLevel::L3 => 60,
+    }
+}
+
+/// The production buildings of a base, each with an input of its own. Every building is simulated
+/// over its own shift, and compared per day.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Default)]
+#[serde(default)]
+pub struct BaseLayout {
+    pub name: String,
+    pub trading_posts: Vec<TradingPostProductionInput>,
+    pub factories: Vec<FactoryInput>,
+    pub power_plants: Vec<PowerPlant>,
+}
+
+impl BaseLayout {
+    /// The layout named by `code`, such as `243`: the counts of trading posts, factories and
+    /// power plants, in that order. Trading posts are like `trading_post`, and power plants like
+    /// `power_plant`. Factories are like `factory`, collected along with the trading posts, with
+    /// one making gold for each trading post and the rest making battle records. It is a starting
+    /// point, whose buildings can then be set one by one.
+    pub fn standard(
+        code: &str,
+        trading_post: &TradingPostProductionInput,
+        factory: &FactoryInput,
+        power_plant: &PowerPlant,
+    ) -> Result<Self, BaseError> {
+        let unknown = || BaseError::UnknownLayout(code.to_string());
+        let counts = code
+            .trim()
+            .chars()
+            .map(|c| c.to_digit(10).map(|count| count as usize))
+            .collect::<Option<Vec<usize>>>()
+            .ok_or_else(unknown)?;
+        let (trading_posts, factories, power_plants) = match counts[..] {
+            [trading_posts, factories, power_plants] => (trading_posts, factories, power_plants),
+            _ => return Err(unknown()),
+        };
+        if trading_posts + factories + power_plants > LEFT_SIDE_ROOMS {
+            return Err(unknown());
+        }
+        let factories = (0..factories)
+            .map(|index| FactoryInput {
+                product: if index < trading_posts {
+                    FactoryProduct::Gold
+                } else {
+                    FactoryProduct::BattleRecord
+                },
+                duration_minutes: trading_post.duration_minutes,
+                collection_minutes: trading_post.collection_minutes.clone(),
+                ..factory.clone()
+            })
+            .collect();
+        Ok(Self {
+            name: code.trim().to_string(),
+            trading_posts: vec![trading_post.clone(); trading_posts],
+            factories,
+            power_plants: vec![power_plant.clone(); power_plants],
+        })
+    }
+
+    /// Rooms on the left side of the base the layout takes.
+    pub fn rooms(&self) -> usize {
+        self.trading_posts.len() + self.factories.len() + self.power_plants.len()
+    }
+}
+
+/// Why a base layout can't be simulated.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, PartialEq)]
+pub enum BaseError {
+    /// The code isn't three counts that fit the base.
+    UnknownLayout(String),
+    /// The trading posts, factories and power plants take this many rooms, more than the left
+    /// side of the base has.
+    TooManyRooms(usize),
+    /// A trading post, by index, can't be simulated.
+    TradingPost(usize, SimError),
+    /// A factory, by index, can't be simulated.
+    Factory(usize, FactoryError),
+}
+
+impl std::fmt::Display for BaseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BaseError::UnknownLayout(code) => {
+                write!(f, "`{}` isn't a layout like 243", code)
+            }
+            BaseError::TooManyRooms(rooms) => write!(
+                f,
+                "buildings take {} rooms, but there are only {}",
+                rooms, LEFT_SIDE_ROOMS
+            ),
+            BaseError::TradingPost(index, error) => {
+                write!(f, "trading post {}: {}", index + 1, error)
+            }
+            BaseError::Factory(index, error) => write!(f, "factory {}: {}", index + 1, error),
+        }
+    }
+}
+
+impl std::error::Error for BaseError {}
+
+/// What a base makes each day.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Default)]
+#[serde(default)]
+pub struct BaseOutput {
+    pub trading_posts: TradingPostsOutput,
+    pub factories: Vec<FactoryOutput>,
+    pub daily_lmd: f64,
+    pub daily_exp: f64,
+    pub daily_shards: f64,
+    /// Gold made by the gold factories against the gold the trading posts use up.
+    pub gold: GoldBalance,
+    /// Drones recharged each day.
+    pub daily_drones: f64,
+    /// Drones the trading posts spend each day.
+    pub daily_drones_used: f64,
+    pub power_supplied: i32,
+    /// Power the trading posts and factories draw.
+    pub power_used: i32,
+}
+
+impl BaseOutput {
+    /// Whether the power plants supply the power the buildings draw.
+    pub fn has_power(&self) -> bool {
+        self.power_supplied >= self.power_used
+    }
+}
+
+/// Simulates every building of a base.
+pub fn simulate_base(layout: &BaseLayout) -> Result<BaseOutput, BaseError> {
+    simulate_base_with_progress(layout, &mut |_| true).expect("simulation is never cancelled")
+}
+
+/// `simulate_base` that calls `report` with the fraction done every so often, and gives up with
+/// `None` as soon as `report` returns false.
+pub fn simulate_base_with_progress(
+    layout: &BaseLayout,
+    report: &mut dyn FnMut(f64) -> bool,
+) -> Option<Result<BaseOutput, BaseError>> {
+    let rooms = layout.rooms();
+    if rooms > LEFT_SIDE_ROOMS {
+        return Some(Err(BaseError::TooManyRooms(rooms)));
+    }
+    for (index, trading_post) in layout.trading_posts.iter().enumerate() {
+        if let Err(error) = trading_post.validate() {
+            return Some(Err(BaseError::TradingPost(index, error)));
+        }
+    }
+    let mut factories = vec![];
+    for (index, factory) in layout.factories.iter().enumerate() {
+        match simulate_factory(factory) {
+            Ok(output) => factories.push(output),
+            Err(error) => return Some(Err(BaseError::Factory(index, error))),
+        }
+    }
+    // trading posts take the time, so progress is theirs
+    let trading_posts = simulate_trading_posts_with_progress(&layout.trading_posts, report)?;
+
+    // battle records are worth more EXP the higher the factory's level
+    let daily_made = |product: FactoryProduct, worth: fn(FactoryLevel) -> f64| -> f64 {
+        layout
+            .factories
+            .iter()
+            .zip(factories.iter())
+            .filter(|(_, factory)| factory.product == product)
+            .map(|(input, factory)| factory.daily_made * worth(input.level))
+            .sum()
+    };
+    let drone_speed100 = 100.0
+        + layout
+            .power_plants
+            .iter()
+            .map(|power_plant| power_plant.drone_speed100)
+            .sum::<f64>();
+    let daily_drones_used = layout
+        .trading_posts
+        .iter()
+        .zip(trading_posts.trading_posts.iter())
+        .map(|(input, output)| output.drones_used * 1440.0 / input.duration_minutes as f64)
+        .sum();
+    let trading_posts_power: i32 = layout
+        .trading_posts
+        .iter()
+        .map(|trading_post| trading_post_power(&trading_post.phase))
+        .sum();
+    let factories_power: i32 = layout
+        .factories
+        .iter()
+        .map(|factory| factory_power(factory.level))
+        .sum();
+    Some(Ok(BaseOutput {
+        daily_lmd: trading_posts.daily_lmd,
+        daily_exp: daily_made(
+            FactoryProduct::BattleRecord,
+            FactoryLevel::battle_record_exp,
+        ),
+        daily_shards: daily_made(FactoryProduct::OriginiumShard, |_| 1.0),
+        gold: GoldBalance::new(&factories, &trading_posts.trading_posts),
+        daily_drones: 86400.0 / DRONE_RECHARGE_SECONDS * drone_speed100 / 100.0,
+        daily_drones_used,
+        power_supplied: layout
+            .power_plants
+            .iter()
+            .map(|power_plant| power_plant.level.power())
+            .sum(),
+        power_used: trading_posts_power + factories_power,
+        trading_posts,
+        factories,
+    }))
+}
+
+/// Simulates each of `layouts`, calling `report` with the fraction done every so often, and
+/// giving up with `None` as soon as it returns false.
+pub fn compare_layouts_with_progress(
+    layouts: &[BaseLayout],
+    report: &mut dyn FnMut(f64) -> bool,
+) -> Option<Vec<Result<BaseOutput, BaseError>>> {
+    let count = layouts.len() as f64;
+    layouts
+        .iter()
+        .enumerate()
+        .map(|(index, layout)| {
+            simulate_base_with_progress(layout, &mut |done| report((index as f64 + done) / count))
+        })
+        .collect()
+}
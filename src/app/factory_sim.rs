@@ -0,0 +1,290 @@
+//! Production in factories, and the gold they make set against the gold trading posts use up.
+
+use crate::app::trading_sim::TradingPostProductionOutput;
+
+/// Seconds a gold bar takes at 100% speed.
+pub const GOLD_BAR_SECONDS: f64 = 4320.0;
+
+/// What a factory makes.
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Eq, Clone, Copy, Debug)]
+pub enum FactoryProduct {
+    Gold,
+    /// Battle records of the factory's level.
+    BattleRecord,
+    OriginiumShard,
+}
+
+impl FactoryProduct {
+    /// Seconds one item takes at 100% speed in a factory of `level`.
+    pub fn seconds(self, level: FactoryLevel) -> f64 {
+        match self {
+            FactoryProduct::Gold => GOLD_BAR_SECONDS,
+            FactoryProduct::BattleRecord => match level {
+                FactoryLevel::L1 => 2700.0,
+                FactoryLevel::L2 => 5400.0,
+                FactoryLevel::L3 => 10800.0,
+            },
+            FactoryProduct::OriginiumShard => 3600.0,
+        }
+    }
+}
+
+impl Default for FactoryProduct {
+    fn default() -> Self {
+        FactoryProduct::Gold
+    }
+}
+
+impl std::fmt::Display for FactoryProduct {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            FactoryProduct::Gold => "Gold",
+            FactoryProduct::BattleRecord => "Battle records",
+            FactoryProduct::OriginiumShard => "Originium shards",
+        })
+    }
+}
+
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Eq, Clone, Copy, Debug)]
+pub enum FactoryLevel {
+    L1,
+    L2,
+    L3,
+}
+
+impl FactoryLevel {
+    /// Items a factory of the level holds before skills.
+    pub fn capacity(self) -> i32 {
+        match self {
+            FactoryLevel::L1 => 24,
+            FactoryLevel::L2 => 36,
+            FactoryLevel::L3 => 54,
+        }
+    }
+
+    /// EXP in a battle record a factory of the level makes: drill records at Lv1, frontline
+    /// records at Lv2 and tactical records at Lv3.
+    pub fn battle_record_exp(self) -> f64 {
+        match self {
+            FactoryLevel::L1 => 200.0,
+            FactoryLevel::L2 => 400.0,
+            FactoryLevel::L3 => 1000.0,
+        }
+    }
+}
+
+impl std::fmt::Display for FactoryLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            FactoryLevel::L1 => "Lv1",
+            FactoryLevel::L2 => "Lv2",
+            FactoryLevel::L3 => "Lv3",
+        })
+    }
+}
+
+/// Skill of an operator stationed in the factory.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug, PartialEq)]
+pub struct FactorySkill {
+    pub name: String,
+    /// Percentage points of speed added.
+    pub speed100: f64,
+    /// Items added to what the factory holds.
+    pub capacity: i32,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct FactoryInput {
+    pub duration_minutes: i32,
+    pub product: FactoryProduct,
+    /// Sets the items the factory holds before skills, and the battle records it makes.
+    pub level: FactoryLevel,
+    /// Speed in percent before skills.
+    pub speed100: f64,
+    pub skills: Vec<FactorySkill>,
+    /// Items waiting when the shift starts.
+    pub start_stored: i32,
+    /// Minutes into the shift at which the items are collected.
+    pub collection_minutes: Vec<i32>,
+}
+
+impl Default for FactoryInput {
+    fn default() -> Self {
+        Self {
+            duration_minutes: 720,
+            product: FactoryProduct::Gold,
+            level: FactoryLevel::L3,
+            speed100: 100.0,
+            skills: vec![],
+            start_stored: 0,
+            collection_minutes: vec![],
+        }
+    }
+}
+
+impl FactoryInput {
+    /// Speed in percent once skills are applied.
+    pub fn speed(&self) -> f64 {
+        self.speed100 + self.skills.iter().map(|skill| skill.speed100).sum::<f64>()
+    }
+
+    /// Items the factory holds once skills are applied.
+    pub fn item_limit(&self) -> i32 {
+        self.level.capacity() + self.skills.iter().map(|skill| skill.capacity).sum::<i32>()
+    }
+
+    /// Checks that the input can be simulated.
+    pub fn validate(&self) -> Result<(), FactoryError> {
+        if self.duration_minutes <= 0 {
+            return Err(FactoryError::NonPositiveDuration(self.duration_minutes));
+        }
+        let speed = self.speed();
+        if speed.is_nan() || speed <= 0.0 {
+            return Err(FactoryError::NonPositiveSpeed(speed));
+        }
+        let item_limit = self.item_limit();
+        if item_limit <= 0 {
+            return Err(FactoryError::NonPositiveCapacity(item_limit));
+        }
+        if self.start_stored < 0 {
+            return Err(FactoryError::NegativeStartStored);
+        }
+        Ok(())
+    }
+}
+
+/// Why a factory input can't be simulated.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, PartialEq)]
+pub enum FactoryError {
+    /// The shift lasts this many minutes, and no longer.
+    NonPositiveDuration(i32),
+    /// The speed after skills, in percent.
+    NonPositiveSpeed(f64),
+    /// The items held after skills.
+    NonPositiveCapacity(i32),
+    /// The factory starts with a negative number of items.
+    NegativeStartStored,
+}
+
+impl std::fmt::Display for FactoryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FactoryError::NonPositiveDuration(minutes) => {
+                write!(f, "shift must last more than 0 minutes, not {}", minutes)
+            }
+            FactoryError::NonPositiveSpeed(speed100) => {
+                write!(f, "speed must be above 0%, not {}%", speed100)
+            }
+            FactoryError::NonPositiveCapacity(capacity) => {
+                write!(f, "factory must hold more than 0 items, not {}", capacity)
+            }
+            FactoryError::NegativeStartStored => {
+                write!(f, "factory can't start with negative items")
+            }
+        }
+    }
+}
+
+impl std::error::Error for FactoryError {}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct FactoryOutput {
+    pub product: FactoryProduct,
+    /// Items made during the shift.
+    pub total_made: f64,
+    pub daily_made: f64,
+    /// Minutes the factory stands full during the shift.
+    pub full_minutes: f64,
+    /// Items waiting when the shift ends.
+    pub stored_at_end: i32,
+}
+
+/// Simulates one factory making its product over a shift. Production stops while the factory is
+/// full, and starts on a new item once the items are collected.
+pub fn simulate_factory(input: &FactoryInput) -> Result<FactoryOutput, FactoryError> {
+    input.validate()?;
+    let item_seconds = input.product.seconds(input.level) * 100.0 / input.speed();
+    let item_limit = input.item_limit();
+    let duration = (input.duration_minutes * 60) as f64;
+    let mut collection_minutes: Vec<i32> = input
+        .collection_minutes
+        .iter()
+        .copied()
+        .filter(|minutes| (0..input.duration_minutes).contains(minutes))
+        .collect();
+    collection_minutes.sort_unstable();
+    let collections = collection_minutes
+        .iter()
+        .map(|minutes| (minutes * 60) as f64)
+        .chain(std::iter::once(duration));
+
+    let mut stored = input.start_stored.min(item_limit);
+    // seconds already worked on the item in progress
+    let mut progress = 0.0;
+    let mut made = 0;
+    let mut full_time = 0.0;
+    let mut from = 0.0;
+    for to in collections {
+        let room = item_limit - stored;
+        let worked = progress + (to - from);
+        let items = ((worked / item_seconds).floor() as i32).min(room);
+        if items == room {
+            // full once the last item that fits is made
+            full_time += worked - items as f64 * item_seconds;
+            progress = 0.0;
+        } else {
+            progress = worked - items as f64 * item_seconds;
+        }
+        stored += items;
+        made += items;
+        if to < duration {
+            stored = 0;
+        }
+        from = to;
+    }
+    let total_made = made as f64;
+    Ok(FactoryOutput {
+        product: input.product,
+        total_made,
+        daily_made: total_made * 1440.0 / input.duration_minutes as f64,
+        full_minutes: full_time / 60.0,
+        stored_at_end: stored,
+    })
+}
+
+/// Gold bars made by factories set against the gold bars trading posts use up, per day.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct GoldBalance {
+    pub produced: f64,
+    pub consumed: f64,
+}
+
+impl GoldBalance {
+    /// Gold made by those of `factories` that make gold, against the gold `trading_posts` use up.
+    pub fn new(factories: &[FactoryOutput], trading_posts: &[TradingPostProductionOutput]) -> Self {
+        Self {
+            produced: factories
+                .iter()
+                .filter(|factory| factory.product == FactoryProduct::Gold)
+                .map(|factory| factory.daily_made)
+                .sum(),
+            // trading posts count the gold they use up as negative
+            consumed: -trading_posts
+                .iter()
+                .map(|trading_post| trading_post.daily_gold)
+                .sum::<f64>(),
+        }
+    }
+
+    /// Gold bars left over each day, or short when negative.
+    pub fn net(&self) -> f64 {
+        self.produced - self.consumed
+    }
+
+    /// Whether the factories make at least the gold the trading posts use up.
+    pub fn keeps_up(&self) -> bool {
+        self.net() >= 0.0
+    }
+}
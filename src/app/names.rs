@@ -0,0 +1,8 @@
+//! Operator names, as typed into inputs and the roster.
+
+/// Whether `name` refers to the operator going by `own`, ignoring case and surrounding spaces.
+/// Nobody goes by an empty name.
+pub fn is_named(own: &str, name: &str) -> bool {
+    let own = own.trim();
+    !own.is_empty() && own.eq_ignore_ascii_case(name.trim())
+}
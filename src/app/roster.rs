@@ -0,0 +1,93 @@
+//! Operators the player owns, so that operator pickers offer what they can actually field.
+
+use crate::app::names::is_named;
+use crate::app::trading_sim::{
+    HighRarityOperatorPhase, TradingPostProductionInput, TradingPostTailoringSkill,
+};
+
+/// Operators the trading post editor picks by name, in the order of `unowned_in`.
+pub const NAMED_OPERATORS: [&str; 3] = ["Jaye", "Tequila", "Proviso"];
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct OwnedOperator {
+    pub name: String,
+    /// Elite phase, from E0 to E2.
+    pub elite: HighRarityOperatorPhase,
+    /// Level within the elite phase, from 1.
+    pub level: i32,
+    /// Tailoring skill the operator brings to a trading post at their elite phase, if any.
+    pub tailoring: Option<TradingPostTailoringSkill>,
+}
+
+impl Default for OwnedOperator {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            elite: HighRarityOperatorPhase::E0,
+            level: 1,
+            tailoring: None,
+        }
+    }
+}
+
+impl OwnedOperator {
+    /// Whether the operator goes by `name`, ignoring case and surrounding spaces.
+    pub fn is(&self, name: &str) -> bool {
+        is_named(&self.name, name)
+    }
+}
+
+/// Operators the player owns. An empty roster limits nothing, so that it can be left out.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct Roster {
+    pub operators: Vec<OwnedOperator>,
+}
+
+impl Roster {
+    /// The owned operator named `name`, if any.
+    pub fn get(&self, name: &str) -> Option<&OwnedOperator> {
+        self.operators.iter().find(|operator| operator.is(name))
+    }
+
+    /// Whether the operator named `name` can be fielded at `phase`: nobody always can, and an
+    /// operator only up to the elite phase they are owned at.
+    pub fn allows(&self, name: &str, phase: &HighRarityOperatorPhase) -> bool {
+        match phase {
+            HighRarityOperatorPhase::None => true,
+            _ if self.operators.is_empty() => true,
+            _ => self.get(name).map_or(false, |owned| owned.elite >= *phase),
+        }
+    }
+
+    /// Owned operators that bring a tailoring skill.
+    pub fn tailors(&self) -> impl Iterator<Item = &OwnedOperator> {
+        self.operators
+            .iter()
+            .filter(|operator| operator.tailoring.is_some())
+    }
+
+    /// Whether an owned operator brings the tailoring skill.
+    pub fn allows_tailoring(&self, skill: &TradingPostTailoringSkill) -> bool {
+        self.operators.is_empty()
+            || self
+                .tailors()
+                .any(|operator| operator.tailoring.as_ref() == Some(skill))
+    }
+
+    /// Operators picked by name in `input` that the roster can't field at their phase.
+    pub fn unowned_in(&self, input: &TradingPostProductionInput) -> Vec<&'static str> {
+        let phases = [
+            &input.jaye_phase,
+            &input.tequila_phase,
+            &input.proviso_phase,
+        ];
+        NAMED_OPERATORS
+            .into_iter()
+            .zip(phases)
+            .filter(|(name, phase)| !self.allows(name, phase))
+            .map(|(name, _)| name)
+            .collect()
+    }
+}
@@ -1,3 +1,39 @@
+mod drones;
+mod game_data;
+mod gold_supply;
+mod histogram;
+mod monte_carlo;
+mod morale;
+mod posts;
+pub mod skills;
+mod solver;
+mod speed;
+mod sweep;
+mod timeline;
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use drones::{Accelerated, DRONE_SECONDS};
+pub use drones::{DronePlan, DronePlanner, DroneUsage};
+pub use game_data::{GameDataError, TradingPostGameData};
+pub use gold_supply::{gold_shortage, simulate_gold_shortage, GoldShortage, GoldSupply};
+use histogram::Histogram;
+pub use monte_carlo::{
+    simulate_tp_day_monte_carlo, simulate_tp_monte_carlo, MonteCarloDayOutput, MonteCarloOutput,
+};
+pub use morale::{MoraleShortfall, OperatorMorale, MAX_MORALE};
+pub use posts::{
+    simulate_trading_posts, simulate_trading_posts_with_progress, try_simulate_trading_posts,
+    TradingPostsError, TradingPostsOutput,
+};
+pub use skills::TradingPostSkill;
+pub use solver::{Solution, Solver, StallLimit};
+pub use speed::{SpeedBreakdown, SpeedChange, SpeedContribution, SpeedEffect};
+pub use sweep::{ParameterSweep, SweepAxis, SweepField, SweepResult, SweepRow};
+pub use timeline::ShiftTimeline;
+use timeline::TimelineRecorder;
+
 #[derive(serde::Deserialize, serde::Serialize, PartialEq, Eq, Clone)]
 pub enum TradingPostPhase {
     L1,
@@ -5,12 +41,27 @@ pub enum TradingPostPhase {
     L3,
 }
 
-impl ToString for TradingPostPhase {
-    fn to_string(&self) -> String {
-        match self {
-            TradingPostPhase::L1 => "Lv1".to_string(),
-            TradingPostPhase::L2 => "Lv2".to_string(),
-            TradingPostPhase::L3 => "Lv3".to_string(),
+impl std::fmt::Display for TradingPostPhase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            TradingPostPhase::L1 => "Lv1",
+            TradingPostPhase::L2 => "Lv2",
+            TradingPostPhase::L3 => "Lv3",
+        })
+    }
+}
+
+impl std::str::FromStr for TradingPostPhase {
+    type Err = String;
+
+    /// Parses `1` to `3`, optionally prefixed with `l` or `lv`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lower = s.to_ascii_lowercase();
+        match lower.trim_start_matches("lv").trim_start_matches('l') {
+            "1" => Ok(TradingPostPhase::L1),
+            "2" => Ok(TradingPostPhase::L2),
+            "3" => Ok(TradingPostPhase::L3),
+            _ => Err(format!("unknown trading post level `{}`", s)),
         }
     }
 }
@@ -21,16 +72,29 @@ pub enum TradingPostTailoringSkill {
     Beta,
 }
 
-impl ToString for TradingPostTailoringSkill {
-    fn to_string(&self) -> String {
-        match self {
-            TradingPostTailoringSkill::Alpha => "Alpha".to_string(),
-            TradingPostTailoringSkill::Beta => "Beta".to_string(),
+impl std::fmt::Display for TradingPostTailoringSkill {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            TradingPostTailoringSkill::Alpha => "Alpha",
+            TradingPostTailoringSkill::Beta => "Beta",
+        })
+    }
+}
+
+impl std::str::FromStr for TradingPostTailoringSkill {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "alpha" => Ok(TradingPostTailoringSkill::Alpha),
+            "beta" => Ok(TradingPostTailoringSkill::Beta),
+            _ => Err(format!("unknown tailoring skill `{}`", s)),
         }
     }
 }
 
-#[derive(serde::Deserialize, serde::Serialize, PartialEq, Eq)]
+/// Elite phase of an operator, or `None` when none is picked. Phases compare in order.
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Eq, PartialOrd, Ord, Clone)]
 pub enum HighRarityOperatorPhase {
     None,
     E0,
@@ -38,28 +102,125 @@ pub enum HighRarityOperatorPhase {
     E2,
 }
 
-impl ToString for HighRarityOperatorPhase {
-    fn to_string(&self) -> String {
+impl HighRarityOperatorPhase {
+    /// Elite phase of a picked operator, or `None` when none is picked.
+    pub fn elite(&self) -> Option<usize> {
+        match self {
+            HighRarityOperatorPhase::None => None,
+            HighRarityOperatorPhase::E0 => Some(0),
+            HighRarityOperatorPhase::E1 => Some(1),
+            HighRarityOperatorPhase::E2 => Some(2),
+        }
+    }
+}
+
+impl std::fmt::Display for HighRarityOperatorPhase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            HighRarityOperatorPhase::None => "None",
+            HighRarityOperatorPhase::E0 => "Elite 0",
+            HighRarityOperatorPhase::E1 => "Elite 1",
+            HighRarityOperatorPhase::E2 => "Elite 2",
+        })
+    }
+}
+
+impl std::str::FromStr for HighRarityOperatorPhase {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "none" => Ok(HighRarityOperatorPhase::None),
+            "e0" => Ok(HighRarityOperatorPhase::E0),
+            "e1" => Ok(HighRarityOperatorPhase::E1),
+            "e2" => Ok(HighRarityOperatorPhase::E2),
+            _ => Err(format!("unknown elite phase `{}`", s)),
+        }
+    }
+}
+
+/// Which order count Jaye's skill scales with.
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Eq, Clone)]
+pub enum JayeOrderCount {
+    /// Orders waiting while the order is produced.
+    BeforeFinish,
+    /// Orders waiting once the order being produced has joined them.
+    AfterFinish,
+}
+
+impl std::fmt::Display for JayeOrderCount {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            HighRarityOperatorPhase::None => "None".to_string(),
-            HighRarityOperatorPhase::E0 => "Elite 0".to_string(),
-            HighRarityOperatorPhase::E1 => "Elite 1".to_string(),
-            HighRarityOperatorPhase::E2 => "Elite 2".to_string(),
+            JayeOrderCount::BeforeFinish => write!(f, "Before finish"),
+            JayeOrderCount::AfterFinish => write!(f, "After finish"),
         }
     }
 }
 
-#[derive(serde::Deserialize, serde::Serialize)]
+impl std::str::FromStr for JayeOrderCount {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "before" => Ok(JayeOrderCount::BeforeFinish),
+            "after" => Ok(JayeOrderCount::AfterFinish),
+            _ => Err(format!("unknown Jaye order count `{}`", s)),
+        }
+    }
+}
+
+/// Display names of the order types, in the order used by every per-type table.
+pub const ORDER_NAMES: [&str; 3] = ["2-bar", "3-bar", "4-bar"];
+
+/// What the trading post holds when the shift starts.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Default)]
+#[serde(default)]
+pub struct TradingPostStartState {
+    /// Orders waiting for collection, by order type.
+    pub stored_orders: [i32; 3],
+    /// Minutes left on the order in progress. When `None`, the order is assumed to be uniformly
+    /// far along.
+    pub minutes_left: Option<i32>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone)]
 #[serde(default)]
 pub struct TradingPostProductionInput {
     pub duration_minutes: i32,
     pub phase: TradingPostPhase,
     pub capacity: i32,
+    /// Speed in percent before skills, when there is no speed breakdown.
     pub speed100: i32,
+    /// Parts that make up the speed before skills. When there are any, they give the speed
+    /// rather than `speed100`.
+    pub speed_breakdown: SpeedBreakdown,
+    /// Changes of the speed before skills during the shift. Each takes over from the speed
+    /// breakdown or `speed100` from its minute on.
+    pub speed_schedule: Vec<SpeedChange>,
     pub tailoring_ramped: Vec<(TradingPostTailoringSkill, i32)>,
     pub tequila_phase: HighRarityOperatorPhase,
     pub proviso_phase: HighRarityOperatorPhase,
     pub jaye_phase: HighRarityOperatorPhase,
+    pub jaye_order_count: JayeOrderCount,
+    /// Ignored for every shift of a day plan but the first, which hands its state on instead.
+    pub start_state: TradingPostStartState,
+    /// Minutes into the shift at which all stored orders are collected. A collection at minute 0
+    /// empties the post as the shift starts, such as when a new team takes over.
+    pub collection_minutes: Vec<i32>,
+    /// Drones spent during the shift.
+    pub drone_usages: Vec<DroneUsage>,
+    /// Morale of the operators whose effects stop when it runs out.
+    pub morale: Vec<OperatorMorale>,
+    /// Gold on hand to deliver orders with, for `gold_shortage` to estimate how it holds up.
+    /// The rest of the simulation assumes there is always enough. Day plans of several shifts
+    /// can't have one.
+    pub gold_supply: Option<GoldSupply>,
+    /// Not saved with the input, so that saved inputs pick up newer bundled data.
+    #[serde(skip)]
+    pub game_data: TradingPostGameData,
+    /// Skills of operators without a field of their own, applied after the ones that have one.
+    #[serde(skip)]
+    pub extra_skills: Vec<Arc<dyn TradingPostSkill>>,
 }
 
 impl Default for TradingPostProductionInput {
@@ -69,25 +230,360 @@ impl Default for TradingPostProductionInput {
             phase: TradingPostPhase::L3,
             capacity: 10,
             speed100: 200,
+            speed_breakdown: SpeedBreakdown::default(),
+            speed_schedule: vec![],
             tailoring_ramped: vec![],
             tequila_phase: HighRarityOperatorPhase::None,
             proviso_phase: HighRarityOperatorPhase::None,
             jaye_phase: HighRarityOperatorPhase::None,
+            jaye_order_count: JayeOrderCount::BeforeFinish,
+            start_state: TradingPostStartState::default(),
+            collection_minutes: vec![],
+            drone_usages: vec![],
+            morale: vec![],
+            gold_supply: None,
+            game_data: TradingPostGameData::bundled(),
+            extra_skills: vec![],
+        }
+    }
+}
+
+impl TradingPostProductionInput {
+    /// Every skill in effect during the shift.
+    pub fn skills(&self) -> Vec<Arc<dyn TradingPostSkill>> {
+        let mut skills: Vec<Arc<dyn TradingPostSkill>> = vec![];
+        if !self.speed_breakdown.is_empty() && !self.morale.is_empty() {
+            skills.push(Arc::new(skills::ExhaustedParts {
+                breakdown: self.speed_breakdown.clone(),
+                operators: self.morale.clone(),
+            }));
+        }
+        if !self.speed_schedule.is_empty() {
+            skills.push(Arc::new(skills::SpeedSchedule(self.speed_schedule.clone())));
+        }
+        // the skill of the operator named `name`, for as long as they have morale
+        let until_exhausted =
+            |name: &str, skill: Arc<dyn TradingPostSkill>| -> Arc<dyn TradingPostSkill> {
+                match self.exhausted_seconds(name) {
+                    Some(exhausted_at) => Arc::new(skills::UntilExhausted {
+                        skill,
+                        exhausted_at,
+                    }),
+                    None => skill,
+                }
+            };
+        // tailoring operators go by the name of their skill
+        for (skill, ramped_minutes) in self.tailoring_ramped.iter() {
+            let index = match skill {
+                TradingPostTailoringSkill::Alpha => 0,
+                TradingPostTailoringSkill::Beta => 1,
+            };
+            let tailoring = Arc::new(skills::Tailoring {
+                ramp_multiplier: self.game_data.tailoring_ramp_multipliers[index],
+                ramped_minutes: *ramped_minutes,
+            });
+            skills.push(until_exhausted(&skill.to_string(), tailoring));
+        }
+        if let Some(elite) = self.tequila_phase.elite() {
+            let tequila = Arc::new(skills::Tequila {
+                bonus_lmd: self.game_data.tequila_bonus_lmd[elite],
+            });
+            skills.push(until_exhausted("Tequila", tequila));
+        }
+        if let Some(elite) = self.proviso_phase.elite() {
+            let proviso = Arc::new(skills::Proviso {
+                extra_gold: self.game_data.proviso_extra_gold[elite],
+                lmd_per_gold: self.game_data.proviso_lmd_per_gold,
+            });
+            skills.push(until_exhausted("Proviso", proviso));
+        }
+        if let Some(elite) = self.jaye_phase.elite() {
+            let jaye = Arc::new(skills::Jaye {
+                order_count: self.jaye_order_count.clone(),
+                speed_per_order: self.game_data.jaye_speed_per_order[elite],
+                capacity_change: self.game_data.jaye_capacity_change[elite],
+            });
+            skills.push(until_exhausted("Jaye", jaye));
+        }
+        skills.extend(self.extra_skills.iter().cloned());
+        skills
+    }
+
+    /// Speed in percent before skills, from the speed breakdown if there is one.
+    pub fn base_speed(&self) -> f64 {
+        if self.speed_breakdown.is_empty() {
+            self.speed100 as f64
+        } else {
+            self.speed_breakdown.speed()
+        }
+    }
+
+    /// Seconds into the shift at which the operator named `name` runs out of morale, if they
+    /// have morale set and ever do.
+    fn exhausted_seconds(&self, name: &str) -> Option<i32> {
+        self.morale
+            .iter()
+            .filter(|operator| operator.is(name))
+            .filter_map(OperatorMorale::exhausted_seconds)
+            .min()
+    }
+
+    /// The first operator to run out of morale before the shift ends, if any do.
+    pub fn morale_shortfall(&self) -> Option<MoraleShortfall> {
+        self.morale
+            .iter()
+            .filter_map(|operator| Some((operator, operator.exhausted_seconds()? / 60)))
+            .filter(|(_, minutes)| *minutes < self.duration_minutes)
+            .min_by_key(|(_, minutes)| *minutes)
+            .map(|(operator, minutes)| MoraleShortfall {
+                name: operator.name.clone(),
+                exhausted_minutes: minutes,
+                duration_minutes: self.duration_minutes,
+            })
+    }
+
+    /// Checks that the input can be simulated.
+    pub fn validate(&self) -> Result<(), SimError> {
+        self.game_data
+            .validate()
+            .map_err(SimError::InvalidGameData)?;
+        if self.duration_minutes <= 0 {
+            return Err(SimError::NonPositiveDuration(self.duration_minutes));
+        }
+        let base_speeds = std::iter::once(self.base_speed())
+            .chain(self.speed_schedule.iter().map(|change| change.speed100));
+        for base_speed in base_speeds {
+            if base_speed.is_nan() || base_speed <= 0.0 {
+                return Err(SimError::NonPositiveSpeed(base_speed));
+            }
+        }
+        if self.morale.iter().any(|operator| {
+            !(0.0..=MAX_MORALE).contains(&operator.morale)
+                || operator.drain_per_hour.is_nan()
+                || operator.drain_per_hour < 0.0
+        }) {
+            return Err(SimError::InvalidMorale);
+        }
+        let skills = self.skills();
+        let order_limit = self.order_limit(&skills);
+        if order_limit <= 0 {
+            return Err(SimError::NonPositiveCapacity(order_limit));
+        }
+        // the speed before skills only changes at the start, on schedule and as morale runs out
+        let change_times = std::iter::once(0)
+            .chain(
+                self.speed_schedule
+                    .iter()
+                    .map(|change| change.from_minutes.max(0) * 60),
+            )
+            .chain(
+                self.morale
+                    .iter()
+                    .filter_map(OperatorMorale::exhausted_seconds),
+            );
+        for elapsed_time in change_times {
+            for stored in 0..order_limit as usize {
+                let speed100 = self.speed_at(&skills, stored, elapsed_time);
+                if speed100.is_nan() || speed100 <= 0.0 {
+                    return Err(SimError::StalledBySkills { stored, speed100 });
+                }
+            }
+        }
+        let start_state = &self.start_state;
+        if start_state.stored_orders.iter().any(|&count| count < 0)
+            || start_state.minutes_left.map_or(false, |m| m < 0)
+        {
+            return Err(SimError::NegativeStartState);
+        }
+        if self
+            .drone_usages
+            .iter()
+            .any(|usage| usage.minute < 0 || usage.drones < 0)
+        {
+            return Err(SimError::NegativeDroneUsage);
+        }
+        if let Some(supply) = &self.gold_supply {
+            if supply.stock < 0 || supply.per_hour.is_nan() || supply.per_hour < 0.0 {
+                return Err(SimError::NegativeGoldSupply);
+            }
+        }
+        Ok(())
+    }
+
+    /// Order limit once `skills` are applied.
+    fn order_limit(&self, skills: &[Arc<dyn TradingPostSkill>]) -> i32 {
+        skills
+            .iter()
+            .fold(self.capacity, |capacity, skill| skill.capacity(capacity))
+    }
+
+    /// Speed in percent `elapsed_time` seconds into the shift with `stored` orders waiting, once
+    /// `skills` are applied.
+    fn speed_at(
+        &self,
+        skills: &[Arc<dyn TradingPostSkill>],
+        stored: usize,
+        elapsed_time: i32,
+    ) -> f64 {
+        skills.iter().fold(self.base_speed(), |speed100, skill| {
+            skill.speed_at(speed100, stored, elapsed_time)
+        })
+    }
+}
+
+/// Why an input can't be simulated.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, PartialEq)]
+pub enum SimError {
+    /// The shift lasts this many minutes, and no longer.
+    NonPositiveDuration(i32),
+    /// The speed before skills, in percent.
+    NonPositiveSpeed(f64),
+    /// The order limit once skills are applied.
+    NonPositiveCapacity(i32),
+    /// Skills bring the speed down to `speed100` percent with `stored` orders waiting, so the
+    /// post never finishes another order.
+    StalledBySkills { stored: usize, speed100: f64 },
+    /// The start state has a negative order count or time left.
+    NegativeStartState,
+    /// A drone usage has a negative minute or drone count.
+    NegativeDroneUsage,
+    /// An operator's morale is outside 0 to 24, or drains at a negative rate.
+    InvalidMorale,
+    /// The gold supply has a negative stock or income.
+    NegativeGoldSupply,
+    /// A shift of a day plan of several shifts has a gold supply.
+    GoldSupplyInDayPlan,
+    /// The game data can't be simulated with.
+    InvalidGameData(GameDataError),
+}
+
+impl std::fmt::Display for SimError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SimError::NonPositiveDuration(minutes) => {
+                write!(f, "shift must last more than 0 minutes, not {}", minutes)
+            }
+            SimError::NonPositiveSpeed(speed100) => {
+                write!(f, "speed must be above 0%, not {}%", speed100)
+            }
+            SimError::NonPositiveCapacity(capacity) => {
+                write!(
+                    f,
+                    "order limit after skills must be above 0, not {}",
+                    capacity
+                )
+            }
+            SimError::StalledBySkills { stored, speed100 } => write!(
+                f,
+                "skills bring speed down to {}% with {} orders waiting",
+                speed100, stored
+            ),
+            SimError::NegativeStartState => {
+                write!(f, "start state can't have negative orders or time left")
+            }
+            SimError::NegativeDroneUsage => {
+                write!(f, "drone usages can't have negative minutes or drones")
+            }
+            SimError::InvalidMorale => {
+                write!(f, "morale must be from 0 to 24, and drain at 0 or more")
+            }
+            SimError::NegativeGoldSupply => {
+                write!(f, "gold stock and income can't be negative")
+            }
+            SimError::GoldSupplyInDayPlan => {
+                write!(
+                    f,
+                    "gold supply only works for a single shift, not a day plan"
+                )
+            }
+            SimError::InvalidGameData(error) => write!(f, "invalid game data: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for SimError {}
+
+/// Probability distribution of one shift total, binned at a fixed width.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Default)]
+#[serde(default)]
+pub struct ShiftDistribution {
+    pub bin_width: f64,
+    /// `(value, probability)` of every non-empty bin, in increasing value order. The value is the
+    /// mean of the bin's outcomes rather than its lower edge.
+    pub pmf: Vec<(f64, f64)>,
+    pub mean: f64,
+    pub variance: f64,
+    pub p5: f64,
+    pub p50: f64,
+    pub p95: f64,
+}
+
+impl ShiftDistribution {
+    fn from_histogram(histogram: &Histogram, bin_width: f64) -> Self {
+        let total = histogram.total();
+        if total <= 0.0 {
+            return Self::default();
+        }
+        let pmf: Vec<(f64, f64)> = histogram
+            .iter()
+            .map(|(position, mass)| (position * bin_width, mass / total))
+            .collect();
+        let (mean, variance) = histogram.moments();
+        let percentile = |q: f64| {
+            let mut cdf = 0.0;
+            for (value, p) in pmf.iter() {
+                cdf += p;
+                if cdf >= q {
+                    return *value;
+                }
+            }
+            pmf.last().map(|(value, _)| *value).unwrap_or(0.0)
+        };
+        Self {
+            bin_width,
+            mean: mean * bin_width,
+            variance: variance * bin_width * bin_width,
+            p5: percentile(0.05),
+            p50: percentile(0.5),
+            p95: percentile(0.95),
+            pmf,
         }
     }
+
+    pub fn std_dev(&self) -> f64 {
+        self.variance.sqrt()
+    }
 }
 
-#[derive(serde::Deserialize, serde::Serialize, Clone, Copy)]
+#[derive(serde::Deserialize, serde::Serialize, Clone)]
 #[serde(default)]
 pub struct TradingPostProductionOutput {
+    /// Chance of stalling at least once during the shift.
     pub stall_chance: f64,
     pub average_stall_time: f64,
+    /// LMD credited by the end of the shift, including orders stored at the start.
     pub total_lmd: f64,
+    /// Gold credited by the end of the shift, including orders stored at the start.
     pub total_gold: f64,
     pub daily_lmd: f64,
     pub daily_gold: f64,
     pub net_lmd_speed: f64,
     pub net_gold_speed: f64,
+    /// Expected speed averaged over the shift, in percent, with a full post standing still.
+    pub average_speed: f64,
+    /// Expected drones spent. Drones are kept when the post is full as they come up.
+    pub drones_used: f64,
+    /// How the gold supply holds up. Estimating it plays out thousands of shifts, so simulating
+    /// leaves it out, and whatever shows it fills it in with `gold_shortage`. The rest of the
+    /// output assumes there is always gold to deliver orders with.
+    pub gold_shortage: Option<GoldShortage>,
+    pub lmd_distribution: ShiftDistribution,
+    pub gold_distribution: ShiftDistribution,
+    /// Minutes into the shift at which each collection interval ends.
+    pub interval_end_minutes: Vec<i32>,
+    /// Chance of stalling in each collection interval.
+    pub interval_stall_chance: Vec<f64>,
+    pub timeline: ShiftTimeline,
 }
 
 impl Default for TradingPostProductionOutput {
@@ -101,191 +597,1212 @@ impl Default for TradingPostProductionOutput {
             daily_gold: 0f64,
             net_lmd_speed: 0f64,
             net_gold_speed: 0f64,
+            average_speed: 0f64,
+            drones_used: 0f64,
+            gold_shortage: None,
+            lmd_distribution: ShiftDistribution::default(),
+            gold_distribution: ShiftDistribution::default(),
+            interval_end_minutes: vec![],
+            interval_stall_chance: vec![],
+            timeline: ShiftTimeline::default(),
         }
     }
 }
 
-pub fn simulate_tp_production(input: &TradingPostProductionInput) -> TradingPostProductionOutput {
-    let tailoring_ramp_spec = match input.phase {
-        TradingPostPhase::L1 => [(1.0, 1.0), (0.0, 0.0), (0.0, 0.0)],
-        TradingPostPhase::L2 => [(0.7, 0.4), (0.3, 0.6), (0.0, 0.0)],
-        TradingPostPhase::L3 => [(0.3, 0.05), (0.5, 0.1), (0.2, 0.85)],
-    };
+/// Output of a day plan, shift by shift and as a whole.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Default)]
+#[serde(default)]
+pub struct TradingPostDayOutput {
+    /// Output of each shift. Totals count what the shift itself produced, plus the orders stored
+    /// at the start of the first shift; distributions are only tracked for the whole day.
+    pub shifts: Vec<TradingPostProductionOutput>,
+    /// Output over the whole day, with the collection intervals of every shift in turn.
+    pub day: TradingPostProductionOutput,
+}
 
-    let proviso_mod = match input.proviso_phase {
-        HighRarityOperatorPhase::None => 0,
-        HighRarityOperatorPhase::E0 => 1,
-        HighRarityOperatorPhase::E1 => 1,
-        HighRarityOperatorPhase::E2 => 2,
-    };
-    let bonus_lmd = [
-        0.0,
-        0.0,
-        match input.tequila_phase {
-            HighRarityOperatorPhase::None => 0.0,
-            HighRarityOperatorPhase::E0 => 250.0,
-            HighRarityOperatorPhase::E1 => 250.0,
-            HighRarityOperatorPhase::E2 => 500.0,
-        },
-    ];
-
-    let order_lmd = [1000 + 500 * proviso_mod, 1500 + 500 * proviso_mod, 2000];
-    let order_gold = [-2 - proviso_mod, -3 - proviso_mod, -4];
-    let order_duration = [144 * 60, 210 * 60, 276 * 60];
-    let order_limit = input.capacity as usize;
-    let sim_duration = (input.duration_minutes * 60) as usize;
-
-    #[derive(Clone)]
-    struct Cell {
-        weight: f64,
-        lmd: f64,
-        gold: f64,
-    }
-
-    impl Default for Cell {
-        fn default() -> Self {
-            Self {
-                weight: 0f64,
-                lmd: 0f64,
-                gold: 0f64,
-            }
+/// Seconds per DP step when tracking full distributions. Order completions that fall between two
+/// steps are split between them, so expected timings stay close to the exact pass.
+const DISTRIBUTION_TICK: usize = 60;
+const LMD_BIN_WIDTH: f64 = 250.0;
+const GOLD_BIN_WIDTH: f64 = 1.0;
+
+#[derive(Clone, Copy, Default)]
+struct Reward {
+    lmd: f64,
+    gold: f64,
+}
+
+impl std::ops::Add for Reward {
+    type Output = Reward;
+
+    fn add(self, other: Reward) -> Reward {
+        Reward {
+            lmd: self.lmd + other.lmd,
+            gold: self.gold + other.gold,
         }
     }
+}
 
-    let mut dp_table = vec![vec![Cell::default(); order_limit as usize]; sim_duration as usize + 1];
+/// What the DP tracks for each (time, stored orders) state.
+trait Payload: Clone + Default {
+    fn weight(&self) -> f64;
 
-    fn highest_ramp(
-        tailoring_ramped: &[(TradingPostTailoringSkill, i32)],
-        elapsed_time: i32,
-    ) -> f64 {
-        tailoring_ramped
+    /// Adds `weight` of fresh probability that has already earned `reward`.
+    fn seed(&mut self, weight: f64, reward: Reward);
+
+    /// Adds `factor` of `source`, which earns `reward` on the way here.
+    fn advance(&mut self, source: &Self, factor: f64, reward: Reward);
+
+    fn absorb(&mut self, source: &Self) {
+        self.advance(source, 1.0, Reward::default());
+    }
+
+    /// Records that every path in this state has stalled.
+    fn mark_stalled(&mut self) {}
+
+    /// Forgets what happened in the previous shift that is only tracked per shift.
+    fn new_shift(&mut self) {}
+
+    /// The payload as an expectation, if it tracks one.
+    fn expectation(&self) -> Option<&Expectation> {
+        None
+    }
+}
+
+/// Probability weight with the weighted sums of LMD and gold earned so far.
+#[derive(Clone, Default)]
+struct Expectation {
+    weight: f64,
+    lmd: f64,
+    gold: f64,
+    /// Weight of the paths that have stalled at some point during the shift.
+    stalled_weight: f64,
+    /// Weight of the paths that have stalled at some point during the day.
+    day_stalled_weight: f64,
+}
+
+impl Payload for Expectation {
+    fn weight(&self) -> f64 {
+        self.weight
+    }
+
+    fn seed(&mut self, weight: f64, reward: Reward) {
+        self.weight += weight;
+        self.lmd += reward.lmd * weight;
+        self.gold += reward.gold * weight;
+    }
+
+    fn advance(&mut self, source: &Self, factor: f64, reward: Reward) {
+        let combined_weight = factor * source.weight;
+        self.weight += combined_weight;
+        self.lmd += source.lmd * factor + reward.lmd * combined_weight;
+        self.gold += source.gold * factor + reward.gold * combined_weight;
+        self.stalled_weight += source.stalled_weight * factor;
+        self.day_stalled_weight += source.day_stalled_weight * factor;
+    }
+
+    fn mark_stalled(&mut self) {
+        self.stalled_weight = self.weight;
+        self.day_stalled_weight = self.weight;
+    }
+
+    fn new_shift(&mut self) {
+        self.stalled_weight = 0.0;
+    }
+
+    fn expectation(&self) -> Option<&Expectation> {
+        Some(self)
+    }
+}
+
+/// Probability weight with the unnormalised distributions of LMD and gold earned so far.
+#[derive(Clone, Default)]
+struct Distribution {
+    weight: f64,
+    lmd: Histogram,
+    gold: Histogram,
+}
+
+impl Payload for Distribution {
+    fn weight(&self) -> f64 {
+        self.weight
+    }
+
+    fn seed(&mut self, weight: f64, reward: Reward) {
+        self.weight += weight;
+        self.lmd.add_point(reward.lmd / LMD_BIN_WIDTH, weight);
+        self.gold.add_point(reward.gold / GOLD_BIN_WIDTH, weight);
+    }
+
+    fn advance(&mut self, source: &Self, factor: f64, reward: Reward) {
+        self.weight += factor * source.weight;
+        self.lmd
+            .add_shifted(&source.lmd, factor, reward.lmd / LMD_BIN_WIDTH);
+        self.gold
+            .add_shifted(&source.gold, factor, reward.gold / GOLD_BIN_WIDTH);
+    }
+}
+
+/// End state of one DP pass.
+struct ShiftResult<P> {
+    /// States that filled the post after the last collection.
+    stalled: P,
+    /// Probability-weighted seconds spent stalled.
+    stalled_time: f64,
+    /// Probability-weighted drones spent.
+    drones_used: f64,
+    /// Probability of stalling before each collection, and before the end of the shift.
+    interval_stalls: Vec<f64>,
+    /// States still producing at the end of the shift, by stored order count.
+    unfinished: Vec<P>,
+    /// The same states in the terms of the next shift, when there is one.
+    handover: Option<Handover<P>>,
+    /// Course of the shift, when recorded.
+    timeline: Option<TimelineRecorder>,
+}
+
+impl<P: Payload> ShiftResult<P> {
+    fn total(&self) -> P {
+        let mut total = self.stalled.clone();
+        for payload in self.unfinished.iter() {
+            total.absorb(payload);
+        }
+        total
+    }
+}
+
+/// State of the post handed from the end of one shift to the start of the next, in the terms of
+/// the next shift.
+struct Handover<P> {
+    order_limit: usize,
+    /// States where a new order starts when the shift starts, by stored order count.
+    starting: Vec<P>,
+    /// States whose order in progress finishes `index` seconds into the shift, by stored order
+    /// count. The order is already credited in full.
+    arrivals: Vec<Vec<P>>,
+}
+
+impl<P: Payload> Handover<P> {
+    fn new(next: &TradingPostModel<'_>) -> Self {
+        Self {
+            order_limit: next.order_limit,
+            starting: vec![P::default(); next.order_limit + 1],
+            arrivals: vec![],
+        }
+    }
+
+    /// Cell of the states starting an order with `stored` orders waiting. A smaller post keeps
+    /// only as many orders as it holds.
+    fn starting(&mut self, stored: usize) -> &mut P {
+        &mut self.starting[stored.min(self.order_limit)]
+    }
+
+    /// Cell of the states whose order in progress finishes at `time` with `stored` orders
+    /// waiting. A post with an order in progress has room for at least that order.
+    fn arrival(&mut self, time: usize, stored: usize) -> &mut P {
+        if self.arrivals.len() <= time {
+            self.arrivals
+                .resize(time + 1, vec![P::default(); self.order_limit + 1]);
+        }
+        &mut self.arrivals[time][stored.min(self.order_limit.saturating_sub(1))]
+    }
+}
+
+/// LMD and gold of each order type, for orders started from `from` seconds into the shift on.
+#[derive(Clone, Copy, PartialEq)]
+struct OrderValues {
+    from: usize,
+    lmd: [i32; 3],
+    gold: [i32; 3],
+    /// Extra LMD credited in full, even for an order cut off by the end of the shift.
+    bonus_lmd: [f64; 3],
+}
+
+/// Order tables for one input, shared by every DP pass over it.
+struct TradingPostModel<'a> {
+    input: &'a TradingPostProductionInput,
+    skills: Vec<Arc<dyn TradingPostSkill>>,
+    tailoring_ramp_spec: [(f64, f64); 3],
+    /// Values of orders started from each time on, in increasing time order from the start of
+    /// the shift. They change as operators run out of morale.
+    order_values: Vec<OrderValues>,
+    /// Durations of the order in progress at the start of the shift, by order type.
+    first_durations: [usize; 3],
+    /// Durations of orders started as the shift starts, by stored order count then order type.
+    mod_durations: Vec<[usize; 3]>,
+    /// Speed in percent over each minute of the shift, by stored order count then minute. A
+    /// single minute stands for the whole shift when the speed doesn't change.
+    speeds: Vec<Vec<f64>>,
+    /// Work done, in percent-seconds, by the start of each minute of the shift and by the end of
+    /// the last, for the stored order counts whose speed changes.
+    work: Vec<Vec<f64>>,
+    /// Longest any order can take.
+    longest_duration: usize,
+    order_limit: usize,
+    sim_duration: usize,
+    /// Orders stored at the start of the shift, capped at the order limit.
+    start_stored: usize,
+    /// Whether the post is emptied as the shift starts.
+    collected_at_start: bool,
+    /// Seconds left on the order in progress at the start of the shift, if known.
+    first_order_left: Option<usize>,
+    /// Value of the orders stored at the start of the shift.
+    carried: Reward,
+    /// Seconds into the shift at which stored orders are collected, in increasing order.
+    collections: Vec<usize>,
+    /// Seconds into the shift and drone count of every drone usage, in increasing time order
+    /// with one usage per time.
+    drone_usages: Vec<(usize, usize)>,
+}
+
+impl<'a> TradingPostModel<'a> {
+    fn new(input: &'a TradingPostProductionInput) -> Self {
+        let game_data = &input.game_data;
+        let tailoring_ramp_spec = game_data.order_weights[match input.phase {
+            TradingPostPhase::L1 => 0,
+            TradingPostPhase::L2 => 1,
+            TradingPostPhase::L3 => 2,
+        }];
+
+        let skills = input.skills();
+        let order_duration = game_data.order_duration_seconds;
+        let order_limit = input.order_limit(&skills) as usize;
+        let sim_duration = (input.duration_minutes * 60) as usize;
+        let minutes = (sim_duration + 59) / 60;
+        // values only change as operators run out of morale
+        let mut value_changes: Vec<usize> = input
+            .morale
             .iter()
-            .map(|(tailor, ramped)| {
-                let ramped_progress = (ramped * 60 + elapsed_time) as f64 / 9000.0;
-                let ramp_multiplier = match tailor {
-                    TradingPostTailoringSkill::Alpha => 0.5,
-                    TradingPostTailoringSkill::Beta => 1.0,
-                };
-                ramp_multiplier
-                    * if ramped_progress > 1.0 {
-                        1.0
-                    } else {
-                        ramped_progress
-                    }
+            .filter_map(OperatorMorale::exhausted_seconds)
+            .map(|seconds| seconds as usize)
+            .filter(|seconds| (1..sim_duration).contains(seconds))
+            .collect();
+        value_changes.sort_unstable();
+        let mut order_values: Vec<OrderValues> = vec![];
+        for from in std::iter::once(0).chain(value_changes) {
+            let mut lmd = game_data.order_lmd;
+            let mut gold_cost = game_data.order_gold_cost;
+            let mut bonus_lmd = [0.0; 3];
+            for skill in skills.iter() {
+                skill.order_value_at(&mut lmd, &mut gold_cost, from as i32);
+                skill.order_bonus_at(&mut bonus_lmd, from as i32);
+            }
+            let values = OrderValues {
+                from,
+                lmd,
+                gold: gold_cost.map(|cost| -cost),
+                bonus_lmd,
+            };
+            if order_values.last().map_or(true, |last| {
+                (last.lmd, last.gold, last.bonus_lmd) != (lmd, values.gold, bonus_lmd)
+            }) {
+                order_values.push(values);
+            }
+        }
+        // sampled every minute; skills that vary over time could otherwise take the speed to
+        // a standstill in between the times validation checks
+        let speeds: Vec<Vec<f64>> = (0..order_limit)
+            .map(|stored| {
+                let mut speeds: Vec<f64> = (0..minutes.max(1))
+                    .map(|minute| input.speed_at(&skills, stored, minute as i32 * 60).max(1.0))
+                    .collect();
+                if speeds.iter().all(|speed| *speed == speeds[0]) {
+                    speeds.truncate(1);
+                }
+                speeds
             })
-            .reduce(|x, y| if x > y { x } else { y })
-            .unwrap_or(0.0)
+            .collect();
+        let work = speeds
+            .iter()
+            .map(|speeds| {
+                if speeds.len() == 1 {
+                    return vec![];
+                }
+                std::iter::once(0.0)
+                    .chain(speeds.iter().scan(0.0, |work, speed| {
+                        *work += speed * 60.0;
+                        Some(*work)
+                    }))
+                    .collect()
+            })
+            .collect();
+        let duration_at = |odur: i32, speed: f64| (odur as f64 * 100.0 / speed).ceil() as usize;
+        // the order in progress at the start is timed as if no orders were waiting
+        let first_durations = order_duration.map(|odur| duration_at(odur, speeds[0][0]));
+        let mod_durations = speeds
+            .iter()
+            .map(|speeds| order_duration.map(|odur| duration_at(odur, speeds[0])))
+            .collect();
+        let slowest = speeds
+            .iter()
+            .flatten()
+            .copied()
+            .fold(f64::INFINITY, f64::min);
+        let longest_duration = order_duration
+            .iter()
+            .map(|odur| duration_at(*odur, slowest))
+            .max()
+            .unwrap_or(0);
+
+        let mut collections: Vec<usize> = input
+            .collection_minutes
+            .iter()
+            .map(|m| (m * 60) as usize)
+            .filter(|c| (1..sim_duration).contains(c))
+            .collect();
+        collections.sort_unstable();
+        collections.dedup();
+        let mut drone_usages: Vec<(usize, usize)> = vec![];
+        let mut usages = input.drone_usages.clone();
+        usages.sort_by_key(|usage| usage.minute);
+        for usage in usages {
+            let time = (usage.minute.max(0) * 60) as usize;
+            let drones = usage.drones.max(0) as usize;
+            if time >= sim_duration || drones == 0 {
+                continue;
+            }
+            match drone_usages.last_mut() {
+                Some((last, total)) if *last == time => *total += drones,
+                _ => drone_usages.push((time, drones)),
+            }
+        }
+
+        let collected_at_start = input.collection_minutes.contains(&0);
+        let start_state = &input.start_state;
+        let start_values = &order_values[0];
+        let carried = (0..3)
+            .map(|otype| Reward {
+                lmd: (start_values.lmd[otype] as f64 + start_values.bonus_lmd[otype])
+                    * start_state.stored_orders[otype] as f64,
+                gold: (start_values.gold[otype] * start_state.stored_orders[otype]) as f64,
+            })
+            .fold(Reward::default(), |x, y| x + y);
+
+        Self {
+            input,
+            skills,
+            tailoring_ramp_spec,
+            order_values,
+            first_durations,
+            mod_durations,
+            speeds,
+            work,
+            longest_duration,
+            order_limit,
+            sim_duration,
+            start_stored: if collected_at_start {
+                0
+            } else {
+                (start_state.stored_orders.iter().sum::<i32>().max(0) as usize).min(order_limit)
+            },
+            collected_at_start,
+            first_order_left: start_state.minutes_left.map(|m| (m * 60) as usize),
+            carried,
+            collections,
+            drone_usages,
+        }
+    }
+
+    /// Speed in percent `time` seconds into the shift with `stored` orders waiting. The speed
+    /// over the last minute holds on past the end of the shift.
+    fn speed(&self, stored: usize, time: usize) -> f64 {
+        let speeds = &self.speeds[stored];
+        speeds[(time / 60).min(speeds.len() - 1)]
+    }
+
+    /// Seconds into the shift at which an order of type `otype` started at `start` with
+    /// `stored` orders waiting finishes.
+    fn finish(&self, start: usize, stored: usize, otype: usize) -> usize {
+        let speeds = &self.speeds[stored];
+        if speeds.len() == 1 {
+            return start + self.mod_durations[stored][otype];
+        }
+        let work = &self.work[stored];
+        let minutes = speeds.len();
+        let work_at = |time: usize| {
+            let minute = (time / 60).min(minutes);
+            work[minute] + self.speed(stored, time) * (time - minute * 60) as f64
+        };
+        let target =
+            work_at(start) + (self.input.game_data.order_duration_seconds[otype] * 100) as f64;
+        // the minute in which the work adds up, or the time past the end of the table
+        let from = work.partition_point(|done| *done < target).max(1) - 1;
+        let seconds = (target - work[from]) / self.speed(stored, from * 60);
+        // leave out rounding errors, so that whole seconds stay whole
+        let finish = from * 60 + (seconds - 1e-6).ceil().max(0.0) as usize;
+        finish.max(start + 1)
     }
 
-    // first order
-    {
-        // use current order distribution for first partial order
-        let current_ramp = highest_ramp(&input.tailoring_ramped, 0);
-        let order_weight: Vec<f64> = tailoring_ramp_spec
+    /// Order type probabilities for an order started `elapsed_time` seconds into the shift.
+    fn order_weight(&self, elapsed_time: usize) -> [f64; 3] {
+        let ramp_seconds = self.input.game_data.tailoring_ramp_seconds;
+        let current_ramp = self
+            .skills
             .iter()
+            .map(|skill| skill.tailoring_ramp(elapsed_time as i32, ramp_seconds))
+            .reduce(|x, y| if x > y { x } else { y })
+            .unwrap_or(0.0);
+        self.tailoring_ramp_spec
             .map(|(base, peak)| base * (1.0 - current_ramp) + peak * current_ramp)
-            .collect();
-        for (otype, odur) in order_duration.iter().enumerate() {
-            let mod_dur = (*odur as f64 * 100.0 / input.speed100 as f64).ceil() as usize;
-            let weight = order_weight[otype] / mod_dur as f64;
-            for (carried_time, dp_row) in dp_table.iter_mut().enumerate().take(mod_dur) {
-                if carried_time < sim_duration {
-                    // first order ends in sim duration
-                    dp_row[0].weight += weight;
-                    // tequila does not add LMD to first partial order
-                    dp_row[0].lmd +=
-                        order_lmd[otype] as f64 * weight * (carried_time as f64 / mod_dur as f64);
-                    dp_row[0].gold +=
-                        order_gold[otype] as f64 * weight * (carried_time as f64 / mod_dur as f64);
+    }
+
+    /// Minutes into the shift at which each collection interval ends.
+    fn interval_end_minutes(&self) -> Vec<i32> {
+        self.collections
+            .iter()
+            .map(|c| (c / 60) as i32)
+            .chain(std::iter::once(self.input.duration_minutes))
+            .collect()
+    }
+
+    /// First collection after `time`, if any.
+    fn next_collection(&self, time: usize) -> Option<usize> {
+        let index = self.collections.partition_point(|c| *c <= time);
+        self.collections.get(index).copied()
+    }
+
+    /// Orders waiting when an order started at `start` with `stored` orders waiting finishes at
+    /// `finish`. Orders are collected before one that finishes exactly at a collection is stored.
+    fn stored_after(&self, stored: usize, start: usize, finish: usize) -> usize {
+        match self.next_collection(start) {
+            Some(collection) if collection < finish => 1,
+            Some(collection) if collection == finish => 0,
+            _ => stored + 1,
+        }
+    }
+
+    /// Orders waiting once the shift has started with `stored` orders in the post.
+    fn stored_at_start(&self, stored: usize) -> usize {
+        if self.collected_at_start {
+            0
+        } else {
+            stored
+        }
+    }
+
+    /// Orders waiting at the end of the shift for an order started at `start` that is still in
+    /// progress then.
+    fn stored_at_end(&self, stored: usize, start: usize) -> usize {
+        match self.next_collection(start) {
+            Some(_) => 0,
+            None => stored,
+        }
+    }
+
+    /// Type probabilities of the order in progress at the start of the shift.
+    fn first_order_weight(&self) -> [f64; 3] {
+        let order_weight = self.order_weight(0);
+        match self.first_order_left {
+            Some(left) => {
+                // the time left on an order is uniform over its duration, so knowing it weighs
+                // each type that can have that much left by 1 / duration
+                let mut posterior = [0.0; 3];
+                for (otype, duration) in self.first_durations.iter().enumerate() {
+                    if left <= *duration {
+                        posterior[otype] = order_weight[otype] / *duration as f64;
+                    }
+                }
+                let total: f64 = posterior.iter().sum();
+                if total > 0.0 {
+                    posterior.map(|p| p / total)
                 } else {
-                    // first order ends after sim duration
-                    dp_row[0].weight += weight;
-                    // tequila does not add LMD to first partial order
-                    dp_row[0].lmd +=
-                        order_lmd[otype] as f64 * weight * (sim_duration as f64 / mod_dur as f64);
-                    dp_row[0].gold +=
-                        order_gold[otype] as f64 * weight * (sim_duration as f64 / mod_dur as f64);
+                    order_weight
                 }
             }
+            None => order_weight,
         }
     }
 
-    let mut stalled_weight = 0.0;
-    let mut stalled_lmd = 0.0;
-    let mut stalled_gold = 0.0;
-    let mut stalled_time = 0.0;
-    // mid & last orders
-    for t in 0..sim_duration {
-        // non-capped orders
-        for count in 0..(order_limit - 1) {
-            if dp_table[t][count].weight > 0.0 {
-                let current_ramp = highest_ramp(&input.tailoring_ramped, t as i32);
-                let order_weight: Vec<f64> = tailoring_ramp_spec
+    /// Expected speed at each minute, in percent, from the chance of each stored order count
+    /// then.
+    fn expected_speeds(&self, stored: &[Vec<f64>]) -> Vec<f64> {
+        stored
+            .iter()
+            .enumerate()
+            .map(|(minute, chances)| {
+                chances[..self.order_limit]
                     .iter()
-                    .map(|(base, peak)| base * (1.0 - current_ramp) + peak * current_ramp)
-                    .collect();
-                for (otype, odur) in order_duration.iter().enumerate() {
-                    let mod_speed = match input.jaye_phase {
-                        HighRarityOperatorPhase::E0 => input.speed100 - (4 * (count as i32 + 1)),
-                        _ => input.speed100,
+                    .enumerate()
+                    .map(|(stored, chance)| chance / 100.0 * self.speed(stored, minute * 60))
+                    .sum()
+            })
+            .collect()
+    }
+
+    /// Values of orders started `start` seconds into the shift.
+    fn order_values(&self, start: usize) -> &OrderValues {
+        let index = self
+            .order_values
+            .partition_point(|values| values.from <= start);
+        &self.order_values[index.max(1) - 1]
+    }
+
+    /// Reward for an order of type `otype` started at `start`, bonus included.
+    fn full_reward(&self, otype: usize, start: usize) -> Reward {
+        let values = self.order_values(start);
+        Reward {
+            lmd: values.lmd[otype] as f64 + values.bonus_lmd[otype],
+            gold: values.gold[otype] as f64,
+        }
+    }
+
+    /// Reward for working `worked` seconds of an order of type `otype` started at `start` that
+    /// takes `duration`. Orders in progress when the shift starts count as started then.
+    fn partial_reward(&self, otype: usize, start: usize, worked: usize, duration: usize) -> Reward {
+        let values = self.order_values(start);
+        let progress = worked as f64 / duration as f64;
+        Reward {
+            lmd: values.lmd[otype] as f64 * progress,
+            gold: values.gold[otype] as f64 * progress,
+        }
+    }
+
+    /// Seconds into this shift at which an order of type `otype` carried in from the previous
+    /// shift finishes, when `left` of its `duration` seconds were still to go. The work left
+    /// carries over at this shift's speed.
+    fn carried_finish(&self, otype: usize, left: usize, duration: usize) -> usize {
+        (left as f64 * self.first_durations[otype] as f64 / duration as f64).ceil() as usize
+    }
+
+    /// Seconds into this shift at which an order carried through all of `previous` finishes,
+    /// when `left` of its seconds there were still to go. Like in `carried_finish`, the work left
+    /// carries over at this shift's speed.
+    fn carried_on_finish(&self, previous: &TradingPostModel<'_>, left: usize) -> usize {
+        (left as f64 * previous.speed(0, 0) / self.speed(0, 0)).ceil() as usize
+    }
+
+    /// Runs the DP in steps of `tick` seconds. Cell `[stored]` of the row for time `t` holds the
+    /// states where a new order starts at `t` with `stored` orders waiting for collection.
+    ///
+    /// The shift starts from `start` when it follows another one, and from the input's start
+    /// state otherwise. When `next` is given, the end state is also handed over in its terms.
+    /// With `record`, the course of the shift is recorded too, which takes an `Expectation`
+    /// payload. Returns `None` when `progress` asks to stop.
+    fn run<P: Payload>(
+        &self,
+        tick: usize,
+        start: Option<Handover<P>>,
+        next: Option<&TradingPostModel<'_>>,
+        progress: &mut Progress<'_>,
+        record: bool,
+    ) -> Option<ShiftResult<P>> {
+        let order_limit = self.order_limit;
+        let sim_duration = self.sim_duration;
+        let mut timeline = if record {
+            Some(TimelineRecorder::new(
+                self.input.duration_minutes as usize,
+                order_limit,
+            ))
+        } else {
+            None
+        };
+        // Records `factor` of `payload` as waiting with `stored` orders from `from` until `to`,
+        // or until the end of the shift.
+        let occupy = |timeline: &mut Option<TimelineRecorder>,
+                      from: usize,
+                      to: Option<usize>,
+                      stored: usize,
+                      payload: &Expectation,
+                      factor: f64| {
+            if let Some(timeline) = timeline.as_mut() {
+                let to = to.filter(|to| *to < sim_duration);
+                timeline.occupy(
+                    from,
+                    to,
+                    stored,
+                    self.next_collection(from),
+                    payload,
+                    factor,
+                );
+            }
+        };
+        // states seeded from the start state have earned no more than the orders stored then
+        let carried_only = |weight: f64| Expectation {
+            weight,
+            lmd: self.carried.lmd * weight,
+            gold: self.carried.gold * weight,
+            ..Default::default()
+        };
+
+        // An order started at `t` finishes no later than `t + longest_duration`, so only that
+        // many seconds ahead of `t` are ever written. Keep them in a ring buffer instead of one
+        // row per second of the shift; orders that finish at or after the end of the shift land
+        // in `unfinished`.
+        let longest_duration = std::iter::once(self.longest_duration)
+            .chain(self.first_order_left)
+            .chain(start.as_ref().map(|start| start.arrivals.len()))
+            .max()
+            .unwrap_or(0);
+        let window_len = longest_duration.min(sim_duration) / tick + 2;
+        let mut dp_window = vec![vec![P::default(); order_limit + 1]; window_len];
+        let mut unfinished = vec![P::default(); order_limit + 1];
+        let mut handover = next.map(Handover::new);
+
+        // Hands `cell_update` the cell of a new order starting at `time` with `stored` orders
+        // waiting, together with its share when `time` falls between two steps.
+        let place = |dp_window: &mut Vec<Vec<P>>,
+                     unfinished: &mut Vec<P>,
+                     handover: &mut Option<Handover<P>>,
+                     time: usize,
+                     stored: usize,
+                     cell_update: &mut dyn FnMut(&mut P, f64)| {
+            let step = time / tick;
+            let frac = (time % tick) as f64 / tick as f64;
+            let (stored, splits) = match self.next_collection(step * tick) {
+                // don't split across a collection, or the state on the wrong side of it would
+                // be collected twice or not at all
+                Some(collection) if frac > 0.0 && collection <= (step + 1) * tick => (
+                    if time < collection { 0 } else { stored },
+                    [(step, 0.0), (step + 1, 1.0)],
+                ),
+                _ => (stored, [(step, 1.0 - frac), (step + 1, frac)]),
+            };
+            for (step, share) in splits {
+                if share > 0.0 {
+                    if step * tick < sim_duration {
+                        cell_update(&mut dp_window[step % window_len][stored], share);
+                    } else {
+                        // the new order starts with the next shift
+                        cell_update(&mut unfinished[stored], share);
+                        if let Some(handover) = handover {
+                            cell_update(handover.starting(stored), share);
+                        }
+                    }
+                }
+            }
+        };
+
+        // paths whose order drones finished, by drone usage, then by the drones left and the
+        // orders waiting as the next order starts
+        let mut cascades: Vec<BTreeMap<(usize, usize), P>> =
+            vec![BTreeMap::new(); self.drone_usages.len()];
+        let mut drones_used = 0.0;
+
+        let start_stored = self.start_stored;
+        if let Some(mut start) = start {
+            // carried over from the previous shift
+            for (stored, payload) in start.starting.iter_mut().enumerate() {
+                payload.new_shift();
+                dp_window[0][self.stored_at_start(stored)].absorb(payload);
+            }
+            for (time, row) in start.arrivals.iter_mut().enumerate() {
+                for (stored, payload) in row.iter_mut().enumerate() {
+                    if payload.weight() <= 0.0 {
+                        continue;
+                    }
+                    payload.new_shift();
+                    let payload = &*payload;
+                    let stored = self.stored_at_start(stored);
+                    let accelerated = self.accelerate(0, time, None);
+                    let finish = accelerated.finish();
+                    drones_used += payload.weight() * accelerated.used() as f64;
+                    if let Some(expectation) = payload.expectation() {
+                        occupy(&mut timeline, 0, Some(finish), stored, expectation, 1.0);
+                    }
+                    match accelerated {
+                        Accelerated::ByDrones {
+                            usage, drones_left, ..
+                        } => cascades[usage]
+                            .entry((drones_left, self.stored_after(stored, 0, finish)))
+                            .or_default()
+                            .absorb(payload),
+                        Accelerated::At { .. } if finish < sim_duration => place(
+                            &mut dp_window,
+                            &mut unfinished,
+                            &mut handover,
+                            finish,
+                            self.stored_after(stored, 0, finish),
+                            &mut |cell, share| cell.advance(payload, share, Reward::default()),
+                        ),
+                        Accelerated::At { .. } => {
+                            // the order outlasts this shift too
+                            let stored = self.stored_at_end(stored, 0);
+                            unfinished[stored].absorb(payload);
+                            if let (Some(handover), Some(next)) = (handover.as_mut(), next) {
+                                handover
+                                    .arrival(
+                                        next.carried_on_finish(self, finish - sim_duration),
+                                        stored,
+                                    )
+                                    .absorb(payload);
+                            }
+                        }
+                    }
+                }
+            }
+        } else if start_stored == order_limit {
+            // first order, from the start state
+            // the post is already full
+            dp_window[0][order_limit].seed(1.0, self.carried);
+        } else {
+            // first order, from the start state, by type and seconds left, with the weight of
+            // each
+            let order_weight = self.first_order_weight();
+            let mut first_orders = vec![];
+            for (otype, duration) in self.first_durations.iter().copied().enumerate() {
+                match self.first_order_left {
+                    Some(left) => first_orders.push((otype, left, order_weight[otype])),
+                    // use current order distribution for first partial order, uniformly far
+                    // along
+                    None => first_orders.extend(
+                        (0..duration)
+                            .map(|left| (otype, left, order_weight[otype] / duration as f64)),
+                    ),
+                }
+            }
+            for (otype, left, weight) in first_orders {
+                let duration = self.first_durations[otype];
+                let accelerated = self.accelerate(0, left, None);
+                let finish = accelerated.finish();
+                drones_used += weight * accelerated.used() as f64;
+                occupy(
+                    &mut timeline,
+                    0,
+                    Some(finish),
+                    start_stored,
+                    &carried_only(weight),
+                    1.0,
+                );
+                // tequila does not add LMD to first partial order
+                let full_reward =
+                    self.carried + self.partial_reward(otype, 0, left.min(duration), duration);
+                match accelerated {
+                    Accelerated::ByDrones {
+                        usage, drones_left, ..
+                    } => cascades[usage]
+                        .entry((drones_left, self.stored_after(start_stored, 0, finish)))
+                        .or_default()
+                        .seed(weight, full_reward),
+                    Accelerated::At { .. } if finish < sim_duration => place(
+                        &mut dp_window,
+                        &mut unfinished,
+                        &mut handover,
+                        finish,
+                        self.stored_after(start_stored, 0, finish),
+                        &mut |cell, share| cell.seed(weight * share, full_reward),
+                    ),
+                    Accelerated::At { used, .. } => {
+                        // first order ends after sim duration
+                        let stored = self.stored_at_end(start_stored, 0);
+                        let worked = (sim_duration + used * DRONE_SECONDS)
+                            .min(left)
+                            .min(duration);
+                        unfinished[stored].seed(
+                            weight,
+                            self.carried + self.partial_reward(otype, 0, worked, duration),
+                        );
+                        if let (Some(handover), Some(next)) = (handover.as_mut(), next) {
+                            handover
+                                .arrival(
+                                    next.carried_finish(otype, finish - sim_duration, duration),
+                                    stored,
+                                )
+                                .seed(weight, full_reward);
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut stalled = P::default();
+        let mut stalled_time = 0.0;
+        let mut interval_stalls = vec![0.0; self.collections.len() + 1];
+        // stalled states waiting to resume with an empty post at each collection
+        let mut resumed = vec![P::default(); self.collections.len()];
+        let mut next_resumed = 0;
+        // mid & last orders
+        for step in 0..(sim_duration + tick - 1) / tick {
+            if !progress.step() {
+                return None;
+            }
+            let t = step * tick;
+            // take the current row out of the window so later rows can be written while reading it
+            let mut dp_row = std::mem::take(&mut dp_window[step % window_len]);
+            // collections take effect at the first step at or after them
+            while next_resumed < resumed.len()
+                && (self.collections[next_resumed] + tick - 1) / tick == step
+            {
+                dp_row[0].absorb(&resumed[next_resumed]);
+                next_resumed += 1;
+            }
+            let order_weight = self.order_weight(t);
+            // Starts a new order of each type for `source` at `t` with `stored` orders waiting.
+            // `drones` replaces the drones of a usage at `t`.
+            let start_orders = |source: &P,
+                                stored: usize,
+                                drones: Option<usize>,
+                                dp_window: &mut Vec<Vec<P>>,
+                                unfinished: &mut Vec<P>,
+                                handover: &mut Option<Handover<P>>,
+                                timeline: &mut Option<TimelineRecorder>,
+                                cascades: &mut Vec<BTreeMap<(usize, usize), P>>,
+                                drones_used: &mut f64| {
+                for (otype, wfactor) in order_weight.iter().copied().enumerate() {
+                    let natural_finish = self.finish(t, stored, otype);
+                    let mod_dur = natural_finish - t;
+                    let accelerated = self.accelerate(t, natural_finish, drones);
+                    let tfinish = match accelerated {
+                        Accelerated::ByDrones { .. } => accelerated.finish(),
+                        // an order finishing within the step it starts in would be written to
+                        // the row being read
+                        Accelerated::At { finish, .. } => finish.max(t + tick),
                     };
-                    let mod_dur = ((odur * 100) as f64 / mod_speed as f64).ceil() as usize;
-                    let tfinish = t + mod_dur;
-                    let wfactor = order_weight[otype];
-                    let combined_weight = wfactor * dp_table[t][count].weight;
-                    if tfinish < sim_duration {
+                    *drones_used += source.weight() * wfactor * accelerated.used() as f64;
+                    if let Some(expectation) = source.expectation() {
+                        occupy(timeline, t, Some(tfinish), stored, expectation, wfactor);
+                    }
+                    let full_reward = self.full_reward(otype, t);
+                    match accelerated {
+                        Accelerated::ByDrones {
+                            usage, drones_left, ..
+                        } => cascades[usage]
+                            .entry((drones_left, self.stored_after(stored, t, tfinish)))
+                            .or_default()
+                            .advance(source, wfactor, full_reward),
                         // mid orders
-                        dp_table[tfinish][count + 1].weight += combined_weight;
-                        dp_table[tfinish][count + 1].lmd += dp_table[t][count].lmd * wfactor
-                            + order_lmd[otype] as f64 * combined_weight
-                            + bonus_lmd[otype] * combined_weight;
-                        dp_table[tfinish][count + 1].gold += dp_table[t][count].gold * wfactor
-                            + order_gold[otype] as f64 * combined_weight;
-                    } else {
-                        // last order
-                        dp_table[sim_duration][count + 1].weight += combined_weight;
-                        // fully credit Tequila for last order even if only worked partially
-                        dp_table[sim_duration][count + 1].lmd += dp_table[t][count].lmd * wfactor
-                            + order_lmd[otype] as f64 * combined_weight * (sim_duration - t) as f64
-                                / mod_dur as f64
-                            + bonus_lmd[otype] * combined_weight;
-                        dp_table[sim_duration][count + 1].gold += dp_table[t][count].gold * wfactor
-                            + order_gold[otype] as f64
-                                * combined_weight
-                                * (sim_duration - t) as f64
-                                / mod_dur as f64;
+                        Accelerated::At { .. } if tfinish < sim_duration => place(
+                            dp_window,
+                            unfinished,
+                            handover,
+                            tfinish,
+                            self.stored_after(stored, t, tfinish),
+                            &mut |cell, share| cell.advance(source, wfactor * share, full_reward),
+                        ),
+                        Accelerated::At { used, .. } => {
+                            // last order
+                            // fully credit Tequila for last order even if only worked partially
+                            let worked = (sim_duration - t + used * DRONE_SECONDS).min(mod_dur);
+                            let mut reward = self.partial_reward(otype, t, worked, mod_dur);
+                            reward.lmd += self.order_values(t).bonus_lmd[otype];
+                            let end_stored = self.stored_at_end(stored, t);
+                            unfinished[end_stored].advance(source, wfactor, reward);
+                            // the next shift is credited with the rest of the order
+                            if let (Some(handover), Some(next)) = (handover.as_mut(), next) {
+                                handover
+                                    .arrival(
+                                        next.carried_finish(otype, tfinish - sim_duration, mod_dur),
+                                        end_stored,
+                                    )
+                                    .advance(source, wfactor, full_reward);
+                            }
+                        }
                     }
                 }
+            };
+            // drones used now go to the orders starting now, then to the ones after those
+            if let Some(usage) = self.drone_usages.iter().position(|(time, _)| *time == t) {
+                let drones = self.drone_usages[usage].1;
+                for (stored, source) in dp_row.iter_mut().enumerate().take(order_limit) {
+                    if source.weight() > 0.0 {
+                        cascades[usage]
+                            .entry((drones, stored))
+                            .or_default()
+                            .absorb(source);
+                        *source = P::default();
+                    }
+                }
+                // starting orders only ever leave fewer drones, so take the most first
+                while let Some(key) = cascades[usage].keys().next_back().copied() {
+                    let source = cascades[usage].remove(&key).expect("key was just found");
+                    let (drones_left, stored) = key;
+                    if stored == order_limit {
+                        // drones can't be used on a full post
+                        dp_row[order_limit].absorb(&source);
+                        continue;
+                    }
+                    start_orders(
+                        &source,
+                        stored,
+                        Some(drones_left),
+                        &mut dp_window,
+                        &mut unfinished,
+                        &mut handover,
+                        &mut timeline,
+                        &mut cascades,
+                        &mut drones_used,
+                    );
+                }
             }
+            // non-capped orders
+            for (stored, source) in dp_row.iter().enumerate().take(order_limit) {
+                if source.weight() > 0.0 {
+                    start_orders(
+                        source,
+                        stored,
+                        None,
+                        &mut dp_window,
+                        &mut unfinished,
+                        &mut handover,
+                        &mut timeline,
+                        &mut cascades,
+                        &mut drones_used,
+                    );
+                }
+            }
+            // capped orders stall until the next collection
+            let capped = &dp_row[order_limit];
+            if capped.weight() > 0.0 {
+                let interval = self.collections.partition_point(|c| *c <= t);
+                interval_stalls[interval] += capped.weight();
+                if let Some(expectation) = capped.expectation() {
+                    let resumes = self.collections.get(interval).copied();
+                    occupy(&mut timeline, t, resumes, order_limit, expectation, 1.0);
+                    if let Some(timeline) = timeline.as_mut() {
+                        timeline.stall(t, expectation);
+                    }
+                }
+                let mut capped = capped.clone();
+                capped.mark_stalled();
+                match self.collections.get(interval) {
+                    Some(collection) => {
+                        stalled_time += capped.weight() * (collection - t) as f64;
+                        resumed[interval].absorb(&capped);
+                    }
+                    None => {
+                        stalled_time += capped.weight() * (sim_duration - t) as f64;
+                        stalled.absorb(&capped);
+                    }
+                }
+            }
+            // clear the row and hand it back to the window for reuse
+            dp_row.fill(P::default());
+            dp_window[step % window_len] = dp_row;
         }
-        // capped orders
-        let w = dp_table[t][order_limit - 1].weight;
-        if w > 0.0 {
-            stalled_weight += w;
-            stalled_lmd += dp_table[t][order_limit - 1].lmd;
-            stalled_gold += dp_table[t][order_limit - 1].gold;
-            stalled_time += w * (sim_duration - t) as f64;
-        }
-    }
-    let healthy_lmd: f64 = dp_table[sim_duration].iter().map(|x| x.lmd).sum();
-    let healthy_gold: f64 = dp_table[sim_duration].iter().map(|x| x.gold).sum();
-    let total_lmd = stalled_lmd + healthy_lmd;
-    let total_gold = stalled_gold + healthy_gold;
-    let lmd_24 = total_lmd * 1440.0 / input.duration_minutes as f64;
-    let gold_24 = total_gold * 1440.0 / input.duration_minutes as f64;
+
+        // a full post starts the next shift stalled
+        if let Some(handover) = handover.as_mut() {
+            handover.starting(order_limit).absorb(&stalled);
+        }
+
+        Some(ShiftResult {
+            stalled,
+            stalled_time,
+            drones_used,
+            interval_stalls,
+            unfinished,
+            handover,
+            timeline,
+        })
+    }
+}
+
+/// Counts the DP steps of a simulation, and reports the fraction done now and then.
+struct Progress<'a> {
+    done: usize,
+    total: usize,
+    next_report: usize,
+    /// Called with the fraction done; returns whether to keep going.
+    report: &'a mut dyn FnMut(f64) -> bool,
+}
+
+impl<'a> Progress<'a> {
+    /// Reports in steps of about half a percent.
+    const REPORTS: usize = 200;
+
+    fn new(models: &[TradingPostModel<'_>], report: &'a mut dyn FnMut(f64) -> bool) -> Self {
+        let total = models
+            .iter()
+            .map(|model| {
+                [1, DISTRIBUTION_TICK]
+                    .iter()
+                    .map(|tick| (model.sim_duration + tick - 1) / tick)
+                    .sum::<usize>()
+            })
+            .sum();
+        Self {
+            done: 0,
+            total,
+            next_report: 0,
+            report,
+        }
+    }
+
+    /// Records one more step. Returns whether to keep going.
+    fn step(&mut self) -> bool {
+        self.done += 1;
+        if self.done < self.next_report {
+            return true;
+        }
+        self.next_report += self.total / Self::REPORTS + 1;
+        (self.report)(self.done as f64 / self.total as f64)
+    }
+}
+
+/// Runs the DP over consecutive shifts, handing each shift's end state to the next, and
+/// recording the course of each shift with `record`. Returns `None` when `progress` asks to
+/// stop.
+fn run_day<P: Payload>(
+    models: &[TradingPostModel<'_>],
+    tick: usize,
+    progress: &mut Progress<'_>,
+    record: bool,
+) -> Option<Vec<ShiftResult<P>>> {
+    let mut results: Vec<ShiftResult<P>> = Vec::with_capacity(models.len());
+    for (index, model) in models.iter().enumerate() {
+        let start = results
+            .last_mut()
+            .and_then(|previous| previous.handover.take());
+        results.push(model.run(tick, start, models.get(index + 1), progress, record)?);
+    }
+    Some(results)
+}
+
+/// Fills in the daily and net speed figures of an output from its totals over `duration_minutes`.
+/// Orders `carried` in from before the first shift count toward the totals but not toward the
+/// rates.
+fn production_output(
+    game_data: &TradingPostGameData,
+    duration_minutes: i32,
+    stall_chance: f64,
+    average_stall_time: f64,
+    total_lmd: f64,
+    total_gold: f64,
+    carried: Reward,
+) -> TradingPostProductionOutput {
+    let lmd_24 = (total_lmd - carried.lmd) * 1440.0 / duration_minutes as f64;
+    let gold_24 = (total_gold - carried.gold) * 1440.0 / duration_minutes as f64;
     let gold_24_extra = gold_24 + lmd_24 / 500.0;
-    let baseline_lmd = 1450.0 * 1440.0 / 203.4;
-    let baseline_gold = 20.0;
+    let baseline_lmd = game_data.baseline_order_lmd * 1440.0 / game_data.baseline_order_minutes;
+    let baseline_gold = game_data.baseline_daily_gold;
     let net_tp_speed = lmd_24 / baseline_lmd;
     let net_gold_speed = gold_24_extra / baseline_gold;
     TradingPostProductionOutput {
-        stall_chance: stalled_weight * 100.0,
-        average_stall_time: stalled_time / 60.0,
+        stall_chance,
+        average_stall_time,
         total_lmd,
         total_gold,
         daily_lmd: lmd_24,
         daily_gold: gold_24,
         net_lmd_speed: net_tp_speed * 100.0,
         net_gold_speed: net_gold_speed * 100.0,
+        ..Default::default()
     }
 }
+
+pub fn simulate_tp_production(input: &TradingPostProductionInput) -> TradingPostProductionOutput {
+    simulate_tp_day(std::slice::from_ref(input)).day
+}
+
+/// `simulate_tp_production` that rejects inputs it can't simulate, rather than panicking or
+/// giving NaN.
+pub fn try_simulate(
+    input: &TradingPostProductionInput,
+) -> Result<TradingPostProductionOutput, SimError> {
+    input.validate()?;
+    Ok(simulate_tp_production(input))
+}
+
+/// Checks every shift of a day plan, giving the index of the first one that can't be simulated
+/// and why.
+pub fn validate_day(shifts: &[TradingPostProductionInput]) -> Result<(), (usize, SimError)> {
+    for (index, shift) in shifts.iter().enumerate() {
+        shift.validate().map_err(|error| (index, error))?;
+        if shifts.len() > 1 && shift.gold_supply.is_some() {
+            return Err((index, SimError::GoldSupplyInDayPlan));
+        }
+    }
+    Ok(())
+}
+
+/// Simulates consecutive shifts, each starting with the orders stored and the order in progress
+/// at the end of the one before.
+pub fn simulate_tp_day(shifts: &[TradingPostProductionInput]) -> TradingPostDayOutput {
+    simulate_tp_day_with_progress(shifts, &mut |_| true).expect("simulation is never cancelled")
+}
+
+/// `simulate_tp_day` that calls `report` with the fraction done every so often, and gives up
+/// with `None` as soon as `report` returns false.
+pub fn simulate_tp_day_with_progress(
+    shifts: &[TradingPostProductionInput],
+    report: &mut dyn FnMut(f64) -> bool,
+) -> Option<TradingPostDayOutput> {
+    if shifts.is_empty() {
+        return Some(TradingPostDayOutput::default());
+    }
+    let models: Vec<TradingPostModel<'_>> = shifts.iter().map(TradingPostModel::new).collect();
+    let mut progress = Progress::new(&models, report);
+    let mut results = run_day::<Expectation>(&models, 1, &mut progress, true)?;
+    let distribution = run_day::<Distribution>(&models, DISTRIBUTION_TICK, &mut progress, false)?
+        .last()
+        .map(ShiftResult::total)
+        .unwrap_or_default();
+
+    let mut previous_total = Expectation::default();
+    let mut shift_outputs = Vec::with_capacity(shifts.len());
+    let mut day_timeline = ShiftTimeline::default();
+    for (index, (model, shift)) in models.iter().zip(results.iter_mut()).enumerate() {
+        let total = shift.total();
+        let (mut timeline, day_stall_chance) = shift
+            .timeline
+            .take()
+            .expect("the exact pass records timelines")
+            .finish(&previous_total);
+        timeline.speed = model.expected_speeds(&timeline.stored);
+        day_timeline.append(&ShiftTimeline {
+            stall_chance: day_stall_chance,
+            ..timeline.clone()
+        });
+        for (lmd, gold) in timeline.lmd.iter_mut().zip(timeline.gold.iter_mut()) {
+            *lmd -= previous_total.lmd;
+            *gold -= previous_total.gold;
+        }
+        let carried = if index == 0 {
+            model.carried
+        } else {
+            Reward::default()
+        };
+        shift_outputs.push(TradingPostProductionOutput {
+            interval_end_minutes: model.interval_end_minutes(),
+            interval_stall_chance: shift.interval_stalls.iter().map(|w| w * 100.0).collect(),
+            average_speed: timeline.average_speed(),
+            drones_used: shift.drones_used,
+            timeline,
+            ..production_output(
+                &model.input.game_data,
+                model.input.duration_minutes,
+                total.stalled_weight * 100.0,
+                shift.stalled_time / 60.0,
+                total.lmd - previous_total.lmd,
+                total.gold - previous_total.gold,
+                carried,
+            )
+        });
+        previous_total = total;
+    }
+
+    // the day's collection intervals are those of every shift, offset by when it starts
+    let mut interval_end_minutes = vec![];
+    let mut shift_start = 0;
+    for model in models.iter() {
+        interval_end_minutes.extend(model.interval_end_minutes().iter().map(|m| m + shift_start));
+        shift_start += model.input.duration_minutes;
+    }
+    let day = TradingPostProductionOutput {
+        lmd_distribution: ShiftDistribution::from_histogram(&distribution.lmd, LMD_BIN_WIDTH),
+        gold_distribution: ShiftDistribution::from_histogram(&distribution.gold, GOLD_BIN_WIDTH),
+        interval_end_minutes,
+        interval_stall_chance: shift_outputs
+            .iter()
+            .flat_map(|shift| shift.interval_stall_chance.iter().copied())
+            .collect(),
+        average_speed: day_timeline.average_speed(),
+        drones_used: shift_outputs.iter().map(|shift| shift.drones_used).sum(),
+        timeline: day_timeline,
+        ..production_output(
+            &models[0].input.game_data,
+            shift_start,
+            previous_total.day_stalled_weight * 100.0,
+            shift_outputs
+                .iter()
+                .map(|shift| shift.average_stall_time)
+                .sum(),
+            previous_total.lmd,
+            previous_total.gold,
+            models[0].carried,
+        )
+    };
+    Some(TradingPostDayOutput {
+        shifts: shift_outputs,
+        day,
+    })
+}
@@ -0,0 +1,195 @@
+use super::{
+    try_simulate, SimError, TradingPostModel, TradingPostProductionInput,
+    TradingPostProductionOutput,
+};
+
+/// Seconds taken off the order in progress by each drone.
+pub(super) const DRONE_SECONDS: usize = 180;
+
+/// Parts the planner splits a drone budget into, each placed where it helps most.
+const PLAN_PARTS: i32 = 4;
+
+/// Drones spent some minutes into the shift. They go to the order in progress, and once that
+/// finishes to the orders after it, until they run out or the post is full.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct DroneUsage {
+    pub minute: i32,
+    pub drones: i32,
+}
+
+/// When an order finishes once drones are used on it.
+pub(super) enum Accelerated {
+    /// At `finish`, after `used` drones have taken time off it.
+    At { finish: usize, used: usize },
+    /// At `time`, as drones of usage `usage` are spent, which leaves `drones_left` of them for
+    /// the orders after it.
+    ByDrones {
+        usage: usize,
+        time: usize,
+        drones_left: usize,
+        used: usize,
+    },
+}
+
+impl Accelerated {
+    pub(super) fn finish(&self) -> usize {
+        match self {
+            Accelerated::At { finish, .. } => *finish,
+            Accelerated::ByDrones { time, .. } => *time,
+        }
+    }
+
+    /// Drones spent on the order.
+    pub(super) fn used(&self) -> usize {
+        match self {
+            Accelerated::At { used, .. } | Accelerated::ByDrones { used, .. } => *used,
+        }
+    }
+}
+
+impl TradingPostModel<'_> {
+    /// When an order in progress from `start` that would finish at `finish` finishes once drones
+    /// are used on it. `drones_at_start` replaces the drones of a usage at `start`, when the
+    /// order follows one those drones finished.
+    pub(super) fn accelerate(
+        &self,
+        start: usize,
+        mut finish: usize,
+        drones_at_start: Option<usize>,
+    ) -> Accelerated {
+        let mut used = 0;
+        for (usage, (time, drones)) in self.drone_usages.iter().copied().enumerate() {
+            if time >= finish {
+                break;
+            }
+            if time < start {
+                continue;
+            }
+            let drones = match drones_at_start {
+                Some(drones_at_start) if time == start => drones_at_start,
+                _ => drones,
+            };
+            let needed = (finish - time + DRONE_SECONDS - 1) / DRONE_SECONDS;
+            if drones >= needed {
+                return Accelerated::ByDrones {
+                    usage,
+                    time,
+                    drones_left: drones - needed,
+                    used: used + needed,
+                };
+            }
+            finish -= drones * DRONE_SECONDS;
+            used += drones;
+        }
+        Accelerated::At { finish, used }
+    }
+}
+
+/// Finds when to spend a drone budget over a shift for the most LMD.
+#[derive(serde::Deserialize, serde::Serialize, Clone)]
+pub struct DronePlanner {
+    /// The shift to plan for. Drone usages already in it are replaced.
+    pub base: TradingPostProductionInput,
+    pub budget: i32,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone)]
+pub struct DronePlan {
+    pub usages: Vec<DroneUsage>,
+    /// Output of the shift with the drones spent as planned.
+    pub output: TradingPostProductionOutput,
+}
+
+impl DronePlanner {
+    /// Minutes considered for spending drones: the start of the shift, every hour, and every
+    /// collection, when the post has just been emptied.
+    fn candidate_minutes(&self) -> Vec<i32> {
+        let duration = self.base.duration_minutes;
+        let mut minutes: Vec<i32> = (0..duration)
+            .step_by(60)
+            .chain(self.base.collection_minutes.iter().copied())
+            .filter(|minute| (0..duration).contains(minute))
+            .collect();
+        minutes.sort_unstable();
+        minutes.dedup();
+        minutes
+    }
+
+    /// The budget split into parts as even as possible.
+    fn parts(&self) -> Vec<i32> {
+        let budget = self.budget.max(0);
+        let parts = PLAN_PARTS.min(budget);
+        (0..parts)
+            .map(|part| budget / parts + i32::from(part < budget % parts))
+            .collect()
+    }
+
+    pub fn plan(&self) -> Result<DronePlan, SimError> {
+        self.plan_with_progress(&mut |_| true)
+            .expect("planner is never cancelled")
+    }
+
+    /// `plan` that calls `report` with the fraction done after each simulation, and gives up
+    /// with `None` as soon as `report` returns false.
+    ///
+    /// Places the budget a part at a time, each at the minute that adds the most LMD to the
+    /// parts placed before it.
+    pub fn plan_with_progress(
+        &self,
+        report: &mut dyn FnMut(f64) -> bool,
+    ) -> Option<Result<DronePlan, SimError>> {
+        let mut input = TradingPostProductionInput {
+            drone_usages: vec![],
+            ..self.base.clone()
+        };
+        let mut output = match try_simulate(&input) {
+            Ok(output) => output,
+            Err(error) => return Some(Err(error)),
+        };
+        let candidates = self.candidate_minutes();
+        let parts = self.parts();
+        let total = candidates.len() * parts.len();
+        let mut done = 0;
+        for drones in parts {
+            let mut best: Option<(Vec<DroneUsage>, TradingPostProductionOutput)> = None;
+            for minute in candidates.iter().copied() {
+                let mut usages = input.drone_usages.clone();
+                match usages.iter_mut().find(|usage| usage.minute == minute) {
+                    Some(usage) => usage.drones += drones,
+                    None => {
+                        usages.push(DroneUsage { minute, drones });
+                        usages.sort_by_key(|usage| usage.minute);
+                    }
+                }
+                let candidate = TradingPostProductionInput {
+                    drone_usages: usages,
+                    ..input.clone()
+                };
+                let candidate_output = match try_simulate(&candidate) {
+                    Ok(output) => output,
+                    Err(error) => return Some(Err(error)),
+                };
+                // the most LMD, then the least stalling
+                let better = best.as_ref().map_or(true, |(_, best)| {
+                    (candidate_output.total_lmd, -candidate_output.stall_chance)
+                        > (best.total_lmd, -best.stall_chance)
+                });
+                if better {
+                    best = Some((candidate.drone_usages, candidate_output));
+                }
+                done += 1;
+                if !report(done as f64 / total as f64) {
+                    return None;
+                }
+            }
+            if let Some((usages, best_output)) = best {
+                input.drone_usages = usages;
+                output = best_output;
+            }
+        }
+        Some(Ok(DronePlan {
+            usages: input.drone_usages,
+            output,
+        }))
+    }
+}
@@ -0,0 +1,137 @@
+/// Game data bundled with the build.
+const BUNDLED_GAME_DATA: &str = include_str!("game_data.json");
+
+/// Game constants of the trading post. Bundled with the build, and replaceable at runtime to
+/// model other servers and patches.
+#[derive(serde::Deserialize, serde::Serialize, Clone, PartialEq)]
+pub struct TradingPostGameData {
+    /// Game version the constants were taken from.
+    pub version: String,
+    /// LMD paid by each order type.
+    pub order_lmd: [i32; 3],
+    /// Gold used up by each order type.
+    pub order_gold_cost: [i32; 3],
+    /// Seconds each order type takes at 100% speed.
+    pub order_duration_seconds: [i32; 3],
+    /// `(unramped, fully ramped)` tailoring probability of each order type, by post level.
+    pub order_weights: [[(f64, f64); 3]; 3],
+    /// Seconds a tailoring skill takes to ramp up fully.
+    pub tailoring_ramp_seconds: i32,
+    /// How far Tailoring Alpha and Beta, in that order, ramp the order weights towards their
+    /// fully ramped values once ramped up, from 0 to 1.
+    pub tailoring_ramp_multipliers: [f64; 2],
+    /// LMD Tequila adds to every 4-bar order at elite 0, 1 and 2.
+    pub tequila_bonus_lmd: [f64; 3],
+    /// Gold Proviso adds to every 2-bar and 3-bar order at elite 0, 1 and 2.
+    pub proviso_extra_gold: [i32; 3],
+    /// LMD those orders pay for every extra gold Proviso adds.
+    pub proviso_lmd_per_gold: i32,
+    /// Speed in percent Jaye adds for every order waiting, at elite 0, 1 and 2. Negative slows
+    /// the post down.
+    pub jaye_speed_per_order: [f64; 3],
+    /// Orders Jaye adds to the order limit at elite 0, 1 and 2.
+    pub jaye_capacity_change: [i32; 3],
+    /// LMD per order of the reference post that net speeds are measured against.
+    pub baseline_order_lmd: f64,
+    /// Minutes per order of the reference post.
+    pub baseline_order_minutes: f64,
+    /// Gold per day of the reference factory.
+    pub baseline_daily_gold: f64,
+}
+
+impl TradingPostGameData {
+    /// The game data shipped with this build.
+    pub fn bundled() -> Self {
+        Self::from_json(BUNDLED_GAME_DATA).expect("bundled game data is valid")
+    }
+
+    /// Reads game data from JSON, rejecting data that can't be simulated with.
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        let game_data: Self = serde_json::from_str(json)?;
+        game_data
+            .validate()
+            .map_err(<serde_json::Error as serde::de::Error>::custom)?;
+        Ok(game_data)
+    }
+
+    /// Checks that every order takes time, that each post level can draw an order, and that
+    /// tailoring ramps the weights no further than fully. Arrays
+    /// always hold one entry per order type, as JSON of any other length doesn't load.
+    pub fn validate(&self) -> Result<(), GameDataError> {
+        if self
+            .order_duration_seconds
+            .iter()
+            .any(|&seconds| seconds <= 0)
+        {
+            return Err(GameDataError::NonPositiveOrderDuration);
+        }
+        for (level, weights) in self.order_weights.iter().enumerate() {
+            let unramped = weights.map(|(unramped, _)| unramped);
+            let ramped = weights.map(|(_, ramped)| ramped);
+            // NaN is not a weight either
+            let valid = |weights: [f64; 3]| {
+                weights.iter().all(|weight| *weight >= 0.0) && weights.iter().sum::<f64>() > 0.0
+            };
+            if !valid(unramped) || !valid(ramped) {
+                return Err(GameDataError::InvalidOrderWeights(level + 1));
+            }
+        }
+        if self.tailoring_ramp_seconds < 0 {
+            return Err(GameDataError::NegativeTailoringRamp);
+        }
+        if !self
+            .tailoring_ramp_multipliers
+            .iter()
+            .all(|multiplier| (0.0..=1.0).contains(multiplier))
+        {
+            return Err(GameDataError::InvalidTailoringMultiplier);
+        }
+        Ok(())
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("game data serializes to JSON")
+    }
+}
+
+impl Default for TradingPostGameData {
+    fn default() -> Self {
+        Self::bundled()
+    }
+}
+
+/// Why game data can't be simulated with.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, PartialEq)]
+pub enum GameDataError {
+    /// An order type takes no time at 100% speed, or less.
+    NonPositiveOrderDuration,
+    /// The order weights of a post level, from 1, are negative or add up to nothing.
+    InvalidOrderWeights(usize),
+    /// Tailoring takes negative time to ramp up.
+    NegativeTailoringRamp,
+    /// A tailoring skill ramps the order weights less than none of the way, or past them.
+    InvalidTailoringMultiplier,
+}
+
+impl std::fmt::Display for GameDataError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GameDataError::NonPositiveOrderDuration => {
+                write!(f, "order durations must be above 0 seconds")
+            }
+            GameDataError::InvalidOrderWeights(level) => write!(
+                f,
+                "order weights of level {} must be 0 or more, and not all 0",
+                level
+            ),
+            GameDataError::NegativeTailoringRamp => {
+                write!(f, "tailoring ramp can't take negative time")
+            }
+            GameDataError::InvalidTailoringMultiplier => {
+                write!(f, "tailoring ramp multipliers must be from 0 to 1")
+            }
+        }
+    }
+}
+
+impl std::error::Error for GameDataError {}
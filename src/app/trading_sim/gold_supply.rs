@@ -0,0 +1,216 @@
+use std::collections::VecDeque;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use super::monte_carlo::{pick, ShiftRules};
+use super::{Reward, TradingPostProductionInput};
+
+/// Shifts played out to estimate a gold shortage.
+const SHORTAGE_RUNS: usize = 4000;
+
+/// Gold bars on hand for the trading post when the shift starts, and the gold coming in. Orders
+/// are only delivered at a collection while there is gold for them, and wait in the post
+/// otherwise.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug, PartialEq)]
+pub struct GoldSupply {
+    pub stock: i32,
+    /// Gold bars coming in every hour, such as from factories.
+    pub per_hour: f64,
+}
+
+/// How a gold supply holds up over a shift.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct GoldShortage {
+    /// Chance that orders wait for gold at each collection, and at the end of the shift, in
+    /// percent.
+    pub interval_wait_chance: Vec<f64>,
+    /// Chance that orders wait for gold at any of them, in percent.
+    pub wait_chance: f64,
+    /// Expected LMD of the orders still waiting for gold at the end of the shift.
+    pub delayed_lmd: f64,
+    /// Expected LMD of the orders the post doesn't get to make, as orders waiting for gold fill
+    /// it up.
+    pub lost_lmd: f64,
+    /// Expected gold on hand at the end of the shift.
+    pub stock_at_end: f64,
+}
+
+/// Outcome of one shift played out with a gold supply.
+struct GoldSample {
+    /// LMD of the orders finished during the shift.
+    made_lmd: f64,
+    /// Whether orders waited for gold at each collection, and at the end of the shift.
+    waited: Vec<bool>,
+    delayed_lmd: f64,
+    stock: f64,
+}
+
+/// Gold on hand, and the orders waiting in the post in the order they finished.
+struct GoldLedger<'a> {
+    supply: Option<&'a GoldSupply>,
+    stock: f64,
+    /// Seconds into the shift up to which incoming gold has been added.
+    updated: usize,
+    /// Value of every order waiting.
+    waiting: VecDeque<Reward>,
+}
+
+impl GoldLedger<'_> {
+    /// Delivers the waiting orders at `time`, in turn, for as long as there is gold for them.
+    /// Returns whether any are left waiting.
+    fn deliver(&mut self, time: usize) -> bool {
+        let supply = match self.supply {
+            Some(supply) => supply,
+            None => {
+                self.waiting.clear();
+                return false;
+            }
+        };
+        self.stock += supply.per_hour * (time - self.updated) as f64 / 3600.0;
+        self.updated = time;
+        while let Some(order) = self.waiting.front() {
+            let cost = -order.gold;
+            if self.stock < cost {
+                break;
+            }
+            self.stock -= cost;
+            self.waiting.pop_front();
+        }
+        !self.waiting.is_empty()
+    }
+}
+
+impl ShiftRules<'_> {
+    /// Plays out one shift order by order like the Monte Carlo simulator, but keeps track of the type of
+    /// every order waiting, so that collections only deliver those there is gold for. Without a
+    /// `supply`, every collection empties the post.
+    fn sample_gold(&self, rng: &mut StdRng, supply: Option<&GoldSupply>) -> GoldSample {
+        let end = self.end;
+        let mut ledger = GoldLedger {
+            supply,
+            stock: supply.map_or(0.0, |supply| supply.stock as f64),
+            updated: 0,
+            waiting: VecDeque::new(),
+        };
+        let mut waited = vec![false; self.collections.len() + 1];
+        let mut made_lmd = 0.0;
+        let mut drones_left = self.drones();
+        let stored_orders = self.input.start_state.stored_orders;
+        for (otype, count) in stored_orders.iter().enumerate() {
+            for _ in 0..(*count).max(0) {
+                if ledger.waiting.len() < self.order_limit {
+                    ledger.waiting.push_back(self.value(otype, 0).full());
+                }
+            }
+        }
+        if self.collected_at_start {
+            waited[0] |= ledger.deliver(0);
+        }
+        // index of the next collection
+        let mut next = 0;
+        let mut collect_until = |ledger: &mut GoldLedger<'_>, next: &mut usize, time: usize| {
+            while let Some(collection) = self.collections.get(*next).copied() {
+                if collection > time {
+                    break;
+                }
+                waited[*next] |= ledger.deliver(collection);
+                *next += 1;
+            }
+        };
+
+        let mut t = 0;
+        if ledger.waiting.len() < self.order_limit {
+            let (otype, left) = self.draw_first_order(rng);
+            let (finish, _) = self.spend_drones(0, left, &mut drones_left);
+            t = finish;
+            if t < end {
+                collect_until(&mut ledger, &mut next, t);
+                ledger.waiting.push_back(self.value(otype, 0).full());
+            }
+        }
+        while t < end {
+            if ledger.waiting.len() >= self.order_limit {
+                // stall until a collection makes room
+                match self.collections.get(next).copied() {
+                    Some(collection) => {
+                        t = collection;
+                        collect_until(&mut ledger, &mut next, t);
+                    }
+                    None => t = end,
+                }
+                continue;
+            }
+            let otype = pick(rng, self.order_weights(t));
+            let finish = self.finish(t, ledger.waiting.len(), otype);
+            let (finish, _) = self.spend_drones(t, finish, &mut drones_left);
+            if finish >= end {
+                break;
+            }
+            // orders are collected before one that finishes exactly at a collection is stored
+            collect_until(&mut ledger, &mut next, finish);
+            let order = self.value(otype, t).full();
+            made_lmd += order.lmd;
+            ledger.waiting.push_back(order);
+            t = finish;
+        }
+        collect_until(&mut ledger, &mut next, end);
+        // what is left is delivered as the shift ends
+        let last = waited.len() - 1;
+        waited[last] |= ledger.deliver(end);
+        let delayed_lmd = ledger.waiting.iter().map(|order| order.lmd).sum();
+        GoldSample {
+            made_lmd,
+            waited,
+            delayed_lmd,
+            stock: ledger.stock,
+        }
+    }
+}
+
+/// Estimates how the gold supply of `input` holds up over the shift, by playing out shifts with
+/// an RNG seeded by `seed`. Each shift is played out with the supply and without it from the
+/// same draws, so that the LMD lost stands out from the randomness of the orders. `None` when
+/// the input has no gold supply.
+pub fn simulate_gold_shortage(
+    input: &TradingPostProductionInput,
+    runs: usize,
+    seed: u64,
+) -> Option<GoldShortage> {
+    let supply = input.gold_supply.as_ref()?;
+    if runs == 0 {
+        return Some(GoldShortage::default());
+    }
+    let rules = ShiftRules::new(input);
+    let mut rng = StdRng::seed_from_u64(seed);
+    // runs in which orders waited at each collection and at the end, and at any of them
+    let mut interval_waits = vec![0; rules.collections.len() + 1];
+    let mut waits = 0;
+    let (mut delayed_lmd, mut lost_lmd, mut stock_at_end) = (0.0, 0.0, 0.0);
+    for _ in 0..runs {
+        let mut unlimited_rng = rng.clone();
+        let sample = rules.sample_gold(&mut rng, Some(supply));
+        let unlimited = rules.sample_gold(&mut unlimited_rng, None);
+        for (count, waited) in interval_waits.iter_mut().zip(sample.waited.iter()) {
+            *count += usize::from(*waited);
+        }
+        waits += usize::from(sample.waited.contains(&true));
+        delayed_lmd += sample.delayed_lmd;
+        lost_lmd += unlimited.made_lmd - sample.made_lmd;
+        stock_at_end += sample.stock;
+    }
+    let chance = |count: usize| count as f64 * 100.0 / runs as f64;
+    Some(GoldShortage {
+        interval_wait_chance: interval_waits.into_iter().map(chance).collect(),
+        wait_chance: chance(waits),
+        delayed_lmd: delayed_lmd / runs as f64,
+        lost_lmd: lost_lmd / runs as f64,
+        stock_at_end: stock_at_end / runs as f64,
+    })
+}
+
+/// `simulate_gold_shortage` with as many runs as the app and the command line report.
+pub fn gold_shortage(input: &TradingPostProductionInput) -> Option<GoldShortage> {
+    simulate_gold_shortage(input, SHORTAGE_RUNS, 0)
+}
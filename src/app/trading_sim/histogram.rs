@@ -0,0 +1,145 @@
+/// Unnormalised probability mass over equally spaced bins, stored only over the range in use.
+///
+/// Every bin also keeps the first two moments of where its mass sits within it. Moving the
+/// histogram by a fractional shift then moves each bin whole instead of spreading it over two
+/// bins, which would widen the distribution with every move, and the variance stays exact.
+#[derive(Clone, Default)]
+pub struct Histogram {
+    first: i64,
+    bins: Vec<Bin>,
+}
+
+/// Mass in one bin, with its moments about the bin's lower edge.
+#[derive(Clone, Copy, Default)]
+struct Bin {
+    mass: f64,
+    /// Sum of mass times position within the bin.
+    offset: f64,
+    /// Sum of mass times squared position within the bin.
+    offset_squared: f64,
+}
+
+impl Bin {
+    /// The same mass with every position moved up by `shift` bins.
+    fn shifted(self, shift: f64, factor: f64) -> Self {
+        Self {
+            mass: self.mass * factor,
+            offset: (self.offset + shift * self.mass) * factor,
+            offset_squared: (self.offset_squared
+                + 2.0 * shift * self.offset
+                + shift * shift * self.mass)
+                * factor,
+        }
+    }
+
+    fn position(&self) -> f64 {
+        self.offset / self.mass
+    }
+
+    /// Variance of the positions within the bin.
+    fn spread(&self) -> f64 {
+        (self.offset_squared / self.mass - self.position().powi(2)).max(0.0)
+    }
+}
+
+impl Histogram {
+    /// Adds `mass` at a fractional bin `position`, in the bin it falls in.
+    pub fn add_point(&mut self, position: f64, mass: f64) {
+        let bin = position.floor();
+        self.add(
+            bin as i64,
+            Bin {
+                mass,
+                ..Default::default()
+            }
+            .shifted(position - bin, 1.0),
+        );
+    }
+
+    /// Adds `factor` of `source`, moved up by a fractional `shift` in bins.
+    pub fn add_shifted(&mut self, source: &Histogram, factor: f64, shift: f64) {
+        if source.bins.is_empty() {
+            return;
+        }
+        let lower = shift.floor() as i64;
+        let last = source.first + source.bins.len() as i64 - 1;
+        self.cover(source.first + lower, last + lower + 1);
+        for (i, bin) in source.bins.iter().enumerate() {
+            if bin.mass <= 0.0 {
+                continue;
+            }
+            // the whole bin lands where its mean does
+            let index = source.first + i as i64;
+            let target = (index as f64 + bin.position() + shift).floor();
+            self.add(
+                target as i64,
+                bin.shifted(index as f64 + shift - target, factor),
+            );
+        }
+    }
+
+    pub fn total(&self) -> f64 {
+        self.bins.iter().map(|bin| bin.mass).sum()
+    }
+
+    /// Mean and variance of the positions, in bins.
+    pub fn moments(&self) -> (f64, f64) {
+        let total = self.total();
+        if total <= 0.0 {
+            return (0.0, 0.0);
+        }
+        let mean = self
+            .iter()
+            .map(|(position, mass)| position * mass)
+            .sum::<f64>()
+            / total;
+        let variance = self
+            .bins
+            .iter()
+            .filter(|bin| bin.mass > 0.0)
+            .zip(self.iter())
+            .map(|(bin, (position, mass))| mass * ((position - mean).powi(2) + bin.spread()))
+            .sum::<f64>()
+            / total;
+        (mean, variance)
+    }
+
+    /// Iterates over `(position, mass)` pairs of every non-empty bin in increasing order, where
+    /// `position` is the mean fractional bin of the bin's mass.
+    pub fn iter(&self) -> impl Iterator<Item = (f64, f64)> + '_ {
+        self.bins
+            .iter()
+            .enumerate()
+            .filter(|(_, bin)| bin.mass > 0.0)
+            .map(move |(i, bin)| ((self.first + i as i64) as f64 + bin.position(), bin.mass))
+    }
+
+    /// Adds `mass` to `index`, growing the stored range to include it.
+    fn add(&mut self, index: i64, mass: Bin) {
+        self.cover(index, index);
+        let bin = &mut self.bins[(index - self.first) as usize];
+        bin.mass += mass.mass;
+        bin.offset += mass.offset;
+        bin.offset_squared += mass.offset_squared;
+    }
+
+    /// Grows the stored range to include bins `lo..=hi`.
+    fn cover(&mut self, lo: i64, hi: i64) {
+        if self.bins.is_empty() {
+            self.first = lo;
+            self.bins = vec![Bin::default(); (hi - lo + 1) as usize];
+            return;
+        }
+        if lo < self.first {
+            let extra = (self.first - lo) as usize;
+            self.bins
+                .splice(0..0, std::iter::repeat(Bin::default()).take(extra));
+            self.first = lo;
+        }
+        let last = self.first + self.bins.len() as i64 - 1;
+        if hi > last {
+            self.bins
+                .resize(self.bins.len() + (hi - last) as usize, Bin::default());
+        }
+    }
+}
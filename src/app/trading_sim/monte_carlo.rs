@@ -0,0 +1,679 @@
+use std::sync::Arc;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use super::{
+    production_output, Histogram, Reward, ShiftDistribution, TradingPostDayOutput,
+    TradingPostPhase, TradingPostProductionInput, TradingPostProductionOutput, TradingPostSkill,
+    DRONE_SECONDS, GOLD_BIN_WIDTH, LMD_BIN_WIDTH,
+};
+
+/// Sample estimate of the production output together with its uncertainty.
+#[derive(Clone, Default)]
+pub struct MonteCarloOutput {
+    pub runs: usize,
+    /// Sample means of every output field, and sample distributions of LMD and gold.
+    pub estimate: TradingPostProductionOutput,
+    /// Half-width of the 95% confidence interval of each field of `estimate`.
+    pub ci95: TradingPostProductionOutput,
+}
+
+/// Sample estimate of a day plan's output together with its uncertainty.
+#[derive(Clone, Default)]
+pub struct MonteCarloDayOutput {
+    pub runs: usize,
+    /// Sample means of every output field, and sample distributions of the day's LMD and gold.
+    pub estimate: TradingPostDayOutput,
+    /// Half-width of the 95% confidence interval of each field of `estimate`.
+    pub ci95: TradingPostDayOutput,
+}
+
+/// Outcome of one simulated shift.
+struct ShiftSample {
+    stall_time: usize,
+    /// Whether the post stalled before each collection, and before the end of the shift.
+    stalled_intervals: Vec<bool>,
+    lmd: f64,
+    gold: f64,
+    drones_used: usize,
+}
+
+/// State of the post handed from the end of one sampled shift to the start of the next.
+#[derive(Default)]
+struct SampleHandover {
+    stored: usize,
+    /// Type of the order in progress, if there is one, and the share of its time still to go.
+    order: Option<(usize, f64)>,
+    /// Value of the order in progress not yet credited.
+    remainder: Reward,
+}
+
+/// Draws an order type from its probabilities.
+pub(super) fn pick(rng: &mut StdRng, order_weight: [f64; 3]) -> usize {
+    let mut roll: f64 = rng.gen();
+    for (otype, weight) in order_weight.iter().enumerate() {
+        if roll < *weight {
+            return otype;
+        }
+        roll -= weight;
+    }
+    // rounding leftovers go to the last order type that can occur
+    order_weight.iter().rposition(|w| *w > 0.0).unwrap_or(0)
+}
+
+/// What an order pays. Its LMD and gold are credited to the shifts that work on it, by their
+/// share of its time, and its bonus LMD to the shift it starts in.
+#[derive(Clone, Copy)]
+pub(super) struct OrderValue {
+    lmd: f64,
+    gold: f64,
+    bonus_lmd: f64,
+}
+
+impl OrderValue {
+    /// Everything the order pays.
+    pub(super) fn full(&self) -> Reward {
+        Reward {
+            lmd: self.lmd + self.bonus_lmd,
+            gold: self.gold,
+        }
+    }
+
+    /// LMD and gold for `share` of the order's time, without the bonus.
+    fn share(&self, share: f64) -> Reward {
+        Reward {
+            lmd: self.lmd * share,
+            gold: self.gold * share,
+        }
+    }
+}
+
+/// How a shift plays out, worked out from the input's game data and skills. The Monte Carlo
+/// simulator keeps these rules to itself instead of sharing the DP's `TradingPostModel`, so that
+/// a mistake in either engine shows up as a disagreement between them.
+pub(super) struct ShiftRules<'a> {
+    pub(super) input: &'a TradingPostProductionInput,
+    skills: Vec<Arc<dyn TradingPostSkill>>,
+    pub(super) order_limit: usize,
+    /// Seconds the shift lasts.
+    pub(super) end: usize,
+    /// Speed in percent over each minute of the shift, by orders waiting then minute.
+    speeds: Vec<Vec<f64>>,
+    /// Whether the post is emptied as the shift starts.
+    pub(super) collected_at_start: bool,
+    /// Seconds into the shift of the collections after it starts, in increasing order.
+    pub(super) collections: Vec<usize>,
+    /// Seconds into the shift and drone count of every drone usage, in increasing time order
+    /// with one usage per time.
+    drone_usages: Vec<(usize, usize)>,
+}
+
+impl<'a> ShiftRules<'a> {
+    pub(super) fn new(input: &'a TradingPostProductionInput) -> Self {
+        let skills = input.skills();
+        let order_limit = input.order_limit(&skills).max(0) as usize;
+        let end = (input.duration_minutes.max(0) * 60) as usize;
+        let speeds = (0..order_limit.max(1))
+            .map(|stored| {
+                (0..((end + 59) / 60).max(1))
+                    .map(|minute| input.speed_at(&skills, stored, minute as i32 * 60).max(1.0))
+                    .collect()
+            })
+            .collect();
+        let mut collections: Vec<usize> = input
+            .collection_minutes
+            .iter()
+            .filter(|minutes| **minutes > 0)
+            .map(|minutes| (minutes * 60) as usize)
+            .filter(|time| *time < end)
+            .collect();
+        collections.sort_unstable();
+        collections.dedup();
+        let mut usages: Vec<(usize, usize)> = input
+            .drone_usages
+            .iter()
+            .map(|usage| {
+                (
+                    (usage.minute.max(0) * 60) as usize,
+                    usage.drones.max(0) as usize,
+                )
+            })
+            .filter(|(time, drones)| *time < end && *drones > 0)
+            .collect();
+        usages.sort_unstable();
+        let mut drone_usages: Vec<(usize, usize)> = vec![];
+        for (time, drones) in usages {
+            match drone_usages.last_mut() {
+                Some((last, total)) if *last == time => *total += drones,
+                _ => drone_usages.push((time, drones)),
+            }
+        }
+        Self {
+            input,
+            skills,
+            order_limit,
+            end,
+            speeds,
+            collected_at_start: input.collection_minutes.contains(&0),
+            collections,
+            drone_usages,
+        }
+    }
+
+    /// Speed in percent `time` seconds into the shift with `stored` orders waiting. The speed of
+    /// the last minute holds on past the end of the shift.
+    fn speed(&self, stored: usize, time: usize) -> f64 {
+        let speeds = &self.speeds[stored];
+        speeds[(time / 60).min(speeds.len() - 1)]
+    }
+
+    /// Type probabilities of an order started `time` seconds into the shift, ramped as far as
+    /// the furthest ramped tailoring skill.
+    pub(super) fn order_weights(&self, time: usize) -> [f64; 3] {
+        let game_data = &self.input.game_data;
+        let level = match self.input.phase {
+            TradingPostPhase::L1 => 0,
+            TradingPostPhase::L2 => 1,
+            TradingPostPhase::L3 => 2,
+        };
+        let ramp = self
+            .skills
+            .iter()
+            .map(|skill| skill.tailoring_ramp(time as i32, game_data.tailoring_ramp_seconds))
+            .fold(0.0, f64::max);
+        game_data.order_weights[level].map(|(base, peak)| base + (peak - base) * ramp)
+    }
+
+    /// What an order of type `otype` started `time` seconds into the shift pays, with the
+    /// skills of the operators still working then.
+    pub(super) fn value(&self, otype: usize, time: usize) -> OrderValue {
+        let game_data = &self.input.game_data;
+        let mut lmd = game_data.order_lmd;
+        let mut gold_cost = game_data.order_gold_cost;
+        let mut bonus_lmd = [0.0; 3];
+        for skill in self.skills.iter() {
+            skill.order_value_at(&mut lmd, &mut gold_cost, time as i32);
+            skill.order_bonus_at(&mut bonus_lmd, time as i32);
+        }
+        OrderValue {
+            lmd: lmd[otype] as f64,
+            gold: -gold_cost[otype] as f64,
+            bonus_lmd: bonus_lmd[otype],
+        }
+    }
+
+    /// Value of the orders stored at the start of the shift, from its start state.
+    pub(super) fn stored_value(&self) -> Reward {
+        let stored_orders = self.input.start_state.stored_orders;
+        (0..3)
+            .map(|otype| {
+                let value = self.value(otype, 0).full();
+                let count = stored_orders[otype] as f64;
+                Reward {
+                    lmd: value.lmd * count,
+                    gold: value.gold * count,
+                }
+            })
+            .fold(Reward::default(), |x, y| x + y)
+    }
+
+    /// Seconds into the shift at which an order of type `otype` started at `start` with
+    /// `stored` orders waiting finishes, working through the speed of each minute in turn.
+    pub(super) fn finish(&self, start: usize, stored: usize, otype: usize) -> usize {
+        let minutes = self.speeds[stored].len();
+        let mut work = (self.input.game_data.order_duration_seconds[otype] * 100) as f64;
+        let mut time = start;
+        loop {
+            let speed = self.speed(stored, time);
+            let minute_end = (time / 60 + 1) * 60;
+            let seconds = work / speed;
+            if time / 60 + 1 >= minutes || seconds <= (minute_end - time) as f64 {
+                // leave out rounding errors, so that whole seconds stay whole
+                let finish = time + (seconds - 1e-6).ceil().max(0.0) as usize;
+                return finish.max(start + 1);
+            }
+            work -= speed * (minute_end - time) as f64;
+            time = minute_end;
+        }
+    }
+
+    /// Seconds an order of type `otype` in progress at the start of the shift takes from start
+    /// to finish. Its speed is unknown, so it counts as worked at the speed the shift starts
+    /// with while no orders wait.
+    fn first_duration(&self, otype: usize) -> usize {
+        let work = (self.input.game_data.order_duration_seconds[otype] * 100) as f64;
+        (work / self.speed(0, 0)).ceil() as usize
+    }
+
+    /// Draws the type of the order in progress at the start of the shift, and the seconds left
+    /// on it. Unless the start state says, the time left is uniform over the order's duration.
+    /// When it does, each type is as likely as it is to have that much left.
+    pub(super) fn draw_first_order(&self, rng: &mut StdRng) -> (usize, usize) {
+        let weights = self.order_weights(0);
+        let left = match self.input.start_state.minutes_left {
+            Some(minutes) => (minutes.max(0) * 60) as usize,
+            None => {
+                let otype = pick(rng, weights);
+                return (otype, rng.gen_range(0..self.first_duration(otype)));
+            }
+        };
+        let mut likely = [0.0; 3];
+        for (otype, weight) in weights.iter().enumerate() {
+            let duration = self.first_duration(otype);
+            if left <= duration {
+                likely[otype] = weight / duration as f64;
+            }
+        }
+        let total: f64 = likely.iter().sum();
+        let otype = if total > 0.0 {
+            pick(rng, likely.map(|chance| chance / total))
+        } else {
+            pick(rng, weights)
+        };
+        (otype, left)
+    }
+
+    /// Orders waiting once an order started at `start` with `stored` orders waiting is stored
+    /// at `finish`. A collection while it is in progress leaves only the new order, and one as
+    /// it finishes takes the new order along.
+    pub(super) fn stored_on_finish(&self, stored: usize, start: usize, finish: usize) -> usize {
+        let collected = self
+            .collections
+            .iter()
+            .rev()
+            .find(|time| (start + 1..=finish).contains(*time));
+        match collected {
+            Some(time) if *time == finish => 0,
+            Some(_) => 1,
+            None => stored + 1,
+        }
+    }
+
+    /// Orders waiting as the shift ends with an order started at `start` still in progress.
+    fn stored_at_end(&self, stored: usize, start: usize) -> usize {
+        if self.collections.iter().any(|time| *time > start) {
+            0
+        } else {
+            stored
+        }
+    }
+
+    /// Drones of every drone usage, in time order.
+    pub(super) fn drones(&self) -> Vec<usize> {
+        self.drone_usages
+            .iter()
+            .map(|(_, drones)| *drones)
+            .collect()
+    }
+
+    /// Spends the drones of every usage while an order is in progress from `start` until
+    /// `finish`, taking them from `drones_left`. Returns when the order finishes, and the drones
+    /// spent on it.
+    pub(super) fn spend_drones(
+        &self,
+        start: usize,
+        mut finish: usize,
+        drones_left: &mut [usize],
+    ) -> (usize, usize) {
+        let mut used = 0;
+        for (usage, (time, _)) in self.drone_usages.iter().enumerate() {
+            if *time >= finish {
+                break;
+            }
+            if *time < start {
+                continue;
+            }
+            let needed = (finish - time + DRONE_SECONDS - 1) / DRONE_SECONDS;
+            let spent = needed.min(drones_left[usage]);
+            drones_left[usage] -= spent;
+            used += spent;
+            if spent == needed {
+                // the order finishes as the drones go in, and the rest wait for the next one
+                return (*time, used);
+            }
+            finish -= spent * DRONE_SECONDS;
+        }
+        (finish, used)
+    }
+
+    /// Minutes into the shift at which each collection interval ends.
+    fn interval_end_minutes(&self) -> Vec<i32> {
+        self.collections
+            .iter()
+            .map(|time| (time / 60) as i32)
+            .chain(std::iter::once(self.input.duration_minutes))
+            .collect()
+    }
+
+    /// Plays out one shift order by order, starting from `start` when it follows another shift.
+    /// With `hand_over`, the end state is also handed over to the next shift.
+    fn sample_shift(
+        &self,
+        rng: &mut StdRng,
+        start: Option<&SampleHandover>,
+        hand_over: bool,
+    ) -> (ShiftSample, SampleHandover) {
+        let end = self.end;
+        let mut sample = ShiftSample {
+            stall_time: 0,
+            stalled_intervals: vec![false; self.collections.len() + 1],
+            lmd: 0.0,
+            gold: 0.0,
+            drones_used: 0,
+        };
+        let mut credit = |reward: Reward| {
+            sample.lmd += reward.lmd;
+            sample.gold += reward.gold;
+        };
+        let mut handover = SampleHandover::default();
+        let mut drones_left = self.drones();
+        let mut drones_used = 0;
+
+        let mut t = 0;
+        let mut stored = match start {
+            Some(start) => {
+                // the rest of an order carried over is credited to the shift it is carried into
+                credit(start.remainder);
+                if self.collected_at_start {
+                    0
+                } else {
+                    start.stored
+                }
+            }
+            None => {
+                // the orders stored at the start are credited as they are delivered
+                credit(self.stored_value());
+                if self.collected_at_start {
+                    0
+                } else {
+                    let stored_orders = self.input.start_state.stored_orders;
+                    stored_orders.iter().sum::<i32>().max(0) as usize
+                }
+            }
+        };
+        let carried = start.and_then(|start| start.order);
+        if carried.is_some() {
+            // a post with an order in progress has room for at least that order
+            stored = stored.min(self.order_limit.saturating_sub(1));
+        } else {
+            stored = stored.min(self.order_limit);
+        }
+        let first_order = match carried {
+            Some((otype, share_left)) => {
+                let left = (share_left * self.first_duration(otype) as f64).ceil() as usize;
+                Some((otype, left, None))
+            }
+            None if start.is_none() && stored < self.order_limit => {
+                let (otype, left) = self.draw_first_order(rng);
+                Some((otype, left, Some(self.value(otype, 0))))
+            }
+            None => None,
+        };
+        // the order in progress at the start; its value is only credited here when it didn't
+        // come from the shift before
+        if let Some((otype, left, value)) = first_order {
+            let duration = self.first_duration(otype);
+            let (finish, used) = self.spend_drones(0, left, &mut drones_left);
+            drones_used += used;
+            // it started before this shift, so its bonus isn't credited to it
+            let worked = (end + used * DRONE_SECONDS).min(left).min(duration);
+            if let Some(value) = value {
+                credit(value.share(worked as f64 / duration as f64));
+            }
+            if finish < end {
+                stored = self.stored_on_finish(stored, 0, finish);
+            } else if hand_over {
+                handover.stored = self.stored_at_end(stored, 0);
+                handover.order = Some((otype, (finish - end) as f64 / duration as f64));
+                if let Some(value) = value {
+                    let rest = left.min(duration) - worked;
+                    handover.remainder = value.share(rest as f64 / duration as f64);
+                }
+            }
+            t = finish;
+        }
+
+        while t < end {
+            if stored >= self.order_limit {
+                // stall until the next collection empties the post
+                let interval = self.collections.partition_point(|time| *time <= t);
+                sample.stalled_intervals[interval] = true;
+                match self.collections.get(interval) {
+                    Some(collection) => {
+                        sample.stall_time += collection - t;
+                        t = *collection;
+                        stored = 0;
+                    }
+                    None => {
+                        // a full post starts the next shift stalled
+                        sample.stall_time += end - t;
+                        t = end;
+                        handover.stored = self.order_limit;
+                    }
+                }
+                continue;
+            }
+            let otype = pick(rng, self.order_weights(t));
+            let natural_finish = self.finish(t, stored, otype);
+            let duration = natural_finish - t;
+            let (finish, used) = self.spend_drones(t, natural_finish, &mut drones_left);
+            drones_used += used;
+            let value = self.value(otype, t);
+            if finish < end {
+                credit(value.full());
+                stored = self.stored_on_finish(stored, t, finish);
+                t = finish;
+                continue;
+            }
+            // it started in this shift, so its bonus is credited to it in full, along with the
+            // share of its time worked
+            let worked = (end - t + used * DRONE_SECONDS).min(duration);
+            credit(value.share(worked as f64 / duration as f64));
+            credit(Reward {
+                lmd: value.bonus_lmd,
+                gold: 0.0,
+            });
+            if hand_over {
+                handover.stored = self.stored_at_end(stored, t);
+                handover.order = Some((otype, (finish - end) as f64 / duration as f64));
+                handover.remainder = value.share((duration - worked) as f64 / duration as f64);
+            }
+            break;
+        }
+        sample.drones_used = drones_used;
+        (sample, handover)
+    }
+}
+
+/// Sample means of every field of `samples`, and the half-widths of their 95% confidence
+/// intervals. Distributions and the average speed are left empty.
+fn summarize(
+    samples: &[TradingPostProductionOutput],
+    interval_end_minutes: Vec<i32>,
+) -> (TradingPostProductionOutput, TradingPostProductionOutput) {
+    // sample mean and 95% confidence half-width of one output field
+    let stats = |field: &dyn Fn(&TradingPostProductionOutput) -> f64| {
+        let n = samples.len() as f64;
+        let mean = samples.iter().map(field).sum::<f64>() / n;
+        let variance = samples
+            .iter()
+            .map(|sample| (field(sample) - mean).powi(2))
+            .sum::<f64>()
+            / (n - 1.0).max(1.0);
+        (mean, 1.96 * (variance / n).sqrt())
+    };
+    let (stall_chance, stall_chance_ci) = stats(&|o| o.stall_chance);
+    let (average_stall_time, average_stall_time_ci) = stats(&|o| o.average_stall_time);
+    let (total_lmd, total_lmd_ci) = stats(&|o| o.total_lmd);
+    let (total_gold, total_gold_ci) = stats(&|o| o.total_gold);
+    let (daily_lmd, daily_lmd_ci) = stats(&|o| o.daily_lmd);
+    let (daily_gold, daily_gold_ci) = stats(&|o| o.daily_gold);
+    let (net_lmd_speed, net_lmd_speed_ci) = stats(&|o| o.net_lmd_speed);
+    let (net_gold_speed, net_gold_speed_ci) = stats(&|o| o.net_gold_speed);
+    let (drones_used, drones_used_ci) = stats(&|o| o.drones_used);
+
+    let (interval_stall_chance, interval_stall_chance_ci) = (0..interval_end_minutes.len())
+        .map(|interval| stats(&|o| o.interval_stall_chance[interval]))
+        .unzip();
+
+    (
+        TradingPostProductionOutput {
+            stall_chance,
+            average_stall_time,
+            total_lmd,
+            total_gold,
+            daily_lmd,
+            daily_gold,
+            net_lmd_speed,
+            net_gold_speed,
+            drones_used,
+            interval_end_minutes: interval_end_minutes.clone(),
+            interval_stall_chance,
+            ..Default::default()
+        },
+        TradingPostProductionOutput {
+            stall_chance: stall_chance_ci,
+            average_stall_time: average_stall_time_ci,
+            total_lmd: total_lmd_ci,
+            total_gold: total_gold_ci,
+            daily_lmd: daily_lmd_ci,
+            daily_gold: daily_gold_ci,
+            net_lmd_speed: net_lmd_speed_ci,
+            net_gold_speed: net_gold_speed_ci,
+            drones_used: drones_used_ci,
+            interval_end_minutes,
+            interval_stall_chance: interval_stall_chance_ci,
+            ..Default::default()
+        },
+    )
+}
+
+/// Estimates the production output by playing out `runs` shifts with an RNG seeded by `seed`.
+/// Orders are timed, stored and credited by rules of its own, worked out from the game data and
+/// skills, so it is an independent check on the DP in `simulate_tp_production`.
+pub fn simulate_tp_monte_carlo(
+    input: &TradingPostProductionInput,
+    runs: usize,
+    seed: u64,
+) -> MonteCarloOutput {
+    let day = simulate_tp_day_monte_carlo(std::slice::from_ref(input), runs, seed);
+    MonteCarloOutput {
+        runs: day.runs,
+        estimate: day.estimate.day,
+        ci95: day.ci95.day,
+    }
+}
+
+/// Estimates the output of a day plan by playing out `runs` days with an RNG seeded by `seed`.
+/// The Monte Carlo counterpart of `simulate_tp_day`.
+pub fn simulate_tp_day_monte_carlo(
+    shifts: &[TradingPostProductionInput],
+    runs: usize,
+    seed: u64,
+) -> MonteCarloDayOutput {
+    if runs == 0 || shifts.is_empty() {
+        return MonteCarloDayOutput::default();
+    }
+    let rules: Vec<ShiftRules<'_>> = shifts.iter().map(ShiftRules::new).collect();
+    let stored_value = rules[0].stored_value();
+    let day_minutes: i32 = shifts.iter().map(|input| input.duration_minutes).sum();
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut lmd_histogram = Histogram::default();
+    let mut gold_histogram = Histogram::default();
+    let mut shift_samples = vec![Vec::with_capacity(runs); shifts.len()];
+    let mut day_samples = Vec::with_capacity(runs);
+    let indicator = |stalled: bool| if stalled { 100.0 } else { 0.0 };
+    for _ in 0..runs {
+        let mut handover = None;
+        let mut day = ShiftSample {
+            stall_time: 0,
+            stalled_intervals: vec![],
+            lmd: 0.0,
+            gold: 0.0,
+            drones_used: 0,
+        };
+        for (index, shift) in rules.iter().enumerate() {
+            let hand_over = index + 1 < rules.len();
+            let (sample, next_handover) =
+                shift.sample_shift(&mut rng, handover.as_ref(), hand_over);
+            handover = Some(next_handover);
+            shift_samples[index].push(TradingPostProductionOutput {
+                interval_stall_chance: sample
+                    .stalled_intervals
+                    .iter()
+                    .map(|stalled| indicator(*stalled))
+                    .collect(),
+                drones_used: sample.drones_used as f64,
+                ..production_output(
+                    &shift.input.game_data,
+                    shift.input.duration_minutes,
+                    indicator(sample.stalled_intervals.contains(&true)),
+                    sample.stall_time as f64 / 60.0,
+                    sample.lmd,
+                    sample.gold,
+                    if index == 0 {
+                        stored_value
+                    } else {
+                        Reward::default()
+                    },
+                )
+            });
+            day.stall_time += sample.stall_time;
+            day.stalled_intervals.extend(sample.stalled_intervals);
+            day.lmd += sample.lmd;
+            day.gold += sample.gold;
+            day.drones_used += sample.drones_used;
+        }
+        lmd_histogram.add_point(day.lmd / LMD_BIN_WIDTH, 1.0);
+        gold_histogram.add_point(day.gold / GOLD_BIN_WIDTH, 1.0);
+        day_samples.push(TradingPostProductionOutput {
+            interval_stall_chance: day
+                .stalled_intervals
+                .iter()
+                .map(|stalled| indicator(*stalled))
+                .collect(),
+            drones_used: day.drones_used as f64,
+            ..production_output(
+                &rules[0].input.game_data,
+                day_minutes,
+                indicator(day.stalled_intervals.contains(&true)),
+                day.stall_time as f64 / 60.0,
+                day.lmd,
+                day.gold,
+                stored_value,
+            )
+        });
+    }
+
+    let (shift_estimates, shift_cis) = rules
+        .iter()
+        .zip(shift_samples.iter())
+        .map(|(shift, samples)| summarize(samples, shift.interval_end_minutes()))
+        .unzip();
+    let mut interval_end_minutes = vec![];
+    let mut shift_start = 0;
+    for shift in rules.iter() {
+        interval_end_minutes.extend(shift.interval_end_minutes().iter().map(|m| m + shift_start));
+        shift_start += shift.input.duration_minutes;
+    }
+    let (day_estimate, day_ci) = summarize(&day_samples, interval_end_minutes);
+    MonteCarloDayOutput {
+        runs,
+        estimate: TradingPostDayOutput {
+            shifts: shift_estimates,
+            day: TradingPostProductionOutput {
+                lmd_distribution: ShiftDistribution::from_histogram(&lmd_histogram, LMD_BIN_WIDTH),
+                gold_distribution: ShiftDistribution::from_histogram(
+                    &gold_histogram,
+                    GOLD_BIN_WIDTH,
+                ),
+                ..day_estimate
+            },
+        },
+        ci95: TradingPostDayOutput {
+            shifts: shift_cis,
+            day: day_ci,
+        },
+    }
+}
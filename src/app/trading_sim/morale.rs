@@ -0,0 +1,58 @@
+use crate::app::names::is_named;
+
+/// Most morale an operator can have.
+pub const MAX_MORALE: f64 = 24.0;
+
+/// Morale of an operator stationed in the trading post. Parts of the speed breakdown named after
+/// the operator stop once their morale runs out, and so do the skills of Jaye, Tequila, Proviso
+/// and tailoring operators, who go by the name of their tailoring skill. Orders started from then
+/// on are timed, drawn and valued without them. The order limit is settled for the whole shift.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug, PartialEq)]
+pub struct OperatorMorale {
+    pub name: String,
+    /// Morale when the shift starts, from 0 to 24.
+    pub morale: f64,
+    /// Morale lost every hour.
+    pub drain_per_hour: f64,
+}
+
+impl OperatorMorale {
+    /// Whether `name` refers to the operator, ignoring case and surrounding spaces.
+    pub fn is(&self, name: &str) -> bool {
+        is_named(&self.name, name)
+    }
+
+    /// Seconds into the shift at which the operator runs out of morale, if they ever do.
+    pub fn exhausted_seconds(&self) -> Option<i32> {
+        if self.morale <= 0.0 {
+            return Some(0);
+        }
+        if self.drain_per_hour <= 0.0 {
+            return None;
+        }
+        Some((self.morale / self.drain_per_hour * 3600.0).ceil() as i32)
+    }
+}
+
+/// A shift that runs on after an operator has run out of morale.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MoraleShortfall {
+    /// The operator who runs out first.
+    pub name: String,
+    /// Whole minutes into the shift the operator lasts.
+    pub exhausted_minutes: i32,
+    pub duration_minutes: i32,
+}
+
+impl std::fmt::Display for MoraleShortfall {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} runs out of morale at {:0>2}h{:0>2}m, {} minutes before the shift ends",
+            self.name,
+            self.exhausted_minutes / 60,
+            self.exhausted_minutes % 60,
+            self.duration_minutes - self.exhausted_minutes
+        )
+    }
+}
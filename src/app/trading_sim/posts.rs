@@ -0,0 +1,89 @@
+use super::{
+    simulate_tp_day_with_progress, SimError, TradingPostProductionInput,
+    TradingPostProductionOutput,
+};
+
+/// Output of trading posts run side by side, post by post and together. Each post runs its own
+/// shift, and draws its orders independently of the others.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Default)]
+#[serde(default)]
+pub struct TradingPostsOutput {
+    pub trading_posts: Vec<TradingPostProductionOutput>,
+    /// Chance that at least one of the posts stalls during its shift.
+    pub stall_chance: f64,
+    /// LMD credited by the end of every post's shift.
+    pub total_lmd: f64,
+    /// Gold credited by the end of every post's shift.
+    pub total_gold: f64,
+    pub daily_lmd: f64,
+    pub daily_gold: f64,
+    /// Gold bars the posts use up each day, which is what the factories have to keep up with.
+    pub gold_demand: f64,
+    /// Expected drones spent over every post's shift.
+    pub drones_used: f64,
+}
+
+/// The first of several trading posts that can't be simulated, by index.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, PartialEq)]
+pub struct TradingPostsError {
+    pub index: usize,
+    pub error: SimError,
+}
+
+impl std::fmt::Display for TradingPostsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "trading post {}: {}", self.index + 1, self.error)
+    }
+}
+
+impl std::error::Error for TradingPostsError {}
+
+/// Simulates trading posts side by side, each over a shift of its own.
+pub fn simulate_trading_posts(posts: &[TradingPostProductionInput]) -> TradingPostsOutput {
+    simulate_trading_posts_with_progress(posts, &mut |_| true)
+        .expect("simulation is never cancelled")
+}
+
+/// `simulate_trading_posts` that rejects posts it can't simulate.
+pub fn try_simulate_trading_posts(
+    posts: &[TradingPostProductionInput],
+) -> Result<TradingPostsOutput, TradingPostsError> {
+    for (index, post) in posts.iter().enumerate() {
+        post.validate()
+            .map_err(|error| TradingPostsError { index, error })?;
+    }
+    Ok(simulate_trading_posts(posts))
+}
+
+/// `simulate_trading_posts` that calls `report` with the fraction done every so often, and gives
+/// up with `None` as soon as `report` returns false.
+pub fn simulate_trading_posts_with_progress(
+    posts: &[TradingPostProductionInput],
+    report: &mut dyn FnMut(f64) -> bool,
+) -> Option<TradingPostsOutput> {
+    let count = posts.len() as f64;
+    let mut outputs = vec![];
+    for (index, post) in posts.iter().enumerate() {
+        let day = simulate_tp_day_with_progress(std::slice::from_ref(post), &mut |done| {
+            report((index as f64 + done) / count)
+        })?;
+        outputs.push(day.day);
+    }
+    let sum = |field: fn(&TradingPostProductionOutput) -> f64| outputs.iter().map(field).sum();
+    // posts are independent, so none stalls only when each of them doesn't
+    let none_stall: f64 = outputs
+        .iter()
+        .map(|output| 1.0 - output.stall_chance / 100.0)
+        .product();
+    Some(TradingPostsOutput {
+        stall_chance: (1.0 - none_stall) * 100.0,
+        total_lmd: sum(|output| output.total_lmd),
+        total_gold: sum(|output| output.total_gold),
+        daily_lmd: sum(|output| output.daily_lmd),
+        daily_gold: sum(|output| output.daily_gold),
+        // orders count the gold they use up as negative
+        gold_demand: -sum(|output| output.daily_gold),
+        drones_used: sum(|output| output.drones_used),
+        trading_posts: outputs,
+    })
+}
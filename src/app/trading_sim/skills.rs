@@ -0,0 +1,218 @@
+use std::sync::Arc;
+
+use super::{JayeOrderCount, OperatorMorale, SpeedBreakdown, SpeedChange};
+
+/// Effect of an operator skill on the trading post. Every hook defaults to no effect, and the
+/// simulator applies the skills of a shift in turn.
+pub trait TradingPostSkill: Send + Sync {
+    /// How far the skill ramps the order weights towards their fully ramped values for an order
+    /// started `elapsed_time` seconds into the shift, from 0 to 1. The post follows the highest
+    /// ramp of any skill.
+    fn tailoring_ramp(&self, _elapsed_time: i32, _ramp_seconds: i32) -> f64 {
+        0.0
+    }
+
+    /// Adjusts the LMD paid and gold used up by each order type.
+    fn order_value(&self, _lmd: &mut [i32; 3], _gold_cost: &mut [i32; 3]) {}
+
+    /// Adds extra LMD credited for each order type.
+    fn order_bonus(&self, _bonus_lmd: &mut [f64; 3]) {}
+
+    /// Adjusts the value of orders started `elapsed_time` seconds into the shift, for skills
+    /// whose effect changes over the shift. The simulator samples it when the shift starts and
+    /// whenever an operator runs out of morale.
+    fn order_value_at(&self, lmd: &mut [i32; 3], gold_cost: &mut [i32; 3], _elapsed_time: i32) {
+        self.order_value(lmd, gold_cost)
+    }
+
+    /// Adds extra LMD credited for orders started `elapsed_time` seconds into the shift, sampled
+    /// like `order_value_at`.
+    fn order_bonus_at(&self, bonus_lmd: &mut [f64; 3], _elapsed_time: i32) {
+        self.order_bonus(bonus_lmd)
+    }
+
+    /// Speed in percent with `stored` orders waiting, given the speed before this skill.
+    fn speed(&self, speed100: f64, _stored: usize) -> f64 {
+        speed100
+    }
+
+    /// Speed in percent `elapsed_time` seconds into the shift, for skills whose effect changes
+    /// over the shift. The simulator samples it at the start of every minute.
+    fn speed_at(&self, speed100: f64, stored: usize, _elapsed_time: i32) -> f64 {
+        self.speed(speed100, stored)
+    }
+
+    /// Order limit of the post, given the limit before this skill.
+    fn capacity(&self, capacity: i32) -> i32 {
+        capacity
+    }
+}
+
+/// Tailoring skill that has already ramped up for `ramped_minutes` when the shift starts, and
+/// ramps the order weights `ramp_multiplier` of the way once ramped up fully. The multiplier
+/// comes from the game data for the skill.
+pub struct Tailoring {
+    pub ramp_multiplier: f64,
+    pub ramped_minutes: i32,
+}
+
+impl TradingPostSkill for Tailoring {
+    fn tailoring_ramp(&self, elapsed_time: i32, ramp_seconds: i32) -> f64 {
+        let ramped_progress =
+            (self.ramped_minutes * 60 + elapsed_time) as f64 / ramp_seconds as f64;
+        self.ramp_multiplier
+            * if ramped_progress > 1.0 {
+                1.0
+            } else {
+                ramped_progress
+            }
+    }
+}
+
+/// Parts of the speed breakdown that stop as the operators they are named after run out of
+/// morale. Applies before the speed schedule, which takes over from it.
+pub struct ExhaustedParts {
+    pub breakdown: SpeedBreakdown,
+    pub operators: Vec<OperatorMorale>,
+}
+
+impl TradingPostSkill for ExhaustedParts {
+    fn speed_at(&self, speed100: f64, _stored: usize, elapsed_time: i32) -> f64 {
+        let exhausted = |name: &str| {
+            self.operators.iter().any(|operator| {
+                operator.is(name)
+                    && operator
+                        .exhausted_seconds()
+                        .map_or(false, |time| time <= elapsed_time)
+            })
+        };
+        if !self.breakdown.0.iter().any(|part| exhausted(&part.name)) {
+            return speed100;
+        }
+        SpeedBreakdown(
+            self.breakdown
+                .0
+                .iter()
+                .filter(|part| !exhausted(&part.name))
+                .cloned()
+                .collect(),
+        )
+        .speed()
+    }
+}
+
+/// Another skill, which stops once its operator runs out of morale `exhausted_at` seconds into
+/// the shift: orders started from then on are timed, drawn and valued without it. Its effect on
+/// the order limit holds for the whole shift.
+pub struct UntilExhausted {
+    pub skill: Arc<dyn TradingPostSkill>,
+    pub exhausted_at: i32,
+}
+
+impl TradingPostSkill for UntilExhausted {
+    fn tailoring_ramp(&self, elapsed_time: i32, ramp_seconds: i32) -> f64 {
+        if elapsed_time < self.exhausted_at {
+            self.skill.tailoring_ramp(elapsed_time, ramp_seconds)
+        } else {
+            0.0
+        }
+    }
+
+    fn order_value(&self, lmd: &mut [i32; 3], gold_cost: &mut [i32; 3]) {
+        self.order_value_at(lmd, gold_cost, 0);
+    }
+
+    fn order_bonus(&self, bonus_lmd: &mut [f64; 3]) {
+        self.order_bonus_at(bonus_lmd, 0);
+    }
+
+    fn order_value_at(&self, lmd: &mut [i32; 3], gold_cost: &mut [i32; 3], elapsed_time: i32) {
+        if elapsed_time < self.exhausted_at {
+            self.skill.order_value_at(lmd, gold_cost, elapsed_time);
+        }
+    }
+
+    fn order_bonus_at(&self, bonus_lmd: &mut [f64; 3], elapsed_time: i32) {
+        if elapsed_time < self.exhausted_at {
+            self.skill.order_bonus_at(bonus_lmd, elapsed_time);
+        }
+    }
+
+    fn speed_at(&self, speed100: f64, stored: usize, elapsed_time: i32) -> f64 {
+        if elapsed_time < self.exhausted_at {
+            self.skill.speed_at(speed100, stored, elapsed_time)
+        } else {
+            speed100
+        }
+    }
+
+    fn capacity(&self, capacity: i32) -> i32 {
+        self.skill.capacity(capacity)
+    }
+}
+
+/// Changes of the speed before skills over the shift, such as when morale runs low or
+/// operators are swapped. Applies before every other skill, but after the speed parts of
+/// operators out of morale stop.
+pub struct SpeedSchedule(pub Vec<SpeedChange>);
+
+impl TradingPostSkill for SpeedSchedule {
+    fn speed_at(&self, speed100: f64, _stored: usize, elapsed_time: i32) -> f64 {
+        // the latest change that has happened, and the last listed of those at the same minute
+        self.0
+            .iter()
+            .filter(|change| change.from_minutes * 60 <= elapsed_time)
+            .max_by_key(|change| change.from_minutes)
+            .map_or(speed100, |change| change.speed100)
+    }
+}
+
+/// Tequila: `bonus_lmd` extra LMD on every 4-bar order, from the game data for Tequila's elite
+/// phase.
+pub struct Tequila {
+    pub bonus_lmd: f64,
+}
+
+impl TradingPostSkill for Tequila {
+    fn order_bonus(&self, bonus_lmd: &mut [f64; 3]) {
+        bonus_lmd[2] += self.bonus_lmd;
+    }
+}
+
+/// Proviso: 2-bar and 3-bar orders use `extra_gold` more gold, and pay `lmd_per_gold` more LMD
+/// for each. Both come from the game data for Proviso's elite phase.
+pub struct Proviso {
+    pub extra_gold: i32,
+    pub lmd_per_gold: i32,
+}
+
+impl TradingPostSkill for Proviso {
+    fn order_value(&self, lmd: &mut [i32; 3], gold_cost: &mut [i32; 3]) {
+        for otype in 0..2 {
+            lmd[otype] += self.lmd_per_gold * self.extra_gold;
+            gold_cost[otype] += self.extra_gold;
+        }
+    }
+}
+
+/// Jaye: the speed changes by `speed_per_order` percent for every order waiting, and the order
+/// limit by `capacity_change`. Both come from the game data for Jaye's elite phase.
+pub struct Jaye {
+    pub order_count: JayeOrderCount,
+    pub speed_per_order: f64,
+    pub capacity_change: i32,
+}
+
+impl TradingPostSkill for Jaye {
+    fn speed(&self, speed100: f64, stored: usize) -> f64 {
+        let orders = match self.order_count {
+            JayeOrderCount::BeforeFinish => stored,
+            JayeOrderCount::AfterFinish => stored + 1,
+        } as f64;
+        speed100 + self.speed_per_order * orders
+    }
+
+    fn capacity(&self, capacity: i32) -> i32 {
+        capacity + self.capacity_change
+    }
+}
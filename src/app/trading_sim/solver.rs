@@ -0,0 +1,130 @@
+use super::{
+    try_simulate, SimError, SweepField, TradingPostProductionInput, TradingPostProductionOutput,
+};
+
+/// How much a shift may stall.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Debug, PartialEq)]
+pub enum StallLimit {
+    /// Highest stall chance, in percent.
+    Chance(f64),
+    /// Longest average stall time, in minutes.
+    AverageTime(f64),
+}
+
+impl StallLimit {
+    fn allows(self, output: &TradingPostProductionOutput) -> bool {
+        match self {
+            StallLimit::Chance(percent) => output.stall_chance <= percent,
+            StallLimit::AverageTime(minutes) => output.average_stall_time <= minutes,
+        }
+    }
+}
+
+impl std::fmt::Display for StallLimit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StallLimit::Chance(percent) => write!(f, "stall chance at most {}%", percent),
+            StallLimit::AverageTime(minutes) => {
+                write!(f, "average stall time at most {} minutes", minutes)
+            }
+        }
+    }
+}
+
+/// Values searched for each field, from the one that stalls least to the one that stalls most.
+/// Stalling gets likelier with speed and duration, and less likely with the order limit.
+fn search_range(field: SweepField) -> [i32; 2] {
+    match field {
+        SweepField::Speed => [1, 1000],
+        SweepField::DurationMinutes => [1, 5400],
+        SweepField::Capacity => [38, 1],
+    }
+}
+
+/// Finds how far one field of an input can be pushed, towards stalling more, before the shift
+/// stalls beyond a limit: the highest speed, the longest duration or the smallest order limit.
+#[derive(serde::Deserialize, serde::Serialize, Clone)]
+pub struct Solver {
+    pub base: TradingPostProductionInput,
+    pub field: SweepField,
+    pub limit: StallLimit,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone)]
+pub struct Solution {
+    /// As far as the field goes within the limit.
+    pub value: i32,
+    /// Output at `value`. Speeds that skills bring to a standstill can't be simulated, but never
+    /// stall either.
+    pub output: Result<TradingPostProductionOutput, SimError>,
+    /// Whether `value` is the end of the range searched, so that the limit may allow more.
+    pub at_range_end: bool,
+}
+
+impl Solver {
+    /// The solution, or `None` when even the value that stalls least stalls beyond the limit.
+    pub fn solve(&self) -> Result<Option<Solution>, SimError> {
+        self.solve_with_progress(&mut |_| true)
+            .expect("solver is never cancelled")
+    }
+
+    /// `solve` that calls `report` with the fraction done after each simulation, and gives up
+    /// with `None` as soon as `report` returns false.
+    pub fn solve_with_progress(
+        &self,
+        report: &mut dyn FnMut(f64) -> bool,
+    ) -> Option<Result<Option<Solution>, SimError>> {
+        if let Err(error) = self.base.validate() {
+            return Some(Err(error));
+        }
+        let [cautious, bold] = search_range(self.field);
+        let direction = (bold - cautious).signum();
+        let value = |offset: i32| cautious + offset * direction;
+
+        // a binary search, plus both ends of the range
+        let span = (bold - cautious).abs();
+        let total = 2 + (32 - span.leading_zeros()) as usize;
+        let mut done = 0;
+        let mut within = |value: i32| {
+            let mut input = self.base.clone();
+            self.field.set(&mut input, value);
+            let output = try_simulate(&input);
+            let allowed = match &output {
+                Ok(output) => self.limit.allows(output),
+                Err(error) => matches!(error, SimError::StalledBySkills { .. }),
+            };
+            done += 1;
+            let keep_going = report(done as f64 / total as f64);
+            keep_going.then(|| allowed.then(|| output))
+        };
+
+        let mut best = match within(cautious)? {
+            Some(output) => output,
+            None => return Some(Ok(None)),
+        };
+        if let Some(output) = within(bold)? {
+            return Some(Ok(Some(Solution {
+                value: bold,
+                output,
+                at_range_end: true,
+            })));
+        }
+        // offsets known to be within the limit, and beyond it
+        let (mut inside, mut outside) = (0, span);
+        while outside - inside > 1 {
+            let middle = inside + (outside - inside) / 2;
+            match within(value(middle))? {
+                Some(output) => {
+                    inside = middle;
+                    best = output;
+                }
+                None => outside = middle,
+            }
+        }
+        Some(Ok(Some(Solution {
+            value: value(inside),
+            output: best,
+            at_range_end: false,
+        })))
+    }
+}
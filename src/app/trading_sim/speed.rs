@@ -0,0 +1,88 @@
+/// Speed before skills from some minute into the shift on.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug, PartialEq)]
+pub struct SpeedChange {
+    pub from_minutes: i32,
+    /// Speed in percent, taking over from the speed breakdown or `speed100`.
+    pub speed100: f64,
+}
+
+/// How a part of the speed breakdown changes the speed.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpeedEffect {
+    /// Adds percentage points, like the base speed, operator skills and control center buffs.
+    Add,
+    /// Multiplies the sum of the parts that add.
+    Multiply,
+}
+
+/// One named part of the trading post speed.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug, PartialEq)]
+pub struct SpeedContribution {
+    pub name: String,
+    pub effect: SpeedEffect,
+    /// Percentage points added, or factor multiplied by.
+    pub value: f64,
+}
+
+impl SpeedContribution {
+    pub fn add(name: &str, percent: f64) -> Self {
+        Self {
+            name: name.to_string(),
+            effect: SpeedEffect::Add,
+            value: percent,
+        }
+    }
+
+    pub fn multiply(name: &str, factor: f64) -> Self {
+        Self {
+            name: name.to_string(),
+            effect: SpeedEffect::Multiply,
+            value: factor,
+        }
+    }
+}
+
+impl std::fmt::Display for SpeedContribution {
+    /// The value as it applies, like `+30%` or `×1.05`, then the name.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.effect {
+            SpeedEffect::Add => write!(f, "{:+}% {}", self.value, self.name),
+            SpeedEffect::Multiply => write!(f, "×{} {}", self.value, self.name),
+        }
+    }
+}
+
+/// Named parts that make up the trading post speed before skills that depend on the orders
+/// waiting, such as Jaye's.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug, Default, PartialEq)]
+#[serde(transparent)]
+pub struct SpeedBreakdown(pub Vec<SpeedContribution>);
+
+impl SpeedBreakdown {
+    /// Speed in percent: the sum of the parts that add, times every part that multiplies.
+    pub fn speed(&self) -> f64 {
+        let (sum, factor) =
+            self.0
+                .iter()
+                .fold((0.0, 1.0), |(sum, factor), part| match part.effect {
+                    SpeedEffect::Add => (sum + part.value, factor),
+                    SpeedEffect::Multiply => (sum, factor * part.value),
+                });
+        sum * factor
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl std::fmt::Display for SpeedBreakdown {
+    /// Every part in turn, then the speed they make up.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for part in self.0.iter() {
+            write!(f, "{} ", part)?;
+        }
+        // to the thousandth, hiding rounding from multiplying
+        write!(f, "= {}%", (self.speed() * 1000.0).round() / 1000.0)
+    }
+}
@@ -0,0 +1,341 @@
+use super::{
+    gold_shortage, try_simulate, GoldShortage, SimError, SpeedBreakdown,
+    TradingPostProductionInput, TradingPostProductionOutput,
+};
+
+/// Input field a parameter sweep can vary.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SweepField {
+    /// Speed before skills, in whole percent. Setting it drops any speed breakdown.
+    Speed,
+    DurationMinutes,
+    Capacity,
+}
+
+impl SweepField {
+    pub(super) fn set(self, input: &mut TradingPostProductionInput, value: i32) {
+        match self {
+            SweepField::Speed => {
+                input.speed100 = value;
+                input.speed_breakdown = SpeedBreakdown::default();
+            }
+            SweepField::DurationMinutes => input.duration_minutes = value,
+            SweepField::Capacity => input.capacity = value,
+        }
+    }
+}
+
+impl std::fmt::Display for SweepField {
+    /// Names the field as it is named in saved inputs.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            SweepField::Speed => "speed100",
+            SweepField::DurationMinutes => "duration_minutes",
+            SweepField::Capacity => "capacity",
+        })
+    }
+}
+
+impl std::str::FromStr for SweepField {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "speed" | "speed100" => Ok(SweepField::Speed),
+            "duration" | "duration_minutes" => Ok(SweepField::DurationMinutes),
+            "capacity" => Ok(SweepField::Capacity),
+            _ => Err(format!("can't sweep over `{}`", s)),
+        }
+    }
+}
+
+/// Values to try for one input field.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct SweepAxis {
+    pub field: SweepField,
+    pub values: Vec<i32>,
+}
+
+impl SweepAxis {
+    /// Values from `from` up to `to` inclusive, `step` apart.
+    pub fn range(field: SweepField, from: i32, to: i32, step: i32) -> Self {
+        let step = step.max(1) as usize;
+        Self {
+            field,
+            values: (from..=to).step_by(step).collect(),
+        }
+    }
+}
+
+/// Every combination of the values of some input fields, on top of a base input.
+#[derive(serde::Deserialize, serde::Serialize, Clone)]
+pub struct ParameterSweep {
+    pub base: TradingPostProductionInput,
+    pub axes: Vec<SweepAxis>,
+}
+
+/// Simulation of one combination of a sweep.
+#[derive(serde::Deserialize, serde::Serialize, Clone)]
+pub struct SweepRow {
+    /// Value of each axis, in the order of the axes.
+    pub values: Vec<i32>,
+    /// The output, without its timeline.
+    pub output: Result<TradingPostProductionOutput, SimError>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone)]
+pub struct SweepResult {
+    pub fields: Vec<SweepField>,
+    /// One row per combination, the last axis varying fastest.
+    pub rows: Vec<SweepRow>,
+}
+
+impl ParameterSweep {
+    /// Speeds around that of `base` by shift lengths from 2 to 24 hours, to weigh stalling
+    /// against how long the post is left alone.
+    pub fn shift_lengths(base: TradingPostProductionInput) -> Self {
+        let speed = base.base_speed().round() as i32;
+        let speeds = SweepAxis {
+            field: SweepField::Speed,
+            values: (speed - 60..=speed + 60)
+                .step_by(15)
+                .filter(|speed| *speed > 0)
+                .collect(),
+        };
+        Self {
+            base,
+            axes: vec![
+                speeds,
+                SweepAxis::range(SweepField::DurationMinutes, 120, 1440, 120),
+            ],
+        }
+    }
+
+    /// Axis values of every combination, the last axis varying fastest.
+    pub fn combinations(&self) -> Vec<Vec<i32>> {
+        self.axes.iter().fold(vec![vec![]], |combinations, axis| {
+            combinations
+                .iter()
+                .flat_map(|values| {
+                    axis.values.iter().map(move |&value| {
+                        let mut values = values.clone();
+                        values.push(value);
+                        values
+                    })
+                })
+                .collect()
+        })
+    }
+
+    /// Simulates every combination, spread over all cores on native.
+    pub fn run(&self) -> SweepResult {
+        self.run_with_progress(&mut |_| true)
+            .expect("sweep is never cancelled")
+    }
+
+    /// `run` that calls `report` with the fraction done after each combination, and gives up
+    /// with `None` as soon as `report` returns false.
+    pub fn run_with_progress(&self, report: &mut dyn FnMut(f64) -> bool) -> Option<SweepResult> {
+        let combinations = self.combinations();
+        let inputs = combinations
+            .iter()
+            .map(|values| {
+                let mut input = self.base.clone();
+                for (axis, &value) in self.axes.iter().zip(values.iter()) {
+                    axis.field.set(&mut input, value);
+                }
+                input
+            })
+            .collect();
+        let outputs = simulate_all(inputs, report)?;
+        Some(SweepResult {
+            fields: self.axes.iter().map(|axis| axis.field).collect(),
+            rows: combinations
+                .into_iter()
+                .zip(outputs)
+                .map(|(values, output)| SweepRow { values, output })
+                .collect(),
+        })
+    }
+}
+
+/// Simulates `input`, leaving out the timeline, which takes more memory than a sweep is worth.
+/// The gold shortage is filled in when `input` has a gold supply.
+fn simulate(input: &TradingPostProductionInput) -> Result<TradingPostProductionOutput, SimError> {
+    try_simulate(input).map(|output| TradingPostProductionOutput {
+        timeline: Default::default(),
+        gold_shortage: gold_shortage(input),
+        ..output
+    })
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn simulate_all(
+    inputs: Vec<TradingPostProductionInput>,
+    report: &mut dyn FnMut(f64) -> bool,
+) -> Option<Vec<Result<TradingPostProductionOutput, SimError>>> {
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::{mpsc, Arc};
+
+    let count = inputs.len();
+    let threads = std::thread::available_parallelism()
+        .map_or(1, |threads| threads.get())
+        .min(count);
+    let inputs = Arc::new(inputs);
+    let next = Arc::new(AtomicUsize::new(0));
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let (sender, receiver) = mpsc::channel();
+    let workers: Vec<_> = (0..threads)
+        .map(|_| {
+            let inputs = inputs.clone();
+            let next = next.clone();
+            let cancelled = cancelled.clone();
+            let sender = sender.clone();
+            std::thread::spawn(move || {
+                while !cancelled.load(Ordering::Relaxed) {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let input = match inputs.get(index) {
+                        Some(input) => input,
+                        None => break,
+                    };
+                    // the receiver outlives every worker
+                    let _ = sender.send((index, simulate(input)));
+                }
+            })
+        })
+        .collect();
+    drop(sender);
+    let mut outputs: Vec<Option<_>> = (0..count).map(|_| None).collect();
+    let mut done = 0;
+    for (index, output) in receiver.iter() {
+        outputs[index] = Some(output);
+        done += 1;
+        if !report(done as f64 / count as f64) {
+            cancelled.store(true, Ordering::Relaxed);
+            break;
+        }
+    }
+    for worker in workers {
+        worker.join().expect("sweep worker doesn't panic");
+    }
+    outputs.into_iter().collect()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn simulate_all(
+    inputs: Vec<TradingPostProductionInput>,
+    report: &mut dyn FnMut(f64) -> bool,
+) -> Option<Vec<Result<TradingPostProductionOutput, SimError>>> {
+    let count = inputs.len();
+    let mut outputs = Vec::with_capacity(count);
+    for input in inputs.iter() {
+        outputs.push(simulate(input));
+        if !report(outputs.len() as f64 / count as f64) {
+            return None;
+        }
+    }
+    Some(outputs)
+}
+
+type OutputColumn = (&'static str, fn(&TradingPostProductionOutput) -> f64);
+
+/// Every single-valued output field, by CSV column.
+const OUTPUT_COLUMNS: [OutputColumn; 20] = [
+    ("stall_chance", |o| o.stall_chance),
+    ("average_stall_time", |o| o.average_stall_time),
+    ("total_lmd", |o| o.total_lmd),
+    ("total_gold", |o| o.total_gold),
+    ("daily_lmd", |o| o.daily_lmd),
+    ("daily_gold", |o| o.daily_gold),
+    ("net_lmd_speed", |o| o.net_lmd_speed),
+    ("net_gold_speed", |o| o.net_gold_speed),
+    ("average_speed", |o| o.average_speed),
+    ("drones_used", |o| o.drones_used),
+    ("lmd_mean", |o| o.lmd_distribution.mean),
+    ("lmd_variance", |o| o.lmd_distribution.variance),
+    ("lmd_p5", |o| o.lmd_distribution.p5),
+    ("lmd_p50", |o| o.lmd_distribution.p50),
+    ("lmd_p95", |o| o.lmd_distribution.p95),
+    ("gold_mean", |o| o.gold_distribution.mean),
+    ("gold_variance", |o| o.gold_distribution.variance),
+    ("gold_p5", |o| o.gold_distribution.p5),
+    ("gold_p50", |o| o.gold_distribution.p50),
+    ("gold_p95", |o| o.gold_distribution.p95),
+];
+
+type ShortageColumn = (&'static str, fn(&GoldShortage) -> f64);
+
+/// Gold shortage fields, by CSV column. They are left empty for inputs without a gold supply.
+const SHORTAGE_COLUMNS: [ShortageColumn; 3] = [
+    ("wait_chance", |s| s.wait_chance),
+    ("delayed_lmd", |s| s.delayed_lmd),
+    ("lost_lmd", |s| s.lost_lmd),
+];
+
+impl SweepResult {
+    /// A header, then one line per row: the axis values, every output field, the gold shortage,
+    /// and the end and stall chance of each collection interval. Rows that can't be simulated
+    /// leave the output empty and give the reason in the last column.
+    pub fn to_csv(&self) -> String {
+        let intervals = self
+            .rows
+            .iter()
+            .filter_map(|row| row.output.as_ref().ok())
+            .map(|output| output.interval_end_minutes.len())
+            .max()
+            .unwrap_or(0);
+
+        let mut header: Vec<String> = self.fields.iter().map(ToString::to_string).collect();
+        header.extend(OUTPUT_COLUMNS.iter().map(|(name, _)| name.to_string()));
+        header.extend(SHORTAGE_COLUMNS.iter().map(|(name, _)| name.to_string()));
+        for interval in 1..=intervals {
+            header.push(format!("interval_{}_end_minutes", interval));
+            header.push(format!("interval_{}_stall_chance", interval));
+        }
+        header.push("error".to_string());
+
+        let mut csv = header.join(",") + "\n";
+        for row in self.rows.iter() {
+            let mut cells: Vec<String> = row.values.iter().map(ToString::to_string).collect();
+            match &row.output {
+                Ok(output) => {
+                    cells.extend(
+                        OUTPUT_COLUMNS
+                            .iter()
+                            .map(|(_, field)| field(output).to_string()),
+                    );
+                    cells.extend(SHORTAGE_COLUMNS.iter().map(|(_, field)| {
+                        output
+                            .gold_shortage
+                            .as_ref()
+                            .map(|shortage| field(shortage).to_string())
+                            .unwrap_or_default()
+                    }));
+                    for interval in 0..intervals {
+                        let cell = |value: Option<String>| value.unwrap_or_default();
+                        cells.push(cell(
+                            output
+                                .interval_end_minutes
+                                .get(interval)
+                                .map(ToString::to_string),
+                        ));
+                        cells.push(cell(
+                            output
+                                .interval_stall_chance
+                                .get(interval)
+                                .map(ToString::to_string),
+                        ));
+                    }
+                    cells.push(String::new());
+                }
+                Err(error) => {
+                    let columns = OUTPUT_COLUMNS.len() + SHORTAGE_COLUMNS.len() + 2 * intervals;
+                    cells.extend((0..columns).map(|_| String::new()));
+                    cells.push(format!("\"{}\"", error.to_string().replace('"', "\"\"")));
+                }
+            }
+            csv += &(cells.join(",") + "\n");
+        }
+        csv
+    }
+}
@@ -0,0 +1,160 @@
+use super::Expectation;
+
+/// Expected course of a shift, sampled every minute from its start to its end.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Default)]
+#[serde(default)]
+pub struct ShiftTimeline {
+    /// Chance of stalling before each minute, in percent: the stall chance of the shift had it
+    /// ended then.
+    pub stall_chance: Vec<f64>,
+    /// Expected LMD of the orders finished by each minute, with the orders stored at the start.
+    /// Orders carried in from the previous shift count in full from the start.
+    pub lmd: Vec<f64>,
+    /// Expected gold of the orders finished by each minute, like `lmd`.
+    pub gold: Vec<f64>,
+    /// Chance of each stored order count at each minute, in percent, by minute then count.
+    pub stored: Vec<Vec<f64>>,
+    /// Expected speed at each minute, in percent, with a full post standing still.
+    pub speed: Vec<f64>,
+}
+
+impl ShiftTimeline {
+    /// Appends `other`, which starts where this one ends, dropping its first minute.
+    pub(super) fn append(&mut self, other: &ShiftTimeline) {
+        let skip_first = usize::from(!self.lmd.is_empty());
+        self.stall_chance
+            .extend(other.stall_chance.iter().skip(skip_first));
+        self.lmd.extend(other.lmd.iter().skip(skip_first));
+        self.gold.extend(other.gold.iter().skip(skip_first));
+        self.stored
+            .extend(other.stored.iter().skip(skip_first).cloned());
+        self.speed.extend(other.speed.iter().skip(skip_first));
+    }
+
+    /// Expected speed averaged over every minute, in percent.
+    pub fn average_speed(&self) -> f64 {
+        // the last sample is the end of the shift, not a minute of it
+        let minutes = self.speed.len().saturating_sub(1);
+        if minutes == 0 {
+            return 0.0;
+        }
+        self.speed[..minutes].iter().sum::<f64>() / minutes as f64
+    }
+}
+
+/// Collects the course of one shift during the exact DP pass. Every state is recorded over the
+/// time its order is in progress, as differences between per-minute samples.
+pub(super) struct TimelineRecorder {
+    samples: usize,
+    /// Changes in weight by sample, then stored order count.
+    stored: Vec<Vec<f64>>,
+    lmd: Vec<f64>,
+    gold: Vec<f64>,
+    /// Weight of the paths stalling for the first time in the shift, by the first sample after.
+    new_stalls: Vec<f64>,
+    /// The same for the first time in the day.
+    new_day_stalls: Vec<f64>,
+}
+
+impl TimelineRecorder {
+    pub(super) fn new(duration_minutes: usize, order_limit: usize) -> Self {
+        let samples = duration_minutes + 1;
+        // the extra sample takes the changes past the end of the shift
+        Self {
+            samples,
+            stored: vec![vec![0.0; order_limit + 1]; samples + 1],
+            lmd: vec![0.0; samples + 1],
+            gold: vec![0.0; samples + 1],
+            new_stalls: vec![0.0; samples + 1],
+            new_day_stalls: vec![0.0; samples + 1],
+        }
+    }
+
+    /// Records `factor` of `payload` as waiting with `stored` orders from `from` seconds into the
+    /// shift until `to`, or until the end of the shift when `to` is `None`. The post is empty
+    /// from `collection` on, if that comes first.
+    pub(super) fn occupy(
+        &mut self,
+        from: usize,
+        to: Option<usize>,
+        stored: usize,
+        collection: Option<usize>,
+        payload: &Expectation,
+        factor: f64,
+    ) {
+        let weighted = [payload.weight, payload.lmd, payload.gold].map(|sum| sum * factor);
+        match collection {
+            Some(collection) if to.map_or(true, |to| collection < to) => {
+                self.add_span(from, Some(collection), stored, weighted);
+                self.add_span(collection, to, 0, weighted);
+            }
+            _ => self.add_span(from, to, stored, weighted),
+        }
+    }
+
+    /// Records the paths of `payload` that stall `time` seconds into the shift.
+    pub(super) fn stall(&mut self, time: usize, payload: &Expectation) {
+        // a stall at `time` counts toward every shift that runs past it
+        let sample = (time / 60 + 1).min(self.samples);
+        self.new_stalls[sample] += payload.weight - payload.stalled_weight;
+        self.new_day_stalls[sample] += payload.weight - payload.day_stalled_weight;
+    }
+
+    fn add_span(&mut self, from: usize, to: Option<usize>, stored: usize, weighted: [f64; 3]) {
+        let first = (from + 59) / 60;
+        let end = to.map_or(self.samples, |to| ((to + 59) / 60).min(self.samples));
+        if first >= end {
+            return;
+        }
+        let [weight, lmd, gold] = weighted;
+        self.stored[first][stored] += weight;
+        self.stored[end][stored] -= weight;
+        self.lmd[first] += lmd;
+        self.lmd[end] -= lmd;
+        self.gold[first] += gold;
+        self.gold[end] -= gold;
+    }
+
+    /// The timeline, with LMD and gold counted from the start of the day, together with the
+    /// chance of stalling at some point in the day before each minute. `previous` is the total of
+    /// the shifts before.
+    pub(super) fn finish(self, previous: &Expectation) -> (ShiftTimeline, Vec<f64>) {
+        let running_sum = |changes: &[f64], start: f64| {
+            changes[..self.samples]
+                .iter()
+                .scan(start, |sum, change| {
+                    *sum += change;
+                    Some(*sum)
+                })
+                .collect::<Vec<f64>>()
+        };
+        let mut stored_weight = vec![0.0; self.stored[0].len()];
+        let stored = self.stored[..self.samples]
+            .iter()
+            .map(|changes| {
+                for (weight, change) in stored_weight.iter_mut().zip(changes) {
+                    *weight += change;
+                }
+                // rounding leaves counts that can't happen a hair below zero
+                stored_weight
+                    .iter()
+                    .map(|weight| weight.max(0.0) * 100.0)
+                    .collect()
+            })
+            .collect();
+        let percent = |chances: Vec<f64>| chances.iter().map(|chance| chance * 100.0).collect();
+        let timeline = ShiftTimeline {
+            stall_chance: percent(running_sum(&self.new_stalls, 0.0)),
+            lmd: running_sum(&self.lmd, 0.0),
+            gold: running_sum(&self.gold, 0.0),
+            stored,
+            // left to the model, which knows the speeds
+            speed: vec![],
+        };
+        let day_stall_chance = percent(running_sum(
+            &self.new_day_stalls,
+            previous.day_stalled_weight,
+        ));
+        (timeline, day_stall_chance)
+    }
+}
@@ -0,0 +1,566 @@
+//! Headless simulator, for scripting comparisons without the GUI.
+
+use std::io::{ErrorKind, Write};
+use std::process::ExitCode;
+
+use ak_riic_tools::trading_sim::*;
+
+const USAGE: &str = "\
+Usage: riic-sim tp [OPTIONS]
+       riic-sim sweep --vary FIELD=VALUES... [OPTIONS]
+       riic-sim solve --for FIELD (--max-stall-chance PERCENT | --max-stall-time MINUTES)
+                      [OPTIONS]
+
+`tp` simulates one trading post shift and prints the output. `sweep` simulates every
+combination of the values given to `--vary`, and prints one CSV row per combination. `solve`
+finds the highest speed, the longest duration or the smallest order limit that keeps the shift
+within a stall limit, and prints it with the output there.
+
+Options:
+  --input FILE          read the input from a TOML or JSON file; other options override it
+  --game-data FILE      read game data from a JSON file instead of using the bundled data
+  --level LEVEL         trading post level, 1 to 3; also sets the order limit to 6, 8 or 10
+                        for the level unless `--capacity` is given
+  --capacity N          order limit
+  --speed PERCENT       production speed; replaces any speed breakdown
+  --add-speed NAME=PERCENT
+                        adds a part to the speed breakdown, which then gives the speed
+  --multiply-speed NAME=FACTOR
+                        adds a part to the speed breakdown that multiplies the parts that add
+  --speed-change MINUTE=PERCENT
+                        changes the production speed this many minutes into the shift
+  --duration MINUTES    shift length
+  --tailoring SKILL[:MINUTES]
+                        adds a tailoring skill (alpha or beta), ramped up for MINUTES when the
+                        shift starts
+  --tequila PHASE       Tequila's elite phase: none, e0, e1 or e2
+  --proviso PHASE       Proviso's elite phase
+  --jaye PHASE          Jaye's elite phase
+  --jaye-count WHEN     whether Jaye counts orders before or after the order finishes
+  --stored N,N,N        2-bar, 3-bar and 4-bar orders stored when the shift starts
+  --minutes-left N      minutes left on the order in progress when the shift starts
+  --collect MINUTES     adds a collection this many minutes into the shift
+  --drones MINUTE=COUNT spends drones this many minutes into the shift
+  --morale NAME=MORALE[:DRAIN]
+                        sets the morale of an operator when the shift starts, and how much it
+                        drains an hour (1 by default); speed parts named after the operator,
+                        and the skill of Jaye, Tequila, Proviso or a tailoring operator (named
+                        alpha or beta), stop when it runs out
+  --gold STOCK[:PER_HOUR]
+                        gold bars on hand when the shift starts, and coming in every hour (0 by
+                        default); orders only deliver while there is gold for them
+  --format FORMAT       table (default), json or csv; sweeps only print CSV
+  --vary FIELD=VALUES   values to sweep speed, duration or capacity over, either listed as
+                        8,10,12 or given as FROM:TO[:STEP]; repeat to sweep several fields
+  --for FIELD           field to solve for: speed, duration or capacity
+  --max-stall-chance PERCENT
+                        stall chance to stay within when solving
+  --max-stall-time MINUTES
+                        average stall time to stay within when solving
+  -h, --help            print this help
+
+Exits with 1 when the input can't be simulated or no value meets the stall limit, and 2 on bad
+usage. Sweeps give the reason a combination can't be simulated in the error column instead.
+`tp` warns when an operator runs out of morale before the shift ends.
+";
+
+/// Options that set a field of the input, applied in the order given.
+const INPUT_FLAGS: [&str; 18] = [
+    "--level",
+    "--capacity",
+    "--speed",
+    "--add-speed",
+    "--multiply-speed",
+    "--speed-change",
+    "--duration",
+    "--tailoring",
+    "--tequila",
+    "--proviso",
+    "--jaye",
+    "--jaye-count",
+    "--stored",
+    "--minutes-left",
+    "--collect",
+    "--drones",
+    "--morale",
+    "--gold",
+];
+
+enum Failure {
+    /// The command line doesn't make sense.
+    Usage(String),
+    /// The input can't be read or simulated.
+    Input(String),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Command {
+    Tp,
+    Sweep,
+    Solve,
+}
+
+#[derive(Clone, Copy)]
+enum Format {
+    Table,
+    Json,
+    Csv,
+}
+
+impl std::str::FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "table" => Ok(Format::Table),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            _ => Err(format!("unknown output format `{}`", s)),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Options {
+    help: bool,
+    input_file: Option<String>,
+    game_data_file: Option<String>,
+    format: Option<Format>,
+    /// `(flag, value)` of every option in `INPUT_FLAGS`.
+    fields: Vec<(String, String)>,
+    axes: Vec<SweepAxis>,
+    solve_for: Option<SweepField>,
+    limit: Option<StallLimit>,
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match run(&args) {
+        Ok(text) => match write_stdout(&text) {
+            Ok(()) => ExitCode::SUCCESS,
+            // whoever reads the output has seen enough, as `head` does
+            Err(error) if error.kind() == ErrorKind::BrokenPipe => ExitCode::SUCCESS,
+            Err(error) => {
+                eprintln!("error: {}", error);
+                ExitCode::from(1)
+            }
+        },
+        Err(Failure::Usage(message)) => {
+            eprintln!("error: {}\n\n{}", message, USAGE);
+            ExitCode::from(2)
+        }
+        Err(Failure::Input(message)) => {
+            eprintln!("error: {}", message);
+            ExitCode::from(1)
+        }
+    }
+}
+
+/// Writes `text` to stdout, reporting the errors `print!` would panic on.
+fn write_stdout(text: &str) -> std::io::Result<()> {
+    let mut stdout = std::io::stdout().lock();
+    stdout.write_all(text.as_bytes())?;
+    stdout.flush()
+}
+
+fn run(args: &[String]) -> Result<String, Failure> {
+    let command = match args.first().map(String::as_str) {
+        Some("tp") => Command::Tp,
+        Some("sweep") => Command::Sweep,
+        Some("solve") => Command::Solve,
+        Some("-h" | "--help") => return Ok(USAGE.to_string()),
+        Some(command) => return Err(Failure::Usage(format!("unknown command `{}`", command))),
+        None => return Err(Failure::Usage("missing command".to_string())),
+    };
+    let mut options = parse_options(&args[1..]).map_err(Failure::Usage)?;
+    if options.help {
+        return Ok(USAGE.to_string());
+    }
+    let sweep = command == Command::Sweep;
+    if sweep && options.axes.is_empty() {
+        return Err(Failure::Usage(
+            "`sweep` takes at least one `--vary`".to_string(),
+        ));
+    }
+    if !sweep && !options.axes.is_empty() {
+        return Err(Failure::Usage("only `sweep` takes `--vary`".to_string()));
+    }
+    if sweep && !matches!(options.format, None | Some(Format::Csv)) {
+        return Err(Failure::Usage("sweeps only print CSV".to_string()));
+    }
+    let solve = match (command, options.solve_for, options.limit) {
+        (Command::Solve, Some(field), Some(limit)) => Some((field, limit)),
+        (Command::Solve, _, _) => {
+            return Err(Failure::Usage(
+                "`solve` takes `--for` and a stall limit".to_string(),
+            ))
+        }
+        (_, None, None) => None,
+        _ => {
+            return Err(Failure::Usage(
+                "only `solve` takes `--for` and stall limits".to_string(),
+            ))
+        }
+    };
+    if solve.is_some() && matches!(options.format, Some(Format::Csv)) {
+        return Err(Failure::Usage("solutions don't print as CSV".to_string()));
+    }
+
+    let mut input = match &options.input_file {
+        Some(path) => read_input(path)?,
+        None => TradingPostProductionInput::default(),
+    };
+    if let Some(path) = &options.game_data_file {
+        let json = read_file(path)?;
+        input.game_data = TradingPostGameData::from_json(&json)
+            .map_err(|error| Failure::Input(format!("{}: {}", path, error)))?;
+    }
+    // an explicit order limit wins over the one of the level, wherever it is given
+    options.fields.sort_by_key(|(flag, _)| flag == "--capacity");
+    for (flag, value) in options.fields.iter() {
+        set_field(&mut input, flag, value).map_err(Failure::Usage)?;
+    }
+
+    if sweep {
+        let sweep = ParameterSweep {
+            base: input,
+            axes: options.axes,
+        };
+        return Ok(sweep.run().to_csv());
+    }
+    if let Some((field, limit)) = solve {
+        let solver = Solver {
+            base: input,
+            field,
+            limit,
+        };
+        let solution = solver
+            .solve()
+            .map_err(|error| Failure::Input(error.to_string()))?
+            .ok_or_else(|| Failure::Input(format!("no {} keeps the {}", field, limit)))?;
+        return Ok(match options.format.unwrap_or(Format::Table) {
+            Format::Json => {
+                serde_json::to_string_pretty(&solution).expect("solution serializes to JSON") + "\n"
+            }
+            _ => format_solution(field, &solution),
+        });
+    }
+    if let Some(shortfall) = input.morale_shortfall() {
+        eprintln!("warning: {}", shortfall);
+    }
+    let mut output = try_simulate(&input).map_err(|error| Failure::Input(error.to_string()))?;
+    output.gold_shortage = gold_shortage(&input);
+    Ok(match options.format.unwrap_or(Format::Table) {
+        Format::Table => format_table(&output),
+        Format::Json => {
+            serde_json::to_string_pretty(&output).expect("output serializes to JSON") + "\n"
+        }
+        Format::Csv => format_csv(&output),
+    })
+}
+
+fn parse_options(args: &[String]) -> Result<Options, String> {
+    let mut options = Options::default();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "-h" || arg == "--help" {
+            options.help = true;
+            continue;
+        }
+        let (flag, inline_value) = match arg.split_once('=') {
+            Some((flag, value)) => (flag, Some(value.to_string())),
+            None => (arg.as_str(), None),
+        };
+        let known = matches!(
+            flag,
+            "--input"
+                | "--game-data"
+                | "--format"
+                | "--vary"
+                | "--for"
+                | "--max-stall-chance"
+                | "--max-stall-time"
+        ) || INPUT_FLAGS.contains(&flag);
+        if !known {
+            return Err(format!("unknown option `{}`", arg));
+        }
+        let value = match inline_value.or_else(|| args.next().cloned()) {
+            Some(value) => value,
+            None => return Err(format!("`{}` takes a value", flag)),
+        };
+        match flag {
+            "--input" => options.input_file = Some(value),
+            "--game-data" => options.game_data_file = Some(value),
+            "--format" => options.format = Some(value.parse()?),
+            "--vary" => options.axes.push(parse_axis(&value)?),
+            "--for" => options.solve_for = Some(value.parse()?),
+            "--max-stall-chance" => {
+                options.limit = Some(StallLimit::Chance(parse_amount(flag, &value)?))
+            }
+            "--max-stall-time" => {
+                options.limit = Some(StallLimit::AverageTime(parse_amount(flag, &value)?))
+            }
+            _ => options.fields.push((flag.to_string(), value)),
+        }
+    }
+    Ok(options)
+}
+
+fn read_file(path: &str) -> Result<String, Failure> {
+    std::fs::read_to_string(path)
+        .map_err(|error| Failure::Input(format!("can't read {}: {}", path, error)))
+}
+
+/// Reads an input from TOML if the file name ends in `.toml`, and from JSON otherwise.
+fn read_input(path: &str) -> Result<TradingPostProductionInput, Failure> {
+    let text = read_file(path)?;
+    let parsed = if path.ends_with(".toml") {
+        toml::from_str(&text).map_err(|error| error.to_string())
+    } else {
+        serde_json::from_str(&text).map_err(|error| error.to_string())
+    };
+    parsed.map_err(|error| Failure::Input(format!("{}: {}", path, error)))
+}
+
+fn parse_number(flag: &str, value: &str) -> Result<i32, String> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| format!("`{}` takes a whole number, not `{}`", flag, value))
+}
+
+fn parse_amount(flag: &str, value: &str) -> Result<f64, String> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| format!("`{}` takes a number, not `{}`", flag, value))
+}
+
+/// Parses `FIELD=FROM:TO[:STEP]` or `FIELD=VALUE,VALUE,...`.
+fn parse_axis(value: &str) -> Result<SweepAxis, String> {
+    let (field, values) = value
+        .split_once('=')
+        .ok_or_else(|| format!("`--vary` takes FIELD=VALUES, not `{}`", value))?;
+    let field = field.parse()?;
+    if values.contains(':') {
+        let bounds = values
+            .split(':')
+            .map(|bound| parse_number("--vary", bound))
+            .collect::<Result<Vec<i32>, String>>()?;
+        match bounds[..] {
+            [from, to] => Ok(SweepAxis::range(field, from, to, 1)),
+            [from, to, step] if step > 0 => Ok(SweepAxis::range(field, from, to, step)),
+            _ => Err(format!("`--vary` takes FROM:TO[:STEP], not `{}`", values)),
+        }
+    } else {
+        let values = values
+            .split(',')
+            .map(|value| parse_number("--vary", value))
+            .collect::<Result<Vec<i32>, String>>()?;
+        Ok(SweepAxis { field, values })
+    }
+}
+
+fn set_field(
+    input: &mut TradingPostProductionInput,
+    flag: &str,
+    value: &str,
+) -> Result<(), String> {
+    match flag {
+        "--level" => {
+            input.phase = value.parse()?;
+            input.capacity = match input.phase {
+                TradingPostPhase::L1 => 6,
+                TradingPostPhase::L2 => 8,
+                TradingPostPhase::L3 => 10,
+            };
+        }
+        "--capacity" => input.capacity = parse_number(flag, value)?,
+        "--speed" => {
+            input.speed100 = parse_number(flag, value)?;
+            input.speed_breakdown = SpeedBreakdown::default();
+        }
+        "--add-speed" | "--multiply-speed" => {
+            let (name, amount) = value
+                .split_once('=')
+                .ok_or_else(|| format!("`{}` takes NAME=VALUE, not `{}`", flag, value))?;
+            let amount = parse_amount(flag, amount)?;
+            input.speed_breakdown.0.push(if flag == "--add-speed" {
+                SpeedContribution::add(name, amount)
+            } else {
+                SpeedContribution::multiply(name, amount)
+            });
+        }
+        "--speed-change" => {
+            let (minute, speed) = value
+                .split_once('=')
+                .ok_or_else(|| format!("`{}` takes MINUTE=PERCENT, not `{}`", flag, value))?;
+            input.speed_schedule.push(SpeedChange {
+                from_minutes: parse_number(flag, minute)?,
+                speed100: parse_amount(flag, speed)?,
+            });
+        }
+        "--duration" => input.duration_minutes = parse_number(flag, value)?,
+        "--tailoring" => {
+            let (skill, minutes) = value.split_once(':').unwrap_or((value, "0"));
+            input
+                .tailoring_ramped
+                .push((skill.parse()?, parse_number(flag, minutes)?));
+        }
+        "--tequila" => input.tequila_phase = value.parse()?,
+        "--proviso" => input.proviso_phase = value.parse()?,
+        "--jaye" => input.jaye_phase = value.parse()?,
+        "--jaye-count" => input.jaye_order_count = value.parse()?,
+        "--stored" => {
+            let counts = value
+                .split(',')
+                .map(|count| parse_number(flag, count))
+                .collect::<Result<Vec<i32>, String>>()?;
+            input.start_state.stored_orders = counts
+                .try_into()
+                .map_err(|_| format!("`{}` takes three counts, not `{}`", flag, value))?;
+        }
+        "--minutes-left" => input.start_state.minutes_left = Some(parse_number(flag, value)?),
+        "--collect" => input.collection_minutes.push(parse_number(flag, value)?),
+        "--drones" => {
+            let (minute, drones) = value
+                .split_once('=')
+                .ok_or_else(|| format!("`{}` takes MINUTE=COUNT, not `{}`", flag, value))?;
+            input.drone_usages.push(DroneUsage {
+                minute: parse_number(flag, minute)?,
+                drones: parse_number(flag, drones)?,
+            });
+        }
+        "--morale" => {
+            let (name, morale) = value
+                .split_once('=')
+                .ok_or_else(|| format!("`{}` takes NAME=MORALE[:DRAIN], not `{}`", flag, value))?;
+            let (morale, drain) = morale.split_once(':').unwrap_or((morale, "1"));
+            input.morale.push(OperatorMorale {
+                name: name.to_string(),
+                morale: parse_amount(flag, morale)?,
+                drain_per_hour: parse_amount(flag, drain)?,
+            });
+        }
+        "--gold" => {
+            let (stock, per_hour) = value.split_once(':').unwrap_or((value, "0"));
+            input.gold_supply = Some(GoldSupply {
+                stock: parse_number(flag, stock)?,
+                per_hour: parse_amount(flag, per_hour)?,
+            });
+        }
+        _ => unreachable!("`{}` is not in INPUT_FLAGS", flag),
+    }
+    Ok(())
+}
+
+/// `(label, CSV column, value, unit)` of every output field shown in a table or CSV.
+fn output_fields(output: &TradingPostProductionOutput) -> Vec<(String, String, f64, &'static str)> {
+    let field = |label: &str, column: &str, value, unit| {
+        (label.to_string(), column.to_string(), value, unit)
+    };
+    let mut fields = vec![field(
+        "Stall chance",
+        "stall_chance",
+        output.stall_chance,
+        "%",
+    )];
+    if output.interval_end_minutes.len() > 1 {
+        for (end, chance) in output
+            .interval_end_minutes
+            .iter()
+            .zip(output.interval_stall_chance.iter())
+        {
+            fields.push((
+                format!("  until {:0>2}h{:0>2}m", end / 60, end % 60),
+                format!("stall_chance_until_{}", end),
+                *chance,
+                "%",
+            ));
+        }
+    }
+    fields.extend([
+        field(
+            "Average stall time",
+            "average_stall_time",
+            output.average_stall_time,
+            "minutes",
+        ),
+        field("Total LMD", "total_lmd", output.total_lmd, ""),
+        field("Total Gold", "total_gold", output.total_gold, ""),
+        field("Daily LMD", "daily_lmd", output.daily_lmd, ""),
+        field("Daily Gold", "daily_gold", output.daily_gold, ""),
+        field("Net LMD Speed", "net_lmd_speed", output.net_lmd_speed, "%"),
+        field(
+            "Net Gold Speed",
+            "net_gold_speed",
+            output.net_gold_speed,
+            "%",
+        ),
+        field("Average Speed", "average_speed", output.average_speed, "%"),
+        field("Drones Used", "drones_used", output.drones_used, ""),
+    ]);
+    if let Some(shortage) = &output.gold_shortage {
+        fields.push(field(
+            "Gold wait chance",
+            "gold_wait_chance",
+            shortage.wait_chance,
+            "%",
+        ));
+        if output.interval_end_minutes.len() > 1 {
+            for (end, chance) in output
+                .interval_end_minutes
+                .iter()
+                .zip(shortage.interval_wait_chance.iter())
+            {
+                fields.push((
+                    format!("  at {:0>2}h{:0>2}m", end / 60, end % 60),
+                    format!("gold_wait_chance_at_{}", end),
+                    *chance,
+                    "%",
+                ));
+            }
+        }
+        fields.extend([
+            field("Delayed LMD", "delayed_lmd", shortage.delayed_lmd, ""),
+            field("Lost LMD", "lost_lmd", shortage.lost_lmd, ""),
+            field("Gold at end", "gold_at_end", shortage.stock_at_end, ""),
+        ]);
+    }
+    fields
+}
+
+fn format_table(output: &TradingPostProductionOutput) -> String {
+    output_fields(output)
+        .iter()
+        .map(|(label, _, value, unit)| format!("{:<20}{:>14.4} {}\n", label, value, unit))
+        .map(|line| line.trim_end().to_string() + "\n")
+        .collect()
+}
+
+fn format_csv(output: &TradingPostProductionOutput) -> String {
+    let fields = output_fields(output);
+    let columns: Vec<&str> = fields
+        .iter()
+        .map(|(_, column, _, _)| column.as_str())
+        .collect();
+    let values: Vec<String> = fields
+        .iter()
+        .map(|(_, _, value, _)| value.to_string())
+        .collect();
+    format!("{}\n{}\n", columns.join(","), values.join(","))
+}
+
+/// The value solved for, then the output there as a table.
+fn format_solution(field: SweepField, solution: &Solution) -> String {
+    let mut text = format!("{:<20}{:>14}", field.to_string(), solution.value);
+    if solution.at_range_end {
+        text += " (end of the range searched)";
+    }
+    text += "\n\n";
+    match &solution.output {
+        Ok(output) => text += &format_table(output),
+        Err(error) => text += &format!("{}\n", error),
+    }
+    text
+}
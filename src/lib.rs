@@ -1,4 +1,11 @@
 #![warn(clippy::all, rust_2018_idioms)]
 
 mod app;
+pub use app::base_sim;
+pub use app::factory_sim;
+pub use app::roster;
+pub use app::trading_sim;
 pub use app::TradingPostProductionApp;
+
+#[cfg(target_arch = "wasm32")]
+pub use app::run_simulation_worker;
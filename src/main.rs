@@ -18,6 +18,11 @@ fn main() {
 // when compiling to web using trunk.
 #[cfg(target_arch = "wasm32")]
 fn main() {
+    // The web worker loads this module too, but only runs simulations.
+    if web_sys::window().is_none() {
+        return;
+    }
+
     // Make sure panics are logged using `console.error`.
     console_error_panic_hook::set_once();
 
@@ -0,0 +1,100 @@
+use ak_riic_tools::base_sim::*;
+use ak_riic_tools::factory_sim::*;
+use ak_riic_tools::trading_sim::*;
+
+fn standard(code: &str) -> Result<BaseLayout, BaseError> {
+    let trading_post = TradingPostProductionInput {
+        capacity: 10,
+        collection_minutes: vec![360],
+        ..Default::default()
+    };
+    BaseLayout::standard(
+        code,
+        &trading_post,
+        &FactoryInput::default(),
+        &PowerPlant::default(),
+    )
+}
+
+#[test]
+fn standard_layouts_count_rooms() {
+    let layout = standard(" 243 ").unwrap();
+    assert_eq!(layout.name, "243");
+    assert_eq!(layout.trading_posts.len(), 2);
+    assert_eq!(layout.power_plants.len(), 3);
+    let products: Vec<FactoryProduct> = layout.factories.iter().map(|f| f.product).collect();
+    use FactoryProduct::*;
+    assert_eq!(products, vec![Gold, Gold, BattleRecord, BattleRecord]);
+    // factories are collected along with the trading posts
+    assert_eq!(layout.factories[0].collection_minutes, vec![360]);
+
+    for code in ["24", "2434", "2a3", "null", "match", "064", "453"] {
+        let error = Some(BaseError::UnknownLayout(code.to_string()));
+        assert_eq!(standard(code).err(), error);
+    }
+}
+
+#[test]
+fn adds_up_the_buildings() {
+    let layout = standard("153").unwrap();
+    let base = simulate_base(&layout).unwrap();
+    let trading_post = simulate_tp_production(&layout.trading_posts[0]);
+    assert!((base.daily_lmd - trading_post.daily_lmd).abs() < 1e-9);
+    // a factory makes 4 battle records a shift, one every 3 hours
+    assert_eq!(base.daily_exp, 4.0 * 8000.0);
+    assert_eq!(base.daily_shards, 0.0);
+    assert_eq!(base.gold.produced, 20.0);
+    assert!((base.gold.consumed + trading_post.daily_gold).abs() < 1e-9);
+    assert_eq!(base.daily_drones, 240.0);
+    assert_eq!(base.power_supplied, 810);
+    assert_eq!(base.power_used, 360);
+    assert!(base.has_power());
+
+    let charged = BaseLayout {
+        power_plants: vec![
+            PowerPlant {
+                level: PowerPlantLevel::L1,
+                drone_speed100: 50.0,
+            };
+            2
+        ],
+        ..layout
+    };
+    let base = simulate_base(&charged).unwrap();
+    assert_eq!(base.daily_drones, 480.0);
+    assert!(!base.has_power());
+}
+
+#[test]
+fn reports_the_building_that_fails() {
+    let mut layout = standard("252").unwrap();
+    layout.trading_posts[1].duration_minutes = 0;
+    let error = simulate_base(&layout).err();
+    let duration = SimError::NonPositiveDuration(0);
+    assert_eq!(error, Some(BaseError::TradingPost(1, duration)));
+
+    let mut layout = standard("252").unwrap();
+    layout.factories[3].speed100 = 0.0;
+    let error = simulate_base(&layout).err();
+    let speed = FactoryError::NonPositiveSpeed(0.0);
+    assert_eq!(error, Some(BaseError::Factory(3, speed)));
+}
+
+#[test]
+fn buildings_are_set_one_by_one() {
+    let mut layout = standard("153").unwrap();
+    layout.factories[1].level = FactoryLevel::L2;
+    layout.factories[2].product = FactoryProduct::OriginiumShard;
+    layout.factories[3].product = FactoryProduct::Gold;
+    let base = simulate_base(&layout).unwrap();
+    // a Lv2 factory makes 8 frontline records a shift, and a Lv3 one 4 tactical records
+    assert_eq!(base.daily_exp, 16.0 * 400.0 + 8.0 * 1000.0);
+    // a shard an hour
+    assert_eq!(base.daily_shards, 24.0);
+    assert_eq!(base.gold.produced, 40.0);
+    assert_eq!(base.power_used, 60 + 4 * 60 + 30);
+
+    layout.power_plants.push(PowerPlant::default());
+    let error = simulate_base(&layout).err();
+    assert_eq!(error, Some(BaseError::TooManyRooms(10)));
+}
@@ -0,0 +1,245 @@
+use std::process::{Command, Output, Stdio};
+
+use ak_riic_tools::trading_sim::*;
+
+fn riic_sim(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_riic-sim"))
+        .args(args)
+        .output()
+        .expect("riic-sim runs")
+}
+
+fn json_output(args: &[&str]) -> TradingPostProductionOutput {
+    let output = riic_sim(args);
+    assert!(output.status.success(), "{:?}", output);
+    serde_json::from_slice(&output.stdout).expect("stdout is an output in JSON")
+}
+
+#[test]
+fn flags_set_the_input() {
+    let printed = json_output(&[
+        "tp",
+        "--format",
+        "json",
+        "--level=2",
+        "--speed",
+        "180",
+        "--duration",
+        "600",
+        "--tailoring",
+        "beta:30",
+        "--jaye",
+        "e0",
+        "--stored",
+        "1,0,2",
+        "--collect",
+        "300",
+    ]);
+    let expected = simulate_tp_production(&TradingPostProductionInput {
+        phase: TradingPostPhase::L2,
+        capacity: 8,
+        speed100: 180,
+        duration_minutes: 600,
+        tailoring_ramped: vec![(TradingPostTailoringSkill::Beta, 30)],
+        jaye_phase: HighRarityOperatorPhase::E0,
+        start_state: TradingPostStartState {
+            stored_orders: [1, 0, 2],
+            minutes_left: None,
+        },
+        collection_minutes: vec![300],
+        ..Default::default()
+    });
+    assert_eq!(printed.total_lmd, expected.total_lmd);
+    assert_eq!(printed.stall_chance, expected.stall_chance);
+    assert_eq!(
+        printed.interval_stall_chance,
+        expected.interval_stall_chance
+    );
+}
+
+#[test]
+fn reads_the_input_from_a_toml_file() {
+    let path = std::env::temp_dir().join(format!("riic-sim-{}.toml", std::process::id()));
+    std::fs::write(
+        &path,
+        "duration_minutes = 480\n\
+         phase = \"L2\"\n\
+         capacity = 8\n\
+         tailoring_ramped = [[\"Alpha\", 60]]\n\
+         proviso_phase = \"E2\"\n",
+    )
+    .unwrap();
+    // flags override the file
+    let printed = json_output(&[
+        "tp",
+        "--input",
+        path.to_str().unwrap(),
+        "--format",
+        "json",
+        "--speed",
+        "150",
+    ]);
+    std::fs::remove_file(&path).unwrap();
+    let expected = simulate_tp_production(&TradingPostProductionInput {
+        duration_minutes: 480,
+        phase: TradingPostPhase::L2,
+        capacity: 8,
+        speed100: 150,
+        tailoring_ramped: vec![(TradingPostTailoringSkill::Alpha, 60)],
+        proviso_phase: HighRarityOperatorPhase::E2,
+        ..Default::default()
+    });
+    assert_eq!(printed.total_lmd, expected.total_lmd);
+}
+
+#[test]
+fn csv_has_a_header_and_a_row() {
+    let output = riic_sim(&["tp", "--format", "csv", "--collect", "240"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with("stall_chance,stall_chance_until_240,"));
+    assert_eq!(lines[0].split(',').count(), lines[1].split(',').count());
+}
+
+#[test]
+fn exits_with_an_error_on_bad_input() {
+    let invalid = riic_sim(&["tp", "--speed", "0"]);
+    assert_eq!(invalid.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&invalid.stderr).contains("speed"));
+
+    // game data the post can't work with
+    let path = std::env::temp_dir().join(format!("riic-sim-{}.json", std::process::id()));
+    let mut game_data = TradingPostGameData::bundled();
+    game_data.order_duration_seconds = [0, 0, 0];
+    std::fs::write(&path, game_data.to_json()).unwrap();
+    let invalid = riic_sim(&["tp", "--game-data", path.to_str().unwrap()]);
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(invalid.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&invalid.stderr).contains("order durations"));
+
+    assert_eq!(riic_sim(&["tp", "--jaye", "e3"]).status.code(), Some(2));
+    assert_eq!(riic_sim(&["tp", "--duration"]).status.code(), Some(2));
+    assert_eq!(riic_sim(&["factory"]).status.code(), Some(2));
+}
+
+#[test]
+fn warns_when_morale_runs_out_before_the_shift_ends() {
+    let output = riic_sim(&[
+        "tp",
+        "--add-speed",
+        "Base=100",
+        "--add-speed",
+        "Texas=100",
+        "--morale",
+        "Texas=6",
+    ]);
+    assert!(output.status.success(), "{:?}", output);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.starts_with("warning: Texas runs out of morale at 06h00m"));
+
+    let lasting = riic_sim(&["tp", "--morale", "Texas=24:0.5"]);
+    assert!(lasting.stderr.is_empty(), "{:?}", lasting);
+}
+
+#[test]
+fn sweeps_print_a_csv_row_per_combination() {
+    let output = riic_sim(&[
+        "sweep",
+        "--vary",
+        "speed=180:260:20",
+        "--vary=capacity=8,16",
+        "--duration",
+        "600",
+    ]);
+    assert!(output.status.success(), "{:?}", output);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout.lines().count(), 1 + 5 * 2);
+    assert!(stdout.starts_with("speed100,capacity,"));
+
+    assert_eq!(riic_sim(&["sweep"]).status.code(), Some(2));
+    assert_eq!(
+        riic_sim(&["tp", "--vary", "speed=100,200"]).status.code(),
+        Some(2)
+    );
+    assert_eq!(
+        riic_sim(&["sweep", "--vary", "morale=1:2"]).status.code(),
+        Some(2)
+    );
+}
+
+#[test]
+fn solves_for_a_field_within_a_stall_limit() {
+    let output = riic_sim(&[
+        "solve",
+        "--for",
+        "duration",
+        "--max-stall-chance=5",
+        "--format",
+        "json",
+        "--speed",
+        "260",
+        "--capacity",
+        "6",
+    ]);
+    assert!(output.status.success(), "{:?}", output);
+    let solution: Solution = serde_json::from_slice(&output.stdout).unwrap();
+    let expected = Solver {
+        base: TradingPostProductionInput {
+            speed100: 260,
+            capacity: 6,
+            ..Default::default()
+        },
+        field: SweepField::DurationMinutes,
+        limit: StallLimit::Chance(5.0),
+    }
+    .solve()
+    .unwrap()
+    .unwrap();
+    assert_eq!(solution.value, expected.value);
+
+    let unreachable = riic_sim(&["solve", "--for", "speed", "--max-stall-time", "-1"]);
+    assert_eq!(unreachable.status.code(), Some(1));
+    assert_eq!(
+        riic_sim(&["solve", "--for", "speed"]).status.code(),
+        Some(2)
+    );
+    assert_eq!(
+        riic_sim(&["tp", "--max-stall-chance", "5"]).status.code(),
+        Some(2)
+    );
+}
+
+#[test]
+fn stops_quietly_when_stdout_is_closed() {
+    // as when piped into `head`
+    let mut child = Command::new(env!("CARGO_BIN_EXE_riic-sim"))
+        .args(["sweep", "--vary", "capacity=8,10"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("riic-sim runs");
+    drop(child.stdout.take());
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    assert!(output.stderr.is_empty(), "{:?}", output);
+}
+
+#[test]
+fn gold_supply_reports_a_gold_shortage() {
+    let printed = json_output(&["tp", "--format", "json", "--gold", "5:0.5"]);
+    let input = TradingPostProductionInput {
+        gold_supply: Some(GoldSupply {
+            stock: 5,
+            per_hour: 0.5,
+        }),
+        ..Default::default()
+    };
+    let printed = printed
+        .gold_shortage
+        .expect("a gold supply gives a gold shortage");
+    let expected = gold_shortage(&input).unwrap();
+    assert!((printed.wait_chance - expected.wait_chance).abs() < 1e-9);
+    assert!((printed.lost_lmd - expected.lost_lmd).abs() < 1e-6);
+}
@@ -0,0 +1,92 @@
+use ak_riic_tools::trading_sim::*;
+
+fn with_drones(
+    input: &TradingPostProductionInput,
+    usages: &[(i32, i32)],
+) -> TradingPostProductionInput {
+    TradingPostProductionInput {
+        drone_usages: usages
+            .iter()
+            .map(|&(minute, drones)| DroneUsage { minute, drones })
+            .collect(),
+        ..input.clone()
+    }
+}
+
+#[test]
+fn drones_add_production_time() {
+    let base = TradingPostProductionInput {
+        capacity: 38,
+        ..Default::default()
+    };
+    let without = simulate_tp_production(&base);
+    let with = simulate_tp_production(&with_drones(&base, &[(0, 10), (300, 30)]));
+    assert!((with.drones_used - 40.0).abs() < 1e-9);
+    // 40 drones take two hours off, worth two more hours of orders
+    let per_minute = without.total_lmd / base.duration_minutes as f64;
+    let gained = with.total_lmd - without.total_lmd;
+    assert!((gained - 120.0 * per_minute).abs() < 0.02 * gained);
+    assert!(with.total_gold < without.total_gold);
+    assert_eq!(without.stall_chance, 0.0);
+    assert_eq!(with.stall_chance, 0.0);
+}
+
+#[test]
+fn drones_wait_while_the_post_is_full() {
+    let full = TradingPostProductionInput {
+        capacity: 4,
+        start_state: TradingPostStartState {
+            stored_orders: [2, 2, 0],
+            minutes_left: None,
+        },
+        collection_minutes: vec![240],
+        ..Default::default()
+    };
+    let without = simulate_tp_production(&full);
+    let spent_while_full = simulate_tp_production(&with_drones(&full, &[(60, 20)]));
+    assert_eq!(spent_while_full.drones_used, 0.0);
+    assert_eq!(spent_while_full.total_lmd, without.total_lmd);
+    // once the post is emptied, they go in
+    let spent_after = simulate_tp_production(&with_drones(&full, &[(240, 20)]));
+    assert!((spent_after.drones_used - 20.0).abs() < 1e-9);
+    assert!(spent_after.total_lmd > without.total_lmd);
+
+    let negative = with_drones(&full, &[(60, -1)]);
+    assert_eq!(
+        try_simulate(&negative).err(),
+        Some(SimError::NegativeDroneUsage)
+    );
+}
+
+#[test]
+fn planner_spends_the_budget_where_it_helps_most() {
+    let base = TradingPostProductionInput {
+        duration_minutes: 480,
+        capacity: 6,
+        speed100: 220,
+        collection_minutes: vec![240],
+        ..Default::default()
+    };
+    let planner = DronePlanner {
+        base: with_drones(&base, &[(300, 99)]),
+        budget: 30,
+    };
+    let plan = planner.plan().unwrap();
+    assert_eq!(
+        plan.usages.iter().map(|usage| usage.drones).sum::<i32>(),
+        30
+    );
+    let without = simulate_tp_production(&base);
+    assert!(plan.output.total_lmd > without.total_lmd);
+    // spending all of it as the shift starts fills the post early
+    let at_start = simulate_tp_production(&with_drones(&base, &[(0, 30)]));
+    assert!(plan.output.total_lmd >= at_start.total_lmd);
+
+    let mut reports = 0;
+    let cancelled = planner.plan_with_progress(&mut |_| {
+        reports += 1;
+        false
+    });
+    assert!(cancelled.is_none());
+    assert_eq!(reports, 1);
+}
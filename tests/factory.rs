@@ -0,0 +1,115 @@
+use ak_riic_tools::factory_sim::*;
+use ak_riic_tools::trading_sim::*;
+
+#[test]
+fn makes_a_bar_every_72_minutes_at_full_speed() {
+    let output = simulate_factory(&FactoryInput::default()).unwrap();
+    assert_eq!(output.total_made, 10.0);
+    assert_eq!(output.daily_made, 20.0);
+    assert_eq!(output.full_minutes, 0.0);
+    assert_eq!(output.stored_at_end, 10);
+
+    // skills add to the speed
+    let skilled = FactoryInput {
+        skills: vec![FactorySkill {
+            name: "Metallurgy".to_string(),
+            speed100: 100.0,
+            capacity: 0,
+        }],
+        ..Default::default()
+    };
+    assert_eq!(simulate_factory(&skilled).unwrap().total_made, 20.0);
+}
+
+#[test]
+fn stops_while_full_until_collected() {
+    let cramped = |capacity: i32| FactoryInput {
+        level: FactoryLevel::L1,
+        skills: vec![FactorySkill {
+            name: "Cramped".to_string(),
+            speed100: 0.0,
+            capacity,
+        }],
+        ..Default::default()
+    };
+    let small = FactoryInput {
+        collection_minutes: vec![360],
+        ..cramped(-20)
+    };
+    let output = simulate_factory(&small).unwrap();
+    // 4 bars by 288 minutes, then 4 more from the collection at 360 until 648
+    assert_eq!(output.total_made, 8.0);
+    assert!((output.full_minutes - 144.0).abs() < 1e-9);
+    assert_eq!(output.stored_at_end, 4);
+
+    let error = simulate_factory(&cramped(-24)).err();
+    assert_eq!(error, Some(FactoryError::NonPositiveCapacity(0)));
+}
+
+#[test]
+fn gold_balance_against_the_trading_post() {
+    let trading_post = simulate_tp_production(&TradingPostProductionInput {
+        capacity: 38,
+        ..Default::default()
+    });
+    let factory = simulate_factory(&FactoryInput::default()).unwrap();
+    let one = GoldBalance::new(
+        std::slice::from_ref(&factory),
+        std::slice::from_ref(&trading_post),
+    );
+    assert_eq!(one.produced, 20.0);
+    assert!((one.consumed + trading_post.daily_gold).abs() < 1e-9);
+    assert!(!one.keeps_up());
+
+    let many = GoldBalance::new(&vec![factory; 4], &[trading_post]);
+    assert!(many.keeps_up());
+    assert!((many.net() - (80.0 - many.consumed)).abs() < 1e-9);
+}
+
+#[test]
+fn only_gold_factories_count_towards_the_balance() {
+    let trading_post = simulate_tp_production(&TradingPostProductionInput::default());
+    let gold = simulate_factory(&FactoryInput::default()).unwrap();
+    let records = simulate_factory(&FactoryInput {
+        product: FactoryProduct::BattleRecord,
+        ..Default::default()
+    })
+    .unwrap();
+    assert_eq!(records.product, FactoryProduct::BattleRecord);
+    assert!(records.daily_made > 0.0);
+
+    let trading_posts = std::slice::from_ref(&trading_post);
+    let balance = GoldBalance::new(&[gold.clone(), records], trading_posts);
+    assert_eq!(balance, GoldBalance::new(&[gold], trading_posts));
+}
+
+#[test]
+fn level_sets_capacity_and_battle_records() {
+    let factory = |level: FactoryLevel, product: FactoryProduct| FactoryInput {
+        level,
+        product,
+        duration_minutes: 1440,
+        collection_minutes: vec![360, 720, 1080],
+        ..Default::default()
+    };
+    for (level, capacity) in [
+        (FactoryLevel::L1, 24),
+        (FactoryLevel::L2, 36),
+        (FactoryLevel::L3, 54),
+    ] {
+        assert_eq!(factory(level, FactoryProduct::Gold).item_limit(), capacity);
+    }
+    // drill records every 45 minutes, frontline records every 90 and tactical ones every 3 hours
+    for (level, made, exp) in [
+        (FactoryLevel::L1, 32.0, 200.0),
+        (FactoryLevel::L2, 16.0, 400.0),
+        (FactoryLevel::L3, 8.0, 1000.0),
+    ] {
+        let output = simulate_factory(&factory(level, FactoryProduct::BattleRecord)).unwrap();
+        assert_eq!(output.daily_made, made);
+        assert_eq!(level.battle_record_exp(), exp);
+    }
+    // gold takes as long at any level
+    let gold = simulate_factory(&factory(FactoryLevel::L1, FactoryProduct::Gold)).unwrap();
+    assert_eq!(gold.daily_made, 20.0);
+}
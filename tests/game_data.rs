@@ -0,0 +1,110 @@
+use ak_riic_tools::trading_sim::*;
+
+#[test]
+fn bundled_game_data_round_trips_through_json() {
+    let bundled = TradingPostGameData::bundled();
+    assert!(!bundled.version.is_empty());
+    assert!(TradingPostGameData::from_json(&bundled.to_json()).unwrap() == bundled);
+    assert!(TradingPostGameData::from_json("{\"version\": \"1\"}").is_err());
+}
+
+#[test]
+fn overridden_game_data_changes_the_output() {
+    let input = TradingPostProductionInput::default();
+    let doubled = TradingPostProductionInput {
+        game_data: TradingPostGameData {
+            version: "double LMD".to_string(),
+            order_lmd: input.game_data.order_lmd.map(|lmd| lmd * 2),
+            ..input.game_data.clone()
+        },
+        ..input.clone()
+    };
+    let output = simulate_tp_production(&input);
+    let doubled_output = simulate_tp_production(&doubled);
+    assert!((doubled_output.total_lmd - 2.0 * output.total_lmd).abs() < 1e-6 * output.total_lmd);
+    assert_eq!(doubled_output.stall_chance, output.stall_chance);
+
+    // Jaye's effect is game data too: with none at E2, E2 Jaye changes nothing
+    let jaye = TradingPostProductionInput {
+        jaye_phase: HighRarityOperatorPhase::E2,
+        game_data: TradingPostGameData {
+            jaye_speed_per_order: [-4.0, -4.0, 0.0],
+            jaye_capacity_change: [0; 3],
+            ..input.game_data.clone()
+        },
+        ..input.clone()
+    };
+    assert_eq!(simulate_tp_production(&jaye).total_lmd, output.total_lmd);
+
+    // and so are Tequila's, Proviso's and the tailoring skills'
+    let skilled = TradingPostProductionInput {
+        tequila_phase: HighRarityOperatorPhase::E2,
+        proviso_phase: HighRarityOperatorPhase::E2,
+        tailoring_ramped: vec![(TradingPostTailoringSkill::Beta, 0)],
+        ..input.clone()
+    };
+    let without_skills = TradingPostProductionInput {
+        game_data: TradingPostGameData {
+            tailoring_ramp_multipliers: [0.0; 2],
+            tequila_bonus_lmd: [0.0; 3],
+            proviso_extra_gold: [0; 3],
+            ..input.game_data.clone()
+        },
+        ..skilled.clone()
+    };
+    assert_eq!(
+        simulate_tp_production(&without_skills).total_lmd,
+        output.total_lmd
+    );
+    let tequila = |bonus_lmd: f64| {
+        let mut input = skilled.clone();
+        input.game_data.tequila_bonus_lmd[2] = bonus_lmd;
+        simulate_tp_production(&input).total_lmd
+    };
+    assert!(tequila(1000.0) > tequila(500.0));
+}
+
+#[test]
+fn rejects_game_data_that_cannot_be_simulated() {
+    let bundled = TradingPostGameData::bundled();
+    assert_eq!(bundled.validate(), Ok(()));
+    let mut no_time = bundled.clone();
+    no_time.order_duration_seconds = [-100, 12600, 16560];
+    assert_eq!(
+        no_time.validate(),
+        Err(GameDataError::NonPositiveOrderDuration)
+    );
+    let mut no_orders = bundled.clone();
+    no_orders.order_weights[2] = [(0.0, 0.0); 3];
+    assert_eq!(
+        no_orders.validate(),
+        Err(GameDataError::InvalidOrderWeights(3))
+    );
+    let mut negative_weight = bundled.clone();
+    negative_weight.order_weights[1][0].1 = -0.1;
+    assert_eq!(
+        negative_weight.validate(),
+        Err(GameDataError::InvalidOrderWeights(2))
+    );
+    let mut negative_ramp = bundled.clone();
+    negative_ramp.tailoring_ramp_seconds = -1;
+    assert_eq!(
+        negative_ramp.validate(),
+        Err(GameDataError::NegativeTailoringRamp)
+    );
+
+    let mut past_ramped = bundled.clone();
+    past_ramped.tailoring_ramp_multipliers[0] = 1.5;
+    assert_eq!(
+        past_ramped.validate(),
+        Err(GameDataError::InvalidTailoringMultiplier)
+    );
+
+    // loading checks the data too, and arrays must hold one entry per order type
+    assert!(TradingPostGameData::from_json(&no_time.to_json()).is_err());
+    let short = bundled
+        .to_json()
+        .replace("\"order_lmd\": [\n    1000,", "\"order_lmd\": [");
+    assert!(short != bundled.to_json());
+    assert!(TradingPostGameData::from_json(&short).is_err());
+}
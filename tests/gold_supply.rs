@@ -0,0 +1,66 @@
+use ak_riic_tools::trading_sim::*;
+
+fn input(gold_supply: Option<GoldSupply>) -> TradingPostProductionInput {
+    TradingPostProductionInput {
+        capacity: 6,
+        collection_minutes: vec![240, 480],
+        gold_supply,
+        ..Default::default()
+    }
+}
+
+fn supply(stock: i32, per_hour: f64) -> Option<GoldSupply> {
+    Some(GoldSupply { stock, per_hour })
+}
+
+#[test]
+fn plenty_of_gold_changes_nothing() {
+    let unlimited = simulate_tp_production(&input(None));
+    assert_eq!(unlimited.gold_shortage, None);
+
+    let plenty = simulate_tp_production(&input(supply(100, 0.0)));
+    assert_eq!(plenty.total_lmd, unlimited.total_lmd);
+    // estimated only when asked for
+    assert_eq!(plenty.gold_shortage, None);
+    assert_eq!(gold_shortage(&input(None)), None);
+    let shortage = gold_shortage(&input(supply(100, 0.0))).expect("the input has a gold supply");
+    assert_eq!(shortage.interval_wait_chance, vec![0.0; 3]);
+    assert_eq!(shortage.wait_chance, 0.0);
+    assert_eq!(shortage.delayed_lmd, 0.0);
+    assert_eq!(shortage.lost_lmd, 0.0);
+    // every order finished is paid for, which is about the gold the shift uses up
+    let used = 100.0 - shortage.stock_at_end;
+    assert!((used + unlimited.total_gold).abs() < 0.1 * used);
+}
+
+#[test]
+fn orders_wait_for_gold_and_fill_the_post() {
+    let shortage = simulate_gold_shortage(&input(supply(0, 0.0)), 1000, 1).unwrap();
+    assert_eq!(shortage.wait_chance, 100.0);
+    assert!(shortage.delayed_lmd > 0.0);
+    // with nothing delivered, 6 orders fill the post long before the shift ends
+    assert!(shortage.lost_lmd > 0.0);
+    assert_eq!(shortage.stock_at_end, 0.0);
+
+    // gold coming in clears some of the orders waiting, and later rather than sooner
+    let trickle = simulate_gold_shortage(&input(supply(8, 0.5)), 1000, 1).unwrap();
+    assert_eq!(trickle.interval_wait_chance[0], 0.0);
+    assert!(trickle.interval_wait_chance[1] < trickle.interval_wait_chance[2]);
+    assert!(trickle.delayed_lmd < shortage.delayed_lmd);
+}
+
+#[test]
+fn gold_supply_must_not_be_negative() {
+    let error = try_simulate(&input(supply(-1, 0.0))).err();
+    assert_eq!(error, Some(SimError::NegativeGoldSupply));
+    let error = try_simulate(&input(supply(10, -0.5))).err();
+    assert_eq!(error, Some(SimError::NegativeGoldSupply));
+}
+
+#[test]
+fn day_plans_of_several_shifts_have_no_gold_supply() {
+    let shift = input(supply(10, 0.5));
+    assert_eq!(validate_day(std::slice::from_ref(&shift)), Ok(()));
+    let day = [input(None), shift];
+    assert_eq!(validate_day(&day), Err((1, SimError::GoldSupplyInDayPlan)));
+}
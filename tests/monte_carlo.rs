@@ -0,0 +1,432 @@
+use ak_riic_tools::trading_sim::*;
+
+type Field = fn(&TradingPostProductionOutput) -> f64;
+
+/// Checks that the DP and the Monte Carlo simulator agree on every output field.
+fn assert_engines_agree(input: &TradingPostProductionInput) {
+    let sampled = simulate_tp_monte_carlo(input, 20_000, 42);
+    assert_outputs_agree(
+        "shift",
+        &simulate_tp_production(input),
+        &sampled.estimate,
+        &sampled.ci95,
+    );
+}
+
+/// Checks that the DP and the Monte Carlo simulator agree on every shift of a day plan, and on
+/// the day as a whole.
+fn assert_day_engines_agree(shifts: &[TradingPostProductionInput]) {
+    let exact = simulate_tp_day(shifts);
+    let sampled = simulate_tp_day_monte_carlo(shifts, 20_000, 42);
+    assert_eq!(exact.shifts.len(), shifts.len());
+    for (i, shift) in exact.shifts.iter().enumerate() {
+        assert_outputs_agree(
+            &format!("shift {}", i),
+            shift,
+            &sampled.estimate.shifts[i],
+            &sampled.ci95.shifts[i],
+        );
+    }
+    assert_outputs_agree("day", &exact.day, &sampled.estimate.day, &sampled.ci95.day);
+}
+
+fn assert_outputs_agree(
+    label: &str,
+    exact: &TradingPostProductionOutput,
+    estimate: &TradingPostProductionOutput,
+    ci95: &TradingPostProductionOutput,
+) {
+    let fields: [(&str, Field); 9] = [
+        ("stall chance", |o| o.stall_chance),
+        ("average stall time", |o| o.average_stall_time),
+        ("total LMD", |o| o.total_lmd),
+        ("total gold", |o| o.total_gold),
+        ("daily LMD", |o| o.daily_lmd),
+        ("daily gold", |o| o.daily_gold),
+        ("net LMD speed", |o| o.net_lmd_speed),
+        ("net gold speed", |o| o.net_gold_speed),
+        ("drones used", |o| o.drones_used),
+    ];
+    let intervals = exact.interval_stall_chance.len();
+    assert_eq!(intervals, estimate.interval_stall_chance.len());
+    let checks = fields
+        .iter()
+        .map(|(name, field)| (name.to_string(), field(exact), field(estimate), field(ci95)))
+        .chain((0..intervals).map(|i| {
+            (
+                format!("stall chance in interval {}", i),
+                exact.interval_stall_chance[i],
+                estimate.interval_stall_chance[i],
+                ci95.interval_stall_chance[i],
+            )
+        }));
+    for (name, exact, estimate, ci) in checks {
+        // twice the 95% interval, plus slack for fields that are (almost) constant across runs
+        let tolerance = 2.0 * ci + 1e-6 * (1.0 + exact.abs());
+        assert!(
+            (exact - estimate).abs() <= tolerance,
+            "{} {}: DP {} vs Monte Carlo {} ± {}",
+            label,
+            name,
+            exact,
+            estimate,
+            ci
+        );
+    }
+}
+
+/// Checks that the DP and the Monte Carlo simulator agree on the spread of LMD and gold, and not
+/// only on their means.
+fn assert_distributions_agree(input: &TradingPostProductionInput) {
+    let exact = simulate_tp_production(input);
+    let sampled = simulate_tp_monte_carlo(input, 20_000, 42).estimate;
+    for (name, exact, sampled) in [
+        ("LMD", &exact.lmd_distribution, &sampled.lmd_distribution),
+        ("gold", &exact.gold_distribution, &sampled.gold_distribution),
+    ] {
+        let tolerance = 0.05 * sampled.std_dev() + 1e-6;
+        assert!(
+            (exact.std_dev() - sampled.std_dev()).abs() <= tolerance,
+            "{} std. deviation: DP {} vs Monte Carlo {}",
+            name,
+            exact.std_dev(),
+            sampled.std_dev()
+        );
+        // both report the mean outcome of the bin a percentile falls in
+        let bin_width = exact.bin_width;
+        for (q, exact, sampled) in [
+            (5, exact.p5, sampled.p5),
+            (50, exact.p50, sampled.p50),
+            (95, exact.p95, sampled.p95),
+        ] {
+            assert!(
+                (exact - sampled).abs() <= bin_width,
+                "{} {}th percentile: DP {} vs Monte Carlo {}",
+                name,
+                q,
+                exact,
+                sampled
+            );
+        }
+    }
+}
+
+#[test]
+fn default_shift() {
+    assert_engines_agree(&TradingPostProductionInput::default());
+}
+
+#[test]
+fn spread_matches_monte_carlo() {
+    // partial orders credit fractions of a bin, which must not widen the spread as they move
+    assert_distributions_agree(&TradingPostProductionInput::default());
+    assert_distributions_agree(&TradingPostProductionInput {
+        duration_minutes: 300,
+        speed100: 150,
+        tequila_phase: HighRarityOperatorPhase::E0,
+        ..Default::default()
+    });
+}
+
+#[test]
+fn short_shift_with_tequila_and_proviso() {
+    // shorter than any order, so only the first and last partial orders are credited
+    assert_engines_agree(&TradingPostProductionInput {
+        duration_minutes: 100,
+        tequila_phase: HighRarityOperatorPhase::E2,
+        proviso_phase: HighRarityOperatorPhase::E1,
+        ..Default::default()
+    });
+    assert_engines_agree(&TradingPostProductionInput {
+        duration_minutes: 300,
+        speed100: 150,
+        tequila_phase: HighRarityOperatorPhase::E0,
+        ..Default::default()
+    });
+}
+
+#[test]
+fn stalling_shift_with_jaye_and_tailoring() {
+    assert_engines_agree(&TradingPostProductionInput {
+        duration_minutes: 960,
+        phase: TradingPostPhase::L2,
+        capacity: 8,
+        speed100: 240,
+        tailoring_ramped: vec![
+            (TradingPostTailoringSkill::Alpha, 60),
+            (TradingPostTailoringSkill::Beta, 0),
+        ],
+        jaye_phase: HighRarityOperatorPhase::E0,
+        tequila_phase: HighRarityOperatorPhase::E2,
+        ..Default::default()
+    });
+}
+
+#[test]
+fn jaye_across_elite_phases() {
+    assert_engines_agree(&TradingPostProductionInput {
+        duration_minutes: 720,
+        capacity: 10,
+        speed100: 180,
+        jaye_phase: HighRarityOperatorPhase::E1,
+        jaye_order_count: JayeOrderCount::AfterFinish,
+        ..Default::default()
+    });
+    // E2 holds fewer orders but speeds up as they pile up
+    assert_engines_agree(&TradingPostProductionInput {
+        duration_minutes: 720,
+        capacity: 12,
+        speed100: 150,
+        jaye_phase: HighRarityOperatorPhase::E2,
+        ..Default::default()
+    });
+    assert_engines_agree(&TradingPostProductionInput {
+        duration_minutes: 480,
+        capacity: 10,
+        speed100: 150,
+        jaye_phase: HighRarityOperatorPhase::E2,
+        jaye_order_count: JayeOrderCount::AfterFinish,
+        collection_minutes: vec![240],
+        ..Default::default()
+    });
+}
+
+#[test]
+fn shift_with_start_state() {
+    assert_engines_agree(&TradingPostProductionInput {
+        duration_minutes: 480,
+        start_state: TradingPostStartState {
+            stored_orders: [2, 3, 1],
+            minutes_left: Some(50),
+        },
+        ..Default::default()
+    });
+    assert_engines_agree(&TradingPostProductionInput {
+        duration_minutes: 600,
+        capacity: 8,
+        jaye_phase: HighRarityOperatorPhase::E0,
+        start_state: TradingPostStartState {
+            stored_orders: [0, 4, 0],
+            minutes_left: None,
+        },
+        ..Default::default()
+    });
+}
+
+#[test]
+fn shift_with_collections() {
+    assert_engines_agree(&TradingPostProductionInput {
+        duration_minutes: 1440,
+        capacity: 6,
+        speed100: 260,
+        collection_minutes: vec![480, 720, 1020],
+        start_state: TradingPostStartState {
+            stored_orders: [1, 1, 0],
+            minutes_left: None,
+        },
+        ..Default::default()
+    });
+    // a post that is full from the start and only emptied half way through
+    assert_engines_agree(&TradingPostProductionInput {
+        duration_minutes: 600,
+        capacity: 4,
+        collection_minutes: vec![300],
+        start_state: TradingPostStartState {
+            stored_orders: [0, 0, 4],
+            minutes_left: None,
+        },
+        ..Default::default()
+    });
+}
+
+#[test]
+fn shift_with_speed_changes() {
+    assert_engines_agree(&TradingPostProductionInput {
+        capacity: 8,
+        speed100: 240,
+        speed_schedule: vec![
+            SpeedChange {
+                from_minutes: 300,
+                speed100: 150.5,
+            },
+            SpeedChange {
+                from_minutes: 540,
+                speed100: 280.0,
+            },
+        ],
+        jaye_phase: HighRarityOperatorPhase::E2,
+        ..Default::default()
+    });
+}
+
+#[test]
+fn shift_with_drones() {
+    // drones on the order in progress at the start, and enough at a collection to finish
+    // several orders in a row
+    assert_engines_agree(&TradingPostProductionInput {
+        capacity: 6,
+        speed100: 230,
+        collection_minutes: vec![360],
+        drone_usages: vec![
+            DroneUsage {
+                minute: 0,
+                drones: 5,
+            },
+            DroneUsage {
+                minute: 360,
+                drones: 60,
+            },
+            DroneUsage {
+                minute: 600,
+                drones: 3,
+            },
+        ],
+        ..Default::default()
+    });
+    // drones finishing the order in progress from the start state, in a post that fills up
+    assert_engines_agree(&TradingPostProductionInput {
+        duration_minutes: 480,
+        capacity: 4,
+        start_state: TradingPostStartState {
+            stored_orders: [1, 1, 0],
+            minutes_left: Some(20),
+        },
+        drone_usages: vec![DroneUsage {
+            minute: 5,
+            drones: 40,
+        }],
+        ..Default::default()
+    });
+}
+
+#[test]
+fn day_of_three_teams() {
+    assert_day_engines_agree(&[
+        TradingPostProductionInput {
+            duration_minutes: 480,
+            speed100: 210,
+            tequila_phase: HighRarityOperatorPhase::E2,
+            start_state: TradingPostStartState {
+                stored_orders: [1, 0, 1],
+                minutes_left: Some(30),
+            },
+            ..Default::default()
+        },
+        TradingPostProductionInput {
+            duration_minutes: 600,
+            speed100: 160,
+            tailoring_ramped: vec![(TradingPostTailoringSkill::Beta, 0)],
+            proviso_phase: HighRarityOperatorPhase::E2,
+            collection_minutes: vec![0, 300],
+            ..Default::default()
+        },
+        TradingPostProductionInput {
+            duration_minutes: 360,
+            capacity: 6,
+            speed100: 280,
+            jaye_phase: HighRarityOperatorPhase::E0,
+            // on the order handed over from the shift before
+            drone_usages: vec![DroneUsage {
+                minute: 0,
+                drones: 8,
+            }],
+            ..Default::default()
+        },
+    ]);
+}
+
+#[test]
+fn day_with_shifts_shorter_than_an_order() {
+    // the order in progress at the end of the first shift outlasts the second one too
+    assert_day_engines_agree(&[
+        TradingPostProductionInput {
+            duration_minutes: 200,
+            capacity: 4,
+            ..Default::default()
+        },
+        TradingPostProductionInput {
+            duration_minutes: 45,
+            capacity: 2,
+            speed100: 100,
+            ..Default::default()
+        },
+        TradingPostProductionInput {
+            duration_minutes: 500,
+            capacity: 3,
+            speed100: 250,
+            ..Default::default()
+        },
+    ]);
+}
+
+#[test]
+fn day_with_tequila_joining_mid_order() {
+    // the engines credit split orders and Tequila's bonus by rules of their own, which must
+    // still add up to the same: the order carried into the second shift started without Tequila,
+    // and the one carried into the third runs through it at a different speed
+    assert_day_engines_agree(&[
+        TradingPostProductionInput {
+            duration_minutes: 95,
+            capacity: 3,
+            start_state: TradingPostStartState {
+                stored_orders: [0, 1, 0],
+                minutes_left: Some(70),
+            },
+            ..Default::default()
+        },
+        TradingPostProductionInput {
+            duration_minutes: 130,
+            capacity: 4,
+            tequila_phase: HighRarityOperatorPhase::E2,
+            speed100: 150,
+            ..Default::default()
+        },
+        TradingPostProductionInput {
+            duration_minutes: 40,
+            capacity: 4,
+            speed100: 320,
+            ..Default::default()
+        },
+        TradingPostProductionInput {
+            duration_minutes: 300,
+            capacity: 2,
+            tequila_phase: HighRarityOperatorPhase::E0,
+            ..Default::default()
+        },
+    ]);
+}
+
+#[test]
+fn day_of_identical_shifts_matches_one_long_shift() {
+    let shift = TradingPostProductionInput {
+        duration_minutes: 480,
+        capacity: 8,
+        speed100: 210,
+        ..Default::default()
+    };
+    let day = simulate_tp_day(&[shift.clone(), shift.clone(), shift.clone()]);
+    let long_shift = simulate_tp_production(&TradingPostProductionInput {
+        duration_minutes: 1440,
+        ..shift
+    });
+    let shift_lmd: f64 = day.shifts.iter().map(|o| o.total_lmd).sum();
+    for (name, chained, single) in [
+        (
+            "stall chance",
+            day.day.stall_chance,
+            long_shift.stall_chance,
+        ),
+        ("total LMD", day.day.total_lmd, long_shift.total_lmd),
+        ("total LMD over shifts", shift_lmd, long_shift.total_lmd),
+        ("total gold", day.day.total_gold, long_shift.total_gold),
+        ("daily LMD", day.day.daily_lmd, long_shift.daily_lmd),
+    ] {
+        assert!(
+            (chained - single).abs() <= 1e-9 * (1.0 + single.abs()),
+            "{}: day plan {} vs one shift {}",
+            name,
+            chained,
+            single
+        );
+    }
+}
@@ -0,0 +1,133 @@
+use ak_riic_tools::trading_sim::*;
+
+fn operator(name: &str, morale: f64, drain_per_hour: f64) -> OperatorMorale {
+    OperatorMorale {
+        name: name.to_string(),
+        morale,
+        drain_per_hour,
+    }
+}
+
+fn input(morale: Vec<OperatorMorale>) -> TradingPostProductionInput {
+    TradingPostProductionInput {
+        capacity: 38,
+        speed_breakdown: SpeedBreakdown(vec![
+            SpeedContribution::add("Base", 100.0),
+            SpeedContribution::add("Texas", 80.0),
+        ]),
+        morale,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn speed_parts_stop_as_morale_runs_out() {
+    // 6 hours of morale, named in any case
+    let exhausted = simulate_tp_production(&input(vec![operator(" texas", 12.0, 2.0)]));
+    let scheduled = simulate_tp_production(&TradingPostProductionInput {
+        speed_schedule: vec![SpeedChange {
+            from_minutes: 360,
+            speed100: 100.0,
+        }],
+        ..input(vec![])
+    });
+    assert_eq!(exhausted.total_lmd, scheduled.total_lmd);
+    assert!((exhausted.average_speed - 140.0).abs() < 1e-9);
+
+    // morale that lasts the shift, or never drains, changes nothing
+    let full = simulate_tp_production(&input(vec![]));
+    for morale in [operator("Texas", 24.0, 1.0), operator("Texas", 1.0, 0.0)] {
+        let lasting = simulate_tp_production(&input(vec![morale]));
+        assert_eq!(full.total_lmd, lasting.total_lmd);
+    }
+}
+
+#[test]
+fn jaye_stops_counting_orders_when_exhausted() {
+    let jaye = |morale| TradingPostProductionInput {
+        jaye_phase: HighRarityOperatorPhase::E0,
+        ..input(morale)
+    };
+    let without = simulate_tp_production(&input(vec![]));
+    let working = simulate_tp_production(&jaye(vec![]));
+    let exhausted = simulate_tp_production(&jaye(vec![operator("Jaye", 0.0, 1.0)]));
+    assert!(working.total_lmd < without.total_lmd);
+    assert_eq!(exhausted.total_lmd, without.total_lmd);
+}
+
+#[test]
+fn shortfall_when_the_shift_outlasts_morale() {
+    let shift = input(vec![
+        operator("Texas", 24.0, 1.0),
+        operator("Jaye", 10.0, 1.5),
+    ]);
+    let shortfall = shift
+        .morale_shortfall()
+        .expect("Jaye runs out at 400 minutes");
+    assert_eq!(
+        shortfall,
+        MoraleShortfall {
+            name: "Jaye".to_string(),
+            exhausted_minutes: 400,
+            duration_minutes: 720,
+        }
+    );
+    assert_eq!(
+        shortfall.to_string(),
+        "Jaye runs out of morale at 06h40m, 320 minutes before the shift ends"
+    );
+
+    let shorter = TradingPostProductionInput {
+        duration_minutes: 400,
+        ..shift
+    };
+    assert_eq!(shorter.morale_shortfall(), None);
+
+    let error = try_simulate(&input(vec![operator("Texas", 30.0, 1.0)])).err();
+    assert_eq!(error, Some(SimError::InvalidMorale));
+    let error = try_simulate(&input(vec![operator("Texas", 24.0, -1.0)])).err();
+    assert_eq!(error, Some(SimError::InvalidMorale));
+}
+
+#[test]
+fn order_skills_end_partway_through_the_shift() {
+    let total = |morale: Vec<OperatorMorale>, shift: &TradingPostProductionInput| {
+        let output = simulate_tp_production(&TradingPostProductionInput {
+            morale,
+            ..shift.clone()
+        });
+        (output.total_lmd, output.total_gold)
+    };
+    let without = total(vec![], &input(vec![]));
+    let skilled = [
+        TradingPostProductionInput {
+            tequila_phase: HighRarityOperatorPhase::E2,
+            ..input(vec![])
+        },
+        TradingPostProductionInput {
+            proviso_phase: HighRarityOperatorPhase::E2,
+            ..input(vec![])
+        },
+        TradingPostProductionInput {
+            tailoring_ramped: vec![(TradingPostTailoringSkill::Beta, 180)],
+            ..input(vec![])
+        },
+    ];
+    // tailoring operators go by their skill
+    for (name, shift) in ["Tequila", "Proviso", "beta"].iter().zip(skilled.iter()) {
+        let working = total(vec![], shift);
+        // out of morale 6 hours into the 12-hour shift
+        let exhausted = total(vec![operator(name, 6.0, 1.0)], shift);
+        assert!(
+            without.0 < exhausted.0 && exhausted.0 < working.0,
+            "{}: {:?} LMD between {:?} and {:?}",
+            name,
+            exhausted.0,
+            without.0,
+            working.0
+        );
+        let (low, high) = (working.1.min(without.1), working.1.max(without.1));
+        assert!((low..=high).contains(&exhausted.1), "{} gold", name);
+        assert_eq!(total(vec![operator(name, 0.0, 1.0)], shift), without);
+    }
+}
@@ -0,0 +1,68 @@
+use ak_riic_tools::factory_sim::*;
+use ak_riic_tools::trading_sim::*;
+
+fn posts() -> Vec<TradingPostProductionInput> {
+    vec![
+        TradingPostProductionInput {
+            capacity: 8,
+            collection_minutes: vec![360],
+            ..Default::default()
+        },
+        TradingPostProductionInput {
+            phase: TradingPostPhase::L2,
+            capacity: 6,
+            speed100: 150,
+            duration_minutes: 480,
+            ..Default::default()
+        },
+    ]
+}
+
+#[test]
+fn each_post_runs_on_its_own() {
+    let posts = posts();
+    let together = simulate_trading_posts(&posts);
+    assert_eq!(together.trading_posts.len(), 2);
+    for (input, output) in posts.iter().zip(together.trading_posts.iter()) {
+        let alone = simulate_tp_production(input);
+        assert_eq!(output.daily_lmd, alone.daily_lmd);
+        assert_eq!(output.stall_chance, alone.stall_chance);
+    }
+    let [first, second] = [&together.trading_posts[0], &together.trading_posts[1]];
+    assert!((together.total_lmd - first.total_lmd - second.total_lmd).abs() < 1e-9);
+    assert!((together.daily_lmd - first.daily_lmd - second.daily_lmd).abs() < 1e-9);
+    assert!((together.daily_gold - first.daily_gold - second.daily_gold).abs() < 1e-9);
+    assert!((together.gold_demand + together.daily_gold).abs() < 1e-9);
+}
+
+#[test]
+fn stalls_when_any_post_does() {
+    let posts = posts();
+    let together = simulate_trading_posts(&posts);
+    let [first, second] = [&together.trading_posts[0], &together.trading_posts[1]];
+    assert!(together.stall_chance >= first.stall_chance.max(second.stall_chance));
+    assert!(together.stall_chance <= first.stall_chance + second.stall_chance);
+    // independent posts only both run clear as often as each does
+    let clear = (100.0 - first.stall_chance) * (100.0 - second.stall_chance) / 100.0;
+    assert!((together.stall_chance - (100.0 - clear)).abs() < 1e-9);
+
+    // gold demand is what the factories are set against
+    let factories = [simulate_factory(&FactoryInput::default()).unwrap()];
+    let balance = GoldBalance::new(&factories, &together.trading_posts);
+    assert!((balance.consumed - together.gold_demand).abs() < 1e-9);
+}
+
+#[test]
+fn reports_the_post_that_fails() {
+    let mut posts = posts();
+    posts[1].duration_minutes = 0;
+    let error = try_simulate_trading_posts(&posts).err();
+    let error = error.expect("the second post can't be simulated");
+    assert_eq!(error.index, 1);
+    assert_eq!(error.error, SimError::NonPositiveDuration(0));
+    assert!(error.to_string().starts_with("trading post 2: "));
+
+    let none = try_simulate_trading_posts(&[]).unwrap();
+    assert_eq!(none.stall_chance, 0.0);
+    assert_eq!(none.gold_demand, 0.0);
+}
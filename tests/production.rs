@@ -0,0 +1,162 @@
+use ak_riic_tools::trading_sim::*;
+
+#[test]
+fn short_shift_credits_the_order_in_progress() {
+    // the first order outlasts the shift; it used to be dropped, crediting 529.98 LMD
+    let output = simulate_tp_production(&TradingPostProductionInput {
+        duration_minutes: 60,
+        capacity: 5,
+        ..Default::default()
+    });
+    assert!((output.total_lmd - 853.4705135269919).abs() < 1e-6);
+    assert!((output.total_gold + 1.7069410270539058).abs() < 1e-9);
+
+    // a post that never stalls earns at the same rate over any length of shift
+    let long = simulate_tp_production(&TradingPostProductionInput::default());
+    let hourly = long.total_lmd / 12.0;
+    assert!(
+        (output.total_lmd - hourly).abs() < 0.01 * hourly,
+        "{} LMD in an hour vs {} an hour over a long shift",
+        output.total_lmd,
+        hourly
+    );
+}
+
+/// Outputs of the original simulator, before it was reworked, for inputs whose modelling has
+/// not changed since: stall chance, average stall time, total LMD, total gold, daily LMD, daily
+/// gold, net LMD speed and net gold speed.
+const GOLDEN: [[f64; 8]; 4] = [
+    [
+        0.02641174357919273,
+        0.0032515808006424677,
+        10263.095949339602,
+        -20.52619189867905,
+        20526.191898679204,
+        -41.0523837973581,
+        199.9534210819612,
+        1.5631940186722206e-12,
+    ],
+    [
+        99.99999999999937,
+        358.7866150305186,
+        9542.616373137116,
+        -19.085232746274368,
+        14313.924559705674,
+        -28.627849119411554,
+        139.43736855575355,
+        -1.0302869668521453e-12,
+    ],
+    [
+        100.00000000001164,
+        912.0083333333333,
+        10999.826388888885,
+        -21.999652777777786,
+        10999.826388888885,
+        -21.999652777777786,
+        107.15348120210723,
+        -8.881784197001252e-14,
+    ],
+    [
+        0.0,
+        0.0,
+        5574.879193776411,
+        -10.336641465655527,
+        16724.637581329232,
+        -31.009924396966582,
+        162.92103850777613,
+        12.196753828459421,
+    ],
+];
+
+#[test]
+fn matches_the_original_simulator() {
+    let inputs = [
+        TradingPostProductionInput::default(),
+        TradingPostProductionInput {
+            duration_minutes: 960,
+            phase: TradingPostPhase::L2,
+            capacity: 8,
+            speed100: 240,
+            tailoring_ramped: vec![
+                (TradingPostTailoringSkill::Alpha, 60),
+                (TradingPostTailoringSkill::Beta, 0),
+            ],
+            jaye_phase: HighRarityOperatorPhase::E0,
+            tequila_phase: HighRarityOperatorPhase::E2,
+            ..Default::default()
+        },
+        TradingPostProductionInput {
+            duration_minutes: 1440,
+            phase: TradingPostPhase::L1,
+            capacity: 6,
+            speed100: 150,
+            proviso_phase: HighRarityOperatorPhase::E2,
+            ..Default::default()
+        },
+        TradingPostProductionInput {
+            duration_minutes: 480,
+            capacity: 6,
+            speed100: 150,
+            tailoring_ramped: vec![(TradingPostTailoringSkill::Alpha, 90)],
+            tequila_phase: HighRarityOperatorPhase::E0,
+            ..Default::default()
+        },
+    ];
+    for (i, (input, golden)) in inputs.iter().zip(GOLDEN.iter()).enumerate() {
+        let output = simulate_tp_production(input);
+        let fields = [
+            output.stall_chance,
+            output.average_stall_time,
+            output.total_lmd,
+            output.total_gold,
+            output.daily_lmd,
+            output.daily_gold,
+            output.net_lmd_speed,
+            output.net_gold_speed,
+        ];
+        for (field, (value, expected)) in fields.iter().zip(golden.iter()).enumerate() {
+            assert!(
+                (value - expected).abs() <= 1e-9 * (1.0 + expected.abs()),
+                "input {} field {}: {} vs {}",
+                i,
+                field,
+                value,
+                expected
+            );
+        }
+    }
+}
+
+#[test]
+fn order_carried_through_a_shift_speeds_up_in_the_next() {
+    // 45 minutes of the order are left after the second shift, which take 22.5 at double speed
+    let day = simulate_tp_day(&[
+        TradingPostProductionInput {
+            duration_minutes: 10,
+            capacity: 1,
+            start_state: TradingPostStartState {
+                stored_orders: [0, 0, 0],
+                minutes_left: Some(100),
+            },
+            ..Default::default()
+        },
+        TradingPostProductionInput {
+            duration_minutes: 45,
+            capacity: 1,
+            ..Default::default()
+        },
+        TradingPostProductionInput {
+            duration_minutes: 60,
+            capacity: 1,
+            speed100: 400,
+            ..Default::default()
+        },
+    ]);
+    let last = &day.shifts[2];
+    assert!((last.stall_chance - 100.0).abs() < 1e-9);
+    assert!(
+        (last.average_stall_time - 37.5).abs() < 1e-9,
+        "stalled {} minutes",
+        last.average_stall_time
+    );
+}
@@ -0,0 +1,45 @@
+use ak_riic_tools::trading_sim::*;
+
+fn day_plan() -> Vec<TradingPostProductionInput> {
+    vec![
+        TradingPostProductionInput {
+            duration_minutes: 600,
+            ..Default::default()
+        },
+        TradingPostProductionInput {
+            duration_minutes: 840,
+            speed100: 180,
+            ..Default::default()
+        },
+    ]
+}
+
+#[test]
+fn progress_rises_to_the_end_without_changing_the_output() {
+    let shifts = day_plan();
+    let mut reports = Vec::new();
+    let output = simulate_tp_day_with_progress(&shifts, &mut |fraction| {
+        reports.push(fraction);
+        true
+    })
+    .expect("not cancelled");
+    assert!(reports.len() > 10);
+    assert!(reports.windows(2).all(|pair| pair[0] < pair[1]));
+    assert!(reports[0] > 0.0 && *reports.last().unwrap() <= 1.0);
+    assert!(*reports.last().unwrap() > 0.99);
+
+    let expected = simulate_tp_day(&shifts);
+    assert_eq!(output.day.total_lmd, expected.day.total_lmd);
+    assert_eq!(output.day.stall_chance, expected.day.stall_chance);
+}
+
+#[test]
+fn cancelling_stops_at_the_next_report() {
+    let mut reports = 0;
+    let output = simulate_tp_day_with_progress(&day_plan(), &mut |_| {
+        reports += 1;
+        reports < 3
+    });
+    assert!(output.is_none());
+    assert_eq!(reports, 3);
+}
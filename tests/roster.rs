@@ -0,0 +1,70 @@
+use ak_riic_tools::roster::*;
+use ak_riic_tools::trading_sim::*;
+
+fn roster() -> Roster {
+    Roster {
+        operators: vec![
+            OwnedOperator {
+                name: " jaye".to_string(),
+                elite: HighRarityOperatorPhase::E1,
+                level: 40,
+                tailoring: None,
+            },
+            OwnedOperator {
+                name: "Gummy".to_string(),
+                elite: HighRarityOperatorPhase::E2,
+                level: 30,
+                tailoring: Some(TradingPostTailoringSkill::Beta),
+            },
+        ],
+    }
+}
+
+#[test]
+fn fields_operators_up_to_their_elite_phase() {
+    let roster = roster();
+    assert_eq!(roster.get("Jaye ").map(|jaye| jaye.level), Some(40));
+    assert!(roster.allows("Jaye", &HighRarityOperatorPhase::E0));
+    assert!(roster.allows("Jaye", &HighRarityOperatorPhase::E1));
+    assert!(!roster.allows("Jaye", &HighRarityOperatorPhase::E2));
+    // nobody can always be picked
+    assert!(roster.allows("Tequila", &HighRarityOperatorPhase::None));
+    assert!(!roster.allows("Tequila", &HighRarityOperatorPhase::E0));
+
+    let input = TradingPostProductionInput {
+        jaye_phase: HighRarityOperatorPhase::E2,
+        tequila_phase: HighRarityOperatorPhase::E0,
+        ..Default::default()
+    };
+    assert_eq!(roster.unowned_in(&input), vec!["Jaye", "Tequila"]);
+}
+
+#[test]
+fn tailoring_comes_from_operators_owned() {
+    let roster = roster();
+    let tailors: Vec<&str> = roster.tailors().map(|op| op.name.as_str()).collect();
+    assert_eq!(tailors, vec!["Gummy"]);
+    assert!(roster.allows_tailoring(&TradingPostTailoringSkill::Beta));
+    assert!(!roster.allows_tailoring(&TradingPostTailoringSkill::Alpha));
+
+    // an empty roster limits nothing
+    let empty = Roster::default();
+    assert!(empty.allows("Proviso", &HighRarityOperatorPhase::E2));
+    assert!(empty.allows_tailoring(&TradingPostTailoringSkill::Alpha));
+    let input = TradingPostProductionInput::default();
+    assert!(empty.unowned_in(&input).is_empty());
+}
+
+#[test]
+fn roster_survives_saving() {
+    let roster = roster();
+    let json = serde_json::to_string(&roster).unwrap();
+    assert!(serde_json::from_str::<Roster>(&json).unwrap() == roster);
+
+    // fields left out take their defaults
+    let roster: Roster = serde_json::from_str(r#"{"operators": [{"name": "Proviso"}]}"#).unwrap();
+    let proviso = roster.get("proviso").unwrap();
+    assert!(proviso.elite == HighRarityOperatorPhase::E0);
+    assert_eq!(proviso.level, 1);
+    assert!(proviso.tailoring.is_none());
+}
@@ -0,0 +1,44 @@
+use std::sync::Arc;
+
+use ak_riic_tools::trading_sim::*;
+
+/// An operator that adds a flat speed bonus and extra order slots.
+struct FlatBonus {
+    speed100: f64,
+    capacity: i32,
+}
+
+impl TradingPostSkill for FlatBonus {
+    fn speed(&self, speed100: f64, _stored: usize) -> f64 {
+        speed100 + self.speed100
+    }
+
+    fn capacity(&self, capacity: i32) -> i32 {
+        capacity + self.capacity
+    }
+}
+
+#[test]
+fn extra_skills_apply_like_the_fields_they_stand_in_for() {
+    let input = TradingPostProductionInput {
+        duration_minutes: 960,
+        capacity: 6,
+        speed100: 180,
+        ..Default::default()
+    };
+    let with_skill = simulate_tp_production(&TradingPostProductionInput {
+        extra_skills: vec![Arc::new(FlatBonus {
+            speed100: 25.0,
+            capacity: 2,
+        })],
+        ..input.clone()
+    });
+    let with_fields = simulate_tp_production(&TradingPostProductionInput {
+        capacity: 8,
+        speed100: 205,
+        ..input
+    });
+    assert_eq!(with_skill.stall_chance, with_fields.stall_chance);
+    assert_eq!(with_skill.total_lmd, with_fields.total_lmd);
+    assert_eq!(with_skill.total_gold, with_fields.total_gold);
+}
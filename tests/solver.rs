@@ -0,0 +1,90 @@
+use ak_riic_tools::trading_sim::*;
+
+/// Checks that `solver` found the value where the limit stops holding.
+fn assert_boundary(solver: &Solver, allowed: fn(&TradingPostProductionOutput) -> bool) -> i32 {
+    let solution = solver.solve().unwrap().expect("the limit can be met");
+    assert!(!solution.at_range_end);
+    assert!(allowed(solution.output.as_ref().unwrap()));
+    let mut input = solver.base.clone();
+    let value = solution.value;
+    let next = match solver.field {
+        SweepField::Capacity => value - 1,
+        _ => value + 1,
+    };
+    match solver.field {
+        SweepField::Speed => input.speed100 = next,
+        SweepField::DurationMinutes => input.duration_minutes = next,
+        SweepField::Capacity => input.capacity = next,
+    }
+    assert!(
+        !allowed(&simulate_tp_production(&input)),
+        "{} is allowed",
+        next
+    );
+    value
+}
+
+#[test]
+fn finds_the_longest_duration_under_a_stall_chance() {
+    let solver = Solver {
+        base: TradingPostProductionInput {
+            capacity: 6,
+            speed100: 260,
+            jaye_phase: HighRarityOperatorPhase::E0,
+            ..Default::default()
+        },
+        field: SweepField::DurationMinutes,
+        limit: StallLimit::Chance(5.0),
+    };
+    let minutes = assert_boundary(&solver, |output| output.stall_chance <= 5.0);
+    assert!((60..1440).contains(&minutes), "{}", minutes);
+}
+
+#[test]
+fn finds_the_highest_speed_under_an_average_stall_time() {
+    let solver = Solver {
+        base: TradingPostProductionInput {
+            duration_minutes: 720,
+            ..Default::default()
+        },
+        field: SweepField::Speed,
+        limit: StallLimit::AverageTime(10.0),
+    };
+    let speed = assert_boundary(&solver, |output| output.average_stall_time <= 10.0);
+    assert!((200..300).contains(&speed), "{}", speed);
+}
+
+#[test]
+fn reports_limits_beyond_the_range() {
+    let base = TradingPostProductionInput {
+        duration_minutes: 240,
+        ..Default::default()
+    };
+    let lenient = Solver {
+        base: base.clone(),
+        field: SweepField::Capacity,
+        limit: StallLimit::Chance(100.0),
+    }
+    .solve()
+    .unwrap()
+    .unwrap();
+    assert_eq!(lenient.value, 1);
+    assert!(lenient.at_range_end);
+
+    let impossible = Solver {
+        base: base.clone(),
+        field: SweepField::Capacity,
+        limit: StallLimit::Chance(-1.0),
+    };
+    assert!(impossible.solve().unwrap().is_none());
+
+    let invalid = Solver {
+        base: TradingPostProductionInput {
+            speed100: 0,
+            ..base
+        },
+        field: SweepField::DurationMinutes,
+        limit: StallLimit::Chance(5.0),
+    };
+    assert_eq!(invalid.solve().err(), Some(SimError::NonPositiveSpeed(0.0)));
+}
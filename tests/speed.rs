@@ -0,0 +1,109 @@
+use ak_riic_tools::trading_sim::*;
+
+fn input(speed100: i32, speed_breakdown: Vec<SpeedContribution>) -> TradingPostProductionInput {
+    TradingPostProductionInput {
+        capacity: 38,
+        speed100,
+        speed_breakdown: SpeedBreakdown(speed_breakdown),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn breakdown_adds_then_multiplies() {
+    let breakdown = SpeedBreakdown(vec![
+        SpeedContribution::add("Base", 100.0),
+        SpeedContribution::multiply("Morale", 1.1),
+        SpeedContribution::add("Proviso", 50.0),
+    ]);
+    assert!((breakdown.speed() - 165.0).abs() < 1e-9);
+    assert_eq!(
+        breakdown.to_string(),
+        "+100% Base ×1.1 Morale +50% Proviso = 165%"
+    );
+}
+
+#[test]
+fn breakdown_replaces_the_speed() {
+    let whole = simulate_tp_production(&input(180, vec![]));
+    let broken_down = simulate_tp_production(&input(
+        999,
+        vec![
+            SpeedContribution::add("Base", 100.0),
+            SpeedContribution::add("Operators", 80.0),
+        ],
+    ));
+    assert_eq!(whole.total_lmd, broken_down.total_lmd);
+
+    // fractional speeds fall between the whole percents around them
+    let fractional = simulate_tp_production(&input(
+        0,
+        vec![
+            SpeedContribution::add("Base", 100.0),
+            SpeedContribution::add("Operators", 103.4),
+        ],
+    ));
+    let slower = simulate_tp_production(&input(203, vec![]));
+    let faster = simulate_tp_production(&input(204, vec![]));
+    assert!(slower.total_lmd < fractional.total_lmd);
+    assert!(fractional.total_lmd < faster.total_lmd);
+}
+
+#[test]
+fn breakdown_must_add_up_to_some_speed() {
+    let error = try_simulate(&input(
+        200,
+        vec![SpeedContribution::multiply("Morale", 1.2)],
+    ))
+    .err();
+    assert_eq!(error, Some(SimError::NonPositiveSpeed(0.0)));
+}
+
+fn scheduled(speed100: i32, speed_schedule: Vec<SpeedChange>) -> TradingPostProductionInput {
+    TradingPostProductionInput {
+        speed_schedule,
+        ..input(speed100, vec![])
+    }
+}
+
+fn change(from_minutes: i32, speed100: f64) -> SpeedChange {
+    SpeedChange {
+        from_minutes,
+        speed100,
+    }
+}
+
+#[test]
+fn schedule_that_keeps_the_speed_changes_nothing() {
+    let constant = simulate_tp_production(&input(180, vec![]));
+    let unchanged = simulate_tp_production(&scheduled(180, vec![change(360, 180.0)]));
+    assert_eq!(constant.total_lmd, unchanged.total_lmd);
+    assert!((constant.average_speed - 180.0).abs() < 1e-9);
+
+    // a change as the shift starts replaces the speed outright
+    let from_start = simulate_tp_production(&scheduled(999, vec![change(0, 180.0)]));
+    assert_eq!(constant.total_lmd, from_start.total_lmd);
+}
+
+#[test]
+fn slowing_down_mid_shift() {
+    let fast = simulate_tp_production(&input(200, vec![]));
+    let slow = simulate_tp_production(&input(120, vec![]));
+    let slowed = simulate_tp_production(&scheduled(200, vec![change(360, 120.0)]));
+    assert!(slow.total_lmd < slowed.total_lmd && slowed.total_lmd < fast.total_lmd);
+    // about half a shift at each speed
+    let halfway = (fast.total_lmd + slow.total_lmd) / 2.0;
+    assert!((slowed.total_lmd - halfway).abs() < 0.02 * halfway);
+
+    let speed = &slowed.timeline.speed;
+    assert_eq!(speed.len(), 721);
+    assert!((speed[359] - 200.0).abs() < 1e-9);
+    assert!((speed[360] - 120.0).abs() < 1e-9);
+    assert!((slowed.average_speed - 160.0).abs() < 1e-9);
+}
+
+#[test]
+fn scheduled_speeds_must_be_positive() {
+    let error = try_simulate(&scheduled(200, vec![change(60, -10.0)])).err();
+    assert_eq!(error, Some(SimError::NonPositiveSpeed(-10.0)));
+}
@@ -0,0 +1,149 @@
+use ak_riic_tools::trading_sim::*;
+
+fn sweep() -> ParameterSweep {
+    ParameterSweep {
+        base: TradingPostProductionInput {
+            collection_minutes: vec![240],
+            ..Default::default()
+        },
+        axes: vec![
+            SweepAxis::range(SweepField::Speed, 180, 260, 40),
+            SweepAxis {
+                field: SweepField::DurationMinutes,
+                values: vec![480, 960],
+            },
+            SweepAxis {
+                field: SweepField::Capacity,
+                values: vec![0, 8],
+            },
+        ],
+    }
+}
+
+#[test]
+fn runs_every_combination_in_order() {
+    let result = sweep().run();
+    assert_eq!(
+        result.fields,
+        [
+            SweepField::Speed,
+            SweepField::DurationMinutes,
+            SweepField::Capacity
+        ]
+    );
+    let values: Vec<Vec<i32>> = result.rows.iter().map(|row| row.values.clone()).collect();
+    assert_eq!(values.len(), 12);
+    assert_eq!(values[0], [180, 480, 0]);
+    assert_eq!(values[1], [180, 480, 8]);
+    assert_eq!(values[2], [180, 960, 0]);
+    assert_eq!(values[11], [260, 960, 8]);
+
+    for row in result.rows.iter() {
+        let input = TradingPostProductionInput {
+            speed100: row.values[0],
+            duration_minutes: row.values[1],
+            capacity: row.values[2],
+            collection_minutes: vec![240],
+            ..Default::default()
+        };
+        match (&row.output, try_simulate(&input)) {
+            (Ok(swept), Ok(single)) => {
+                assert_eq!(swept.total_lmd, single.total_lmd);
+                assert_eq!(swept.stall_chance, single.stall_chance);
+            }
+            (Err(swept), Err(single)) => assert_eq!(*swept, single),
+            _ => panic!("sweep and single run disagree on {:?}", row.values),
+        }
+    }
+}
+
+#[test]
+fn csv_has_a_line_per_combination() {
+    let csv = sweep().run().to_csv();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines.len(), 13);
+    assert!(lines[0].starts_with("speed100,duration_minutes,capacity,stall_chance,"));
+    assert!(lines[0].ends_with(",interval_2_end_minutes,interval_2_stall_chance,error"));
+    // both collection intervals of a valid row are filled in
+    let valid: Vec<&str> = lines[2].split(',').collect();
+    assert_eq!(valid[..3], ["180", "480", "8"]);
+    assert_eq!(valid[valid.len() - 3], "480");
+    assert_eq!(valid[valid.len() - 1], "");
+    // the error message has a comma in it, so it is quoted
+    assert!(lines[1].starts_with("180,480,0,,"));
+    assert!(lines[1].ends_with("\"order limit after skills must be above 0, not 0\""));
+}
+
+#[test]
+fn shift_lengths_surround_the_current_speed() {
+    let sweep = ParameterSweep::shift_lengths(TradingPostProductionInput {
+        speed100: 50,
+        ..Default::default()
+    });
+    assert_eq!(sweep.axes[0].field, SweepField::Speed);
+    assert_eq!(sweep.axes[0].values, [5, 20, 35, 50, 65, 80, 95, 110]);
+    assert_eq!(sweep.axes[1].field, SweepField::DurationMinutes);
+    assert_eq!(sweep.axes[1].values.first(), Some(&120));
+    assert_eq!(sweep.axes[1].values.last(), Some(&1440));
+}
+
+#[test]
+fn reports_progress_and_can_be_cancelled() {
+    let sweep = ParameterSweep {
+        base: Default::default(),
+        axes: vec![SweepAxis::range(SweepField::Speed, 100, 300, 100)],
+    };
+    let mut reports = vec![];
+    let result = sweep
+        .run_with_progress(&mut |fraction| {
+            reports.push(fraction);
+            true
+        })
+        .unwrap();
+    assert_eq!(reports, [1.0 / 3.0, 2.0 / 3.0, 1.0]);
+    // timelines are left out
+    assert!(result
+        .rows
+        .iter()
+        .all(|row| row.output.as_ref().unwrap().timeline.lmd.is_empty()));
+
+    assert!(sweep.run_with_progress(&mut |_| false).is_none());
+}
+
+#[test]
+fn csv_has_the_gold_shortage_of_a_gold_supply() {
+    let columns = |csv: &str, row: usize| -> Vec<String> {
+        let lines: Vec<&str> = csv.lines().collect();
+        let header: Vec<&str> = lines[0].split(',').collect();
+        let cells: Vec<&str> = lines[row].split(',').collect();
+        ["wait_chance", "delayed_lmd", "lost_lmd"]
+            .iter()
+            .map(|name| {
+                let index = header.iter().position(|column| column == name).unwrap();
+                cells[index].to_string()
+            })
+            .collect()
+    };
+
+    // without a supply the columns are there, but empty
+    let csv = sweep().run().to_csv();
+    assert_eq!(columns(&csv, 2), ["", "", ""]);
+
+    let mut sweep = sweep();
+    sweep.base.gold_supply = Some(GoldSupply {
+        stock: 0,
+        per_hour: 0.5,
+    });
+    let result = sweep.run();
+    let output = result.rows[1].output.as_ref().unwrap();
+    let shortage = output.gold_shortage.as_ref();
+    let shortage = shortage.expect("a gold supply gives a gold shortage");
+    let expected = [
+        shortage.wait_chance,
+        shortage.delayed_lmd,
+        shortage.lost_lmd,
+    ];
+    let expected: Vec<String> = expected.iter().map(ToString::to_string).collect();
+    assert_eq!(columns(&result.to_csv(), 2), expected);
+    assert!(shortage.wait_chance > 0.0);
+}
@@ -0,0 +1,115 @@
+use ak_riic_tools::trading_sim::*;
+
+fn assert_close(name: &str, actual: f64, expected: f64) {
+    assert!(
+        (actual - expected).abs() <= 1e-9 * (1.0 + expected.abs()),
+        "{}: {} vs {}",
+        name,
+        actual,
+        expected
+    );
+}
+
+fn assert_consistent(output: &TradingPostProductionOutput, duration_minutes: usize) {
+    let timeline = &output.timeline;
+    assert_eq!(timeline.stall_chance.len(), duration_minutes + 1);
+    assert_eq!(timeline.lmd.len(), duration_minutes + 1);
+    assert_eq!(timeline.stored.len(), duration_minutes + 1);
+    for (minute, chances) in timeline.stored.iter().enumerate() {
+        assert_close(
+            &format!("stored order chances at minute {}", minute),
+            chances.iter().sum(),
+            100.0,
+        );
+    }
+    assert!(timeline
+        .stall_chance
+        .windows(2)
+        .all(|w| w[0] <= w[1] + 1e-9));
+    assert_close(
+        "stall chance at the end",
+        *timeline.stall_chance.last().unwrap(),
+        output.stall_chance,
+    );
+    // only the order in progress at the end is missing
+    assert!(*timeline.lmd.last().unwrap() <= output.total_lmd + 1e-6);
+}
+
+#[test]
+fn stall_chance_by_minute_is_that_of_a_shift_ending_then() {
+    let input = TradingPostProductionInput {
+        duration_minutes: 900,
+        capacity: 6,
+        speed100: 260,
+        jaye_phase: HighRarityOperatorPhase::E0,
+        start_state: TradingPostStartState {
+            stored_orders: [1, 0, 0],
+            minutes_left: Some(40),
+        },
+        ..Default::default()
+    };
+    let output = simulate_tp_production(&input);
+    assert_consistent(&output, 900);
+    for minutes in [120, 360, 455, 700] {
+        let shorter = simulate_tp_production(&TradingPostProductionInput {
+            duration_minutes: minutes as i32,
+            ..input.clone()
+        });
+        assert_close(
+            &format!("stall chance at minute {}", minutes),
+            output.timeline.stall_chance[minutes],
+            shorter.stall_chance,
+        );
+    }
+}
+
+#[test]
+fn collections_empty_the_post() {
+    let output = simulate_tp_production(&TradingPostProductionInput {
+        duration_minutes: 720,
+        capacity: 6,
+        speed100: 240,
+        collection_minutes: vec![360],
+        ..Default::default()
+    });
+    assert_consistent(&output, 720);
+    let stored = &output.timeline.stored;
+    // all but empty at the start and right after the collection, but not before it
+    assert!(stored[0][0] > 99.9);
+    assert!(stored[359][0] < 1.0);
+    assert!(stored[360][0] > 99.9);
+    assert!(output.timeline.lmd.windows(2).all(|w| w[0] <= w[1] + 1e-9));
+}
+
+#[test]
+fn day_timeline_runs_through_every_shift() {
+    let day = simulate_tp_day(&[
+        TradingPostProductionInput {
+            duration_minutes: 480,
+            capacity: 6,
+            speed100: 260,
+            ..Default::default()
+        },
+        TradingPostProductionInput {
+            duration_minutes: 600,
+            capacity: 8,
+            speed100: 200,
+            collection_minutes: vec![0],
+            ..Default::default()
+        },
+    ]);
+    assert_consistent(&day.shifts[0], 480);
+    assert_consistent(&day.shifts[1], 600);
+    assert_consistent(&day.day, 1080);
+    // the second shift starts where the first one ends
+    assert_close(
+        "day LMD at the end of the first shift",
+        day.day.timeline.lmd[480],
+        day.shifts[0].timeline.lmd[480],
+    );
+    assert_close(
+        "day LMD at the end",
+        day.day.timeline.lmd[1080],
+        day.shifts[0].total_lmd + day.shifts[1].timeline.lmd[600],
+    );
+}
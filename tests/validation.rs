@@ -0,0 +1,106 @@
+use ak_riic_tools::trading_sim::*;
+
+fn error_of(input: TradingPostProductionInput) -> SimError {
+    try_simulate(&input).err().expect("input is rejected")
+}
+
+#[test]
+fn valid_input_simulates_as_usual() {
+    let input = TradingPostProductionInput {
+        jaye_phase: HighRarityOperatorPhase::E2,
+        ..Default::default()
+    };
+    let output = try_simulate(&input).expect("input is valid");
+    assert_eq!(output.total_lmd, simulate_tp_production(&input).total_lmd);
+}
+
+#[test]
+fn rejects_inputs_that_cannot_be_simulated() {
+    assert_eq!(
+        error_of(TradingPostProductionInput {
+            duration_minutes: 0,
+            ..Default::default()
+        }),
+        SimError::NonPositiveDuration(0)
+    );
+    assert_eq!(
+        error_of(TradingPostProductionInput {
+            speed100: 0,
+            ..Default::default()
+        }),
+        SimError::NonPositiveSpeed(0.0)
+    );
+    assert_eq!(
+        error_of(TradingPostProductionInput {
+            capacity: 0,
+            ..Default::default()
+        }),
+        SimError::NonPositiveCapacity(0)
+    );
+    assert_eq!(
+        error_of(TradingPostProductionInput {
+            start_state: TradingPostStartState {
+                stored_orders: [0, -1, 0],
+                minutes_left: None,
+            },
+            ..Default::default()
+        }),
+        SimError::NegativeStartState
+    );
+}
+
+#[test]
+fn rejects_game_data_that_cannot_be_simulated() {
+    let game_data = TradingPostGameData::bundled();
+    // these used to simulate to no LMD, or to NaN
+    for order_duration_seconds in [[0, 0, 0], [-100, 12600, 16560]] {
+        assert_eq!(
+            error_of(TradingPostProductionInput {
+                game_data: TradingPostGameData {
+                    order_duration_seconds,
+                    ..game_data.clone()
+                },
+                ..Default::default()
+            }),
+            SimError::InvalidGameData(GameDataError::NonPositiveOrderDuration)
+        );
+    }
+    let error = error_of(TradingPostProductionInput {
+        game_data: TradingPostGameData {
+            order_weights: [[(0.0, 0.0); 3]; 3],
+            ..game_data
+        },
+        ..Default::default()
+    });
+    assert_eq!(
+        error,
+        SimError::InvalidGameData(GameDataError::InvalidOrderWeights(1))
+    );
+    assert!(error.to_string().starts_with("invalid game data: "));
+}
+
+#[test]
+fn rejects_skills_that_leave_the_post_unable_to_work() {
+    // E2 Jaye holds 6 fewer orders
+    assert_eq!(
+        error_of(TradingPostProductionInput {
+            capacity: 6,
+            jaye_phase: HighRarityOperatorPhase::E2,
+            ..Default::default()
+        }),
+        SimError::NonPositiveCapacity(0)
+    );
+    // E0 Jaye slows the post down to a halt before it fills up
+    assert_eq!(
+        error_of(TradingPostProductionInput {
+            capacity: 10,
+            speed100: 28,
+            jaye_phase: HighRarityOperatorPhase::E0,
+            ..Default::default()
+        }),
+        SimError::StalledBySkills {
+            stored: 7,
+            speed100: 0.0
+        }
+    );
+}